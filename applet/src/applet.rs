@@ -35,6 +35,7 @@ pub enum Message {
     FetchStatus,
     StatusLoaded(Result<DaemonStatus, String>),
     IconClicked,
+    SyncNow,
 }
 
 impl OneDriveApplet {
@@ -125,6 +126,12 @@ impl cosmic::Application for OneDriveApplet {
                 let _ = std::process::Command::new("onedrive-sync-ui").spawn();
                 Task::none()
             }
+
+            Message::SyncNow => {
+                let sync_now = with_dbus_client(|client| async move { client.sync_now().await });
+                // Refresh the icon once the daemon has picked up the request
+                Task::perform(sync_now, |_| cosmic::Action::App(Message::FetchStatus))
+            }
         }
     }
 
@@ -145,8 +152,17 @@ impl cosmic::Application for OneDriveApplet {
             .class(cosmic::theme::Button::AppletIcon)
             .on_press(Message::IconClicked);
 
-        // Wrap button in container that fills the entire applet area
-        container(button)
+        // Right click syncs now as well
+        let button = cosmic::widget::mouse_area(button).on_right_press(Message::SyncNow);
+        let sync_button = cosmic::widget::button::text("Sync now").on_press(Message::SyncNow);
+        let content = cosmic::widget::row()
+            .spacing(4)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(button)
+            .push(sync_button);
+
+        // Wrap the buttons in a container that fills the entire applet area
+        container(content)
             .width(Length::Fill)
             .height(Length::Fill)
             .center_x(Length::Fill)
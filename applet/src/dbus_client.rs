@@ -82,6 +82,19 @@ impl DbusClient {
         Ok(status)
     }
 
+    /// Start a sync cycle now; false if one is already running
+    pub async fn sync_now(&self) -> Result<bool> {
+        info!("Requesting a sync cycle");
+        let proxy = self.get_proxy().await?;
+
+        let started = proxy
+            .call_method("SyncNow", &())
+            .await?
+            .body()
+            .deserialize::<bool>()?;
+        Ok(started)
+    }
+
     async fn get_proxy(&self) -> Result<Proxy<'_>, anyhow::Error> {
         let proxy = Proxy::new(&self.connection, DBUS_SERVICE, DBUS_PATH, DBUS_INTERFACE).await?;
         Ok(proxy)
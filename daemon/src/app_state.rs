@@ -4,12 +4,23 @@ use anyhow::{Context, Result};
 use onedrive_sync_lib::config::ProjectConfig;
 
 use crate::{
-    auth::onedrive_auth::OneDriveAuth, connectivity::ConnectivityChecker,
-    file_manager::DefaultFileManager, message_broker::MessageBroker,
+    auth::onedrive_auth::OneDriveAuth, connectivity::{ConnectivityChecker, ConnectivityStatus},
+    file_manager::DefaultFileManager, fuse::kernel_cache::KernelCache, fuse::locks::FileLockManager, message_broker::MessageBroker,
+    onedrive_service::bandwidth::BandwidthLimiter,
     onedrive_service::onedrive_client::{OneDriveClient, OneDriveClientTrait}, persistency::PersistencyManager,
-    
+    sync::selective_sync::{SyncRuleSet, SyncRulesCache}, tasks::change_notifications::SyncTrigger,
+    tasks::transfers::TransferTracker,
 };
 
+/// How the daemon runs, from the command line flags and the settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunMode {
+    /// The mount rejects every change (EROFS), nothing is queued for upload
+    pub read_only: bool,
+    /// No network use: only downloaded files and stored metadata are served
+    pub offline: bool,
+}
+
 /// Application state containing all shared components
 #[derive(Clone)]
 pub struct AppState {
@@ -25,7 +36,22 @@ pub struct AppState {
     pub auth: Arc<OneDriveAuth>,
     /// File manager
     pub file_manager: Arc<DefaultFileManager>,
-
+    /// Advisory locks taken on the mount
+    pub file_locks: Arc<FileLockManager>,
+    /// Kernel cache invalidation for the mount
+    pub kernel_cache: Arc<KernelCache>,
+    /// Read-only and offline modes
+    pub run_mode: RunMode,
+    /// Selective sync rules compiled from the settings
+    pub sync_rules: Arc<SyncRulesCache>,
+    /// Running downloads and uploads
+    pub transfers: Arc<TransferTracker>,
+    /// Upload and download rate limits
+    pub bandwidth: Arc<BandwidthLimiter>,
+    /// Wakes the sync task early, e.g. on a change notification
+    pub sync_trigger: Arc<SyncTrigger>,
+    /// Held while a sync cycle runs, so cycles never overlap
+    pub sync_running: Arc<tokio::sync::Mutex<()>>,
     
     
 }
@@ -55,6 +81,11 @@ impl AppState {
             .context("Failed to create authentication manager")?;
         let auth_arc = Arc::new(auth);
 
+        // Initialize bandwidth limits
+        let bandwidth = Arc::new(BandwidthLimiter::new(
+            &project_config_arc.settings.read().await.transfer_config,
+        ));
+
         // Initialize file manager
         let file_manager = Arc::new(DefaultFileManager::new(project_config_arc.clone()).await?);
 
@@ -67,8 +98,15 @@ impl AppState {
             connectivity_checker: Arc::new(connectivity_checker),
             onedrive_client,
             auth: auth_arc,
-            file_manager
-            
+            file_manager,
+            file_locks: Arc::new(FileLockManager::new()),
+            kernel_cache: Arc::new(KernelCache::new()),
+            run_mode: RunMode::default(),
+            sync_rules: Arc::new(SyncRulesCache::new()),
+            transfers: Arc::new(TransferTracker::new()),
+            bandwidth,
+            sync_trigger: Arc::new(SyncTrigger::new()),
+            sync_running: Arc::new(tokio::sync::Mutex::new(())),
         })
     }
 
@@ -95,9 +133,14 @@ impl AppState {
             .context("Failed to create authentication manager")?;
         let auth_arc = Arc::new(auth);
 
+        // Initialize bandwidth limits, adjustable at runtime over D-Bus
+        let bandwidth = Arc::new(BandwidthLimiter::new(
+            &project_config_arc.settings.read().await.transfer_config,
+        ));
+
         // Initialize OneDrive client
-        let onedrive_client =
-            OneDriveClient::new(auth_arc.clone()).context("Failed to create OneDrive client")?;
+        let onedrive_client = OneDriveClient::new(auth_arc.clone(), bandwidth.clone())
+            .context("Failed to create OneDrive client")?;
 
         // Initialize file manager
         let file_manager = Arc::new(DefaultFileManager::new(project_config_arc.clone()).await?);
@@ -112,8 +155,14 @@ impl AppState {
             onedrive_client: Arc::new(onedrive_client) as Arc<dyn OneDriveClientTrait>,
             auth: auth_arc,
             file_manager,
-
-            
+            file_locks: Arc::new(FileLockManager::new()),
+            kernel_cache: Arc::new(KernelCache::new()),
+            run_mode: RunMode::default(),
+            sync_rules: Arc::new(SyncRulesCache::new()),
+            transfers: Arc::new(TransferTracker::new()),
+            bandwidth,
+            sync_trigger: Arc::new(SyncTrigger::new()),
+            sync_running: Arc::new(tokio::sync::Mutex::new(())),
         })
     }
 
@@ -147,6 +196,61 @@ impl AppState {
         &self.file_manager
     }
 
+    /// Get a reference to the advisory lock table
+    pub fn file_locks(&self) -> &FileLockManager {
+        &self.file_locks
+    }
+
+    /// Get a reference to the kernel cache invalidation
+    pub fn kernel_cache(&self) -> &KernelCache {
+        &self.kernel_cache
+    }
+
+    /// Get a reference to the running transfers
+    pub fn transfers(&self) -> &Arc<TransferTracker> {
+        &self.transfers
+    }
+
+    /// Get a reference to the upload and download rate limits
+    pub fn bandwidth(&self) -> &BandwidthLimiter {
+        &self.bandwidth
+    }
+
+    /// Get a reference to the sync trigger
+    pub fn sync_trigger(&self) -> &SyncTrigger {
+        &self.sync_trigger
+    }
+
+    /// Get the read-only and offline modes
+    pub fn run_mode(&self) -> RunMode {
+        self.run_mode
+    }
+
+    /// Get the selective sync rules of the current settings
+    pub async fn sync_rules(&self) -> Arc<SyncRuleSet> {
+        let settings = self.config().settings.read().await;
+        self.sync_rules.get(&settings)
+    }
+
+    /// Check for usable tokens; offline mode only looks at the stored ones
+    /// since refreshing them needs the network
+    pub async fn is_authenticated(&self) -> bool {
+        if self.run_mode.offline {
+            self.auth.load_tokens().is_ok()
+        } else {
+            self.auth.get_valid_token().await.is_ok()
+        }
+    }
+
+    /// Check the connectivity, without probing the network in offline mode
+    pub async fn connectivity_status(&self) -> ConnectivityStatus {
+        if self.run_mode.offline {
+            ConnectivityStatus::Offline
+        } else {
+            self.connectivity_checker.check_connectivity().await
+        }
+    }
+
     
 }
 
@@ -1,7 +1,9 @@
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use reqwest::Client;
+use std::sync::Mutex;
 use std::time::Duration;
+use tokio::sync::OnceCell;
 use tokio::time::timeout;
 
 /// Default timeout for connectivity checks
@@ -17,6 +19,9 @@ const INTERNET_ENDPOINTS: &[&str] = &[
 /// Microsoft Graph API endpoints for connectivity testing
 const GRAPH_ENDPOINTS: &[&str] = &["https://graph.microsoft.com/v1.0/"];
 
+/// NetworkManager's `NMMetered` values for a metered connection (yes, guess-yes)
+const NM_METERED_YES: &[u32] = &[1, 3];
+
 /// Connectivity status enumeration
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConnectivityStatus {
@@ -38,10 +43,23 @@ impl std::fmt::Display for ConnectivityStatus {
     }
 }
 
+/// Whether the machine should go easy on bulk transfers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetworkConditions {
+    /// NetworkManager reports the primary connection as metered
+    pub metered: bool,
+    /// UPower reports the machine running on battery
+    pub on_battery: bool,
+}
+
 /// Network connectivity checker for OneDrive synchronization
 pub struct ConnectivityChecker {
     http_client: Client,
     timeout_duration: Duration,
+    /// Conditions of the last `refresh_network_conditions`
+    network_conditions: Mutex<NetworkConditions>,
+    /// System bus connection, opened by the first `refresh_network_conditions`
+    system_bus: OnceCell<zbus::Connection>,
 }
 
 impl ConnectivityChecker {
@@ -50,7 +68,94 @@ impl ConnectivityChecker {
         Self {
             http_client: Client::new(),
             timeout_duration: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            network_conditions: Mutex::new(NetworkConditions::default()),
+            system_bus: OnceCell::new(),
+        }
+    }
+
+    /// Last known metered and battery state
+    pub fn network_conditions(&self) -> NetworkConditions {
+        *self.network_conditions.lock().unwrap()
+    }
+
+    /// Override the metered and battery state, returns true if it changed
+    pub fn set_network_conditions(&self, conditions: NetworkConditions) -> bool {
+        let mut current = self.network_conditions.lock().unwrap();
+        let changed = *current != conditions;
+        *current = conditions;
+        changed
+    }
+
+    /// Read the metered and battery state from NetworkManager and UPower on the system bus.
+    /// A missing service counts as not metered / on AC power. Returns true if it changed.
+    pub async fn refresh_network_conditions(&self) -> bool {
+        let connection = match self.system_bus.get_or_try_init(zbus::Connection::system).await {
+            Ok(connection) => connection,
+            Err(e) => {
+                debug!("System bus not available, assuming unmetered AC power: {}", e);
+                return self.set_network_conditions(NetworkConditions::default());
+            }
+        };
+
+        let metered = match Self::read_system_property::<u32>(
+            connection,
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        )
+        .await
+        {
+            Ok(value) => NM_METERED_YES.contains(&value),
+            Err(e) => {
+                debug!("Failed to read NetworkManager Metered property: {}", e);
+                false
+            }
+        };
+        let on_battery = match Self::read_system_property::<bool>(
+            connection,
+            "org.freedesktop.UPower",
+            "/org/freedesktop/UPower",
+            "org.freedesktop.UPower",
+            "OnBattery",
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                debug!("Failed to read UPower OnBattery property: {}", e);
+                false
+            }
+        };
+
+        let conditions = NetworkConditions { metered, on_battery };
+        let changed = self.set_network_conditions(conditions);
+        if changed {
+            info!(
+                "🔋 Network conditions changed: metered={}, on_battery={}",
+                conditions.metered, conditions.on_battery
+            );
         }
+        changed
+    }
+
+    /// Read a property of a system bus service
+    async fn read_system_property<T>(
+        connection: &zbus::Connection,
+        destination: &'static str,
+        path: &'static str,
+        interface: &'static str,
+        property: &'static str,
+    ) -> Result<T>
+    where
+        T: TryFrom<zbus::zvariant::OwnedValue>,
+        T::Error: Into<zbus::Error>,
+    {
+        let proxy = zbus::Proxy::new(connection, destination, path, interface).await?;
+        let value = timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS), proxy.get_property::<T>(property))
+            .await
+            .map_err(|_| anyhow::anyhow!("Timeout reading {}.{}", interface, property))??;
+        Ok(value)
     }
 
     /// Check overall connectivity status
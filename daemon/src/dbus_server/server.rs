@@ -5,12 +5,21 @@ use std::{fs, sync::Arc};
 use log::{debug, error, info, warn};
 use onedrive_sync_lib::dbus::types::{DaemonStatus, SyncQueueItem, SyncStatus, UserProfile};
 use onedrive_sync_lib::dbus::types::MediaItem;
+use onedrive_sync_lib::dbus::types::{
+    SyncPathResult, SyncRuleMatch, SyncRules, TransferItem, TransferLimits, TransferWindowRule,
+};
+use onedrive_sync_lib::config::{SelectiveSync, TransferWindow, TransferWindowDirection};
 use zbus::interface;
 use zbus::object_server::SignalEmitter;
 use uuid;
 use anyhow::Result;
 
 use crate::file_manager::FileManager;
+use crate::sync::conflict_resolution::ConflictResolver;
+use crate::sync::selective_sync::{self, SyncRuleSet};
+use crate::sync::text_merge;
+use crate::tasks::delta_update::SyncCycle;
+use crate::tasks::transfers;
 
 pub struct ServiceImpl {
     app_state: Arc<crate::app_state::AppState>,
@@ -76,99 +85,19 @@ impl ServiceImpl {
             "DBus: resolve_conflict called for item {} with choice {:?}",
             conflicted_item_db_id, choice
         );
-        let processing_repo = self.app_state.persistency().processing_item_repository();
-
-        let conflicted_item = match processing_repo
-            .get_processing_item_by_id(conflicted_item_db_id)
-            .await
-        {
-            Ok(Some(item)) => item,
-            _ => {
-                return Err(zbus::fdo::Error::Failed(
-                    "Conflicted item not found".to_string(),
-                ))
-            }
-        };
-
-        let opposite_change_type = match conflicted_item.change_type {
-            ChangeType::Local => ChangeType::Remote,
-            ChangeType::Remote => ChangeType::Local,
-        };
-
-        let corresponding_item = processing_repo
-            .get_pending_processing_item_by_drive_item_id_and_change_type(
-                &conflicted_item.drive_item.id,
-                &opposite_change_type,
-            )
+        ConflictResolver::new(self.app_state.clone())
+            .resolve(conflicted_item_db_id, choice)
             .await
-            .map_err(|e| {
-                zbus::fdo::Error::Failed(format!("Failed to find corresponding item: {}", e))
-            })?
-            .ok_or_else(|| {
-                zbus::fdo::Error::Failed("Corresponding conflicted item not found".to_string())
-            })?;
-
-        let (winning_item, losing_item) = match (&choice, &conflicted_item.change_type) {
-            (UserChoice::KeepLocal, &ChangeType::Local) => (conflicted_item, corresponding_item),
-            (UserChoice::KeepLocal, &ChangeType::Remote) => (corresponding_item, conflicted_item),
-            (UserChoice::UseRemote, &ChangeType::Remote) => (conflicted_item, corresponding_item),
-            (UserChoice::UseRemote, &ChangeType::Local) => (corresponding_item, conflicted_item),
-        };
-
-        // Check if we need to transform the operation based on the conflict scenario
-        let transformation_result = self.transform_operation_after_conflict_resolution(
-            &winning_item, &losing_item, &choice
-        ).await.map_err(|e| zbus::fdo::Error::Failed(format!("Failed to transform operation: {}", e)))?;
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+    }
 
-        // Cancel the losing item
-        processing_repo
-            .update_status_by_id(losing_item.id.unwrap(), &ProcessingStatus::Cancelled)
+    /// Unified diff from the local to the remote version of a conflicted text file
+    async fn get_conflict_diff(&self, conflicted_item_db_id: i64) -> zbus::fdo::Result<String> {
+        debug!("DBus: get_conflict_diff called for item {}", conflicted_item_db_id);
+        ConflictResolver::new(self.app_state.clone())
+            .conflict_diff(conflicted_item_db_id)
             .await
-            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to cancel losing item: {}", e)))?;
-
-        // Apply transformation if needed
-        if let Some((new_operation, new_id, new_name)) = transformation_result {
-            info!("Transforming operation for conflict resolution: {} {:?} -> {} {:?}", 
-                  winning_item.drive_item.id, winning_item.change_operation, new_id, new_operation);
-            
-            // Update the winning item with transformed operation
-            let mut updated_item = winning_item.clone();
-            updated_item.change_operation = new_operation;
-            updated_item.drive_item.id = new_id.clone();
-            updated_item.status = ProcessingStatus::New; // Set status to New for processing
-            if let Some(name) = new_name {
-                updated_item.drive_item.name = Some(name);
-            }
-            
-            // Update the processing item in database
-            processing_repo
-                .update_processing_item(&updated_item)
-                .await
-                .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to update transformed item: {}", e)))?;
-
-            // Update DriveItemWithFuse if ID changed
-            if new_id != winning_item.drive_item.id {
-                let drive_item_with_fuse_repo = self.app_state.persistency().drive_item_with_fuse_repository();
-                drive_item_with_fuse_repo
-                    .update_onedrive_id(&winning_item.drive_item.id, &new_id)
-                    .await
-                    .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to update drive item ID: {}", e)))?;
-            }
-        } else {
-            // Re-queue the winning item by setting its status to New (original behavior)
-            processing_repo
-                .update_status_by_id(winning_item.id.unwrap(), &ProcessingStatus::New)
-                .await
-                .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to re-queue winning item: {}", e)))?;
-        }
-
-        info!(
-            "Conflict resolved for OneDrive item {}. Kept {} version.",
-            winning_item.drive_item.id,
-            winning_item.change_type.as_str()
-        );
-
-        Ok(())
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
     }
 
 
@@ -195,9 +124,9 @@ impl ServiceImpl {
         debug!("DBus: get_daemon_status called");
 
         // Get actual status from app state
-        let is_authenticated = self.app_state.auth().get_valid_token().await.is_ok();
+        let is_authenticated = self.app_state.is_authenticated().await;
         let is_connected = matches!(
-            self.app_state.connectivity().check_connectivity().await,
+            self.app_state.connectivity_status().await,
             crate::connectivity::ConnectivityStatus::Online
         );
 
@@ -225,12 +154,32 @@ impl ServiceImpl {
             .lines()
             .any(|line| line.split_whitespace().nth(1) == Some(p.to_str().unwrap_or_default()));
 
+        // Drive quota as cached by the last sync cycle
+        let quota = self
+            .app_state
+            .persistency()
+            .drive_quota_repository()
+            .get_quota()
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+
+        // Metered connection or battery holding back background transfers
+        let transfer_pause_reason = transfers::BulkTransferMode::current(&self.app_state)
+            .await
+            .reason();
+
         Ok(DaemonStatus {
             is_authenticated,
             is_connected,
             sync_status,
             has_conflicts,
             is_mounted,
+            quota_total: quota.total,
+            quota_used: quota.used,
+            quota_remaining: quota.remaining,
+            transfer_pause_reason,
         })
     }
 
@@ -301,6 +250,104 @@ impl ServiceImpl {
         Ok(sync_items)
     }
 
+    /// Downloads and uploads in flight with their progress
+    async fn get_transfers(&self) -> zbus::fdo::Result<Vec<TransferItem>> {
+        debug!("DBus: get_transfers called");
+        Ok(self
+            .app_state
+            .transfers()
+            .list()
+            .into_iter()
+            .map(|transfer| TransferItem {
+                transfer_id: transfer.id,
+                onedrive_id: transfer.drive_item_id,
+                name: transfer.name,
+                direction: transfer.direction.as_str().to_string(),
+                bytes_done: transfer.bytes_done,
+                bytes_total: transfer.bytes_total,
+            })
+            .collect())
+    }
+
+    /// Get the upload and download rate limits and the transfer windows
+    async fn get_transfer_limits(&self) -> zbus::fdo::Result<TransferLimits> {
+        debug!("DBus: get_transfer_limits called");
+        let settings = self.app_state.config().settings.read().await;
+        Ok(TransferLimits {
+            max_upload_bytes_per_sec: settings.transfer_config.max_upload_bytes_per_sec,
+            max_download_bytes_per_sec: settings.transfer_config.max_download_bytes_per_sec,
+            windows: settings
+                .sync_config
+                .transfer_windows
+                .iter()
+                .map(|window| TransferWindowRule {
+                    direction: format!("{:?}", window.direction),
+                    start: window.start.clone(),
+                    end: window.end.clone(),
+                    min_file_size: window.min_file_size,
+                })
+                .collect(),
+        })
+    }
+
+    /// Replace the rate limits and transfer windows; running transfers pick them up right away
+    async fn set_transfer_limits(&self, limits: TransferLimits) -> zbus::fdo::Result<()> {
+        debug!("DBus: set_transfer_limits called");
+        let mut windows = Vec::new();
+        for rule in limits.windows {
+            let direction = match rule.direction.as_str() {
+                "Upload" => TransferWindowDirection::Upload,
+                "Download" => TransferWindowDirection::Download,
+                "Both" => TransferWindowDirection::Both,
+                other => {
+                    return Err(zbus::fdo::Error::InvalidArgs(format!(
+                        "Invalid transfer direction: {}",
+                        other
+                    )))
+                }
+            };
+            for time in [&rule.start, &rule.end] {
+                transfers::parse_window_time(time)
+                    .map_err(|e| zbus::fdo::Error::InvalidArgs(format!("{:#}", e)))?;
+            }
+            windows.push(TransferWindow {
+                direction,
+                start: rule.start.trim().to_string(),
+                end: rule.end.trim().to_string(),
+                min_file_size: rule.min_file_size,
+            });
+        }
+
+        // Held until the new limits are saved, so concurrent changes are not lost
+        let mut settings = self.app_state.config().settings.write().await;
+        let mut updated = settings.clone();
+        updated.transfer_config.max_upload_bytes_per_sec = limits.max_upload_bytes_per_sec;
+        updated.transfer_config.max_download_bytes_per_sec = limits.max_download_bytes_per_sec;
+        updated.sync_config.transfer_windows = windows;
+        let config_path = self
+            .app_state
+            .config()
+            .project_dirs
+            .config_dir()
+            .join("settings.json");
+        if let Err(e) = updated.save_to_file(&config_path) {
+            error!("Failed to save settings: {}", e);
+            return Err(zbus::fdo::Error::Failed(format!("Failed to save settings: {}", e)));
+        }
+        *settings = updated;
+        let transfer_config = settings.transfer_config.clone();
+        drop(settings);
+
+        self.app_state.bandwidth().apply(&transfer_config);
+        // Downloads held back by a window may be allowed now
+        self.app_state.transfers().wake();
+        info!(
+            "🚦 Updated transfer limits: upload {} B/s, download {} B/s",
+            limits.max_upload_bytes_per_sec, limits.max_download_bytes_per_sec
+        );
+        Ok(())
+    }
+
     #[allow(dead_code)]
     async fn get_recent_logs(&self) -> zbus::fdo::Result<Vec<String>> {
         use std::fs::File;
@@ -374,6 +421,9 @@ impl ServiceImpl {
         if path.exists() {
             return Ok(path.to_string_lossy().to_string());
         }
+        if self.app_state.run_mode().offline {
+            return Err(zbus::fdo::Error::Failed("Thumbnail not available in offline mode".into()));
+        }
         let bytes = self
             .app_state
             .onedrive()
@@ -389,8 +439,6 @@ impl ServiceImpl {
     #[allow(dead_code)]
     async fn ensure_local_by_ino(&self, ino: u64) -> zbus::fdo::Result<String> {
         info!("DBus: ensure_local_by_ino called for ino: {}", ino);
-        use tokio::fs;
-        use std::path::PathBuf;
         let fm = self.app_state.file_manager();
         if let Some(existing) = fm.get_local_path_if_file_exists(ino) {
             return Ok(existing.to_string_lossy().to_string());
@@ -405,6 +453,9 @@ impl ServiceImpl {
         if item.is_folder() {
             return Err(zbus::fdo::Error::Failed("Requested inode is a folder".into()));
         }
+        if self.app_state.run_mode().offline {
+            return Err(zbus::fdo::Error::Failed("File is not downloaded and the daemon is offline".into()));
+        }
         let item = self.app_state
         .onedrive().get_item_by_id(&item.drive_item.id)
         .await
@@ -416,19 +467,11 @@ impl ServiceImpl {
                 error!("Missing download URL for item: {}", &item.id);
                 zbus::fdo::Error::Failed("Missing download URL".into())
             })?;
-        let id = item.id.to_string();
-        let filename = item.name.clone().unwrap_or(id.clone());
-        let dl = self
-            .app_state
-            .onedrive()
-            .download_file(download_url.as_str(), &id, filename.as_str())
-            .await
-            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to download file: {}", e)))?;
-
         let target_path = fm.get_local_dir().join(ino.to_string());
-        fm.save_downloaded_file_r(&dl, &target_path)
+        transfers::download_to_file(&self.app_state, download_url.as_str(), &item.id, &target_path)
             .await
-            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to save file: {}", e)))?;
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to download file: {:#}", e)))?;
+        text_merge::remember_base_from_file(&self.app_state, &item.id, item.ctag.as_deref(), &target_path).await;
 
         Ok(target_path.to_string_lossy().to_string())
     }
@@ -461,6 +504,24 @@ impl ServiceImpl {
             .clear_profile()
             .await
             .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to clear profile: {}", e)))?;
+        self.app_state
+            .persistency()
+            .drive_quota_repository()
+            .clear_quota()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to clear drive quota: {}", e)))?;
+        self.app_state
+            .persistency()
+            .pinned_item_repository()
+            .clear_all_items()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to clear pinned items: {}", e)))?;
+        self.app_state
+            .persistency()
+            .merge_base_repository()
+            .clear_all_items()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to clear merge bases: {}", e)))?;
 
         // Delete SQLite DB and token file
 
@@ -514,6 +575,8 @@ impl ServiceImpl {
         let mut settings_guard = self.app_state.config().settings.write().await;
 
         settings_guard.download_folders.push(normalized.clone());
+        drop(settings_guard);
+        let rules = self.app_state.sync_rules().await;
         // Query files and queue for download
         let drive_item_with_fuse_repo = self
             .app_state
@@ -527,6 +590,9 @@ impl ServiceImpl {
         {
             Ok(files) => {
                 for file in files {
+                    if !rules.is_included(&selective_sync::item_path(file.drive_item())) {
+                        continue;
+                    }
                     let local_path = file_manager.get_download_dir().join(&file.drive_item.id);
                     let _ = download_queue_repo
                         .add_to_download_queue(&file.drive_item.id, &local_path)
@@ -591,6 +657,154 @@ impl ServiceImpl {
         Ok(true)
     }
 
+    /// Get the selective sync include/exclude patterns
+    async fn get_sync_rules(&self) -> zbus::fdo::Result<SyncRules> {
+        debug!("DBus: get_sync_rules called");
+        let settings = self.app_state.config().settings.read().await;
+        Ok(SyncRules {
+            include: settings.selective_sync.include.clone(),
+            exclude: settings.selective_sync.exclude.clone(),
+            hide_excluded: settings.selective_sync.hide_excluded,
+        })
+    }
+
+    /// Replace the selective sync patterns and queue the files they newly include for download
+    async fn set_sync_rules(&self, rules: SyncRules) -> zbus::fdo::Result<()> {
+        debug!("DBus: set_sync_rules called");
+        let selective_sync = SelectiveSync {
+            include: rules.include,
+            exclude: rules.exclude,
+            hide_excluded: rules.hide_excluded,
+        };
+        // Held until the new rules are saved, so concurrent changes are not lost
+        let mut settings = self.app_state.config().settings.write().await;
+        let new_rules = SyncRuleSet::new(&settings.download_folders, &selective_sync)
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(format!("{:#}", e)))?;
+        let old_rules = SyncRuleSet::from_settings(&settings);
+
+        let mut updated = settings.clone();
+        updated.selective_sync = selective_sync;
+        let config_path = self
+            .app_state
+            .config()
+            .project_dirs
+            .config_dir()
+            .join("settings.json");
+        if let Err(e) = updated.save_to_file(&config_path) {
+            error!("Failed to save settings: {}", e);
+            return Err(zbus::fdo::Error::Failed(format!("Failed to save settings: {}", e)));
+        }
+        *settings = updated;
+        drop(settings);
+
+        let items = self
+            .app_state
+            .persistency()
+            .drive_item_with_fuse_repository()
+            .get_all_drive_items_with_fuse()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to query items: {}", e)))?;
+        let download_queue_repo = self.app_state.persistency().download_queue_repository();
+        let file_manager = self.app_state.file_manager();
+        for item in items.iter().filter(|item| !item.is_deleted()) {
+            let path = selective_sync::item_path(item.drive_item());
+            if old_rules.is_hidden(&path) != new_rules.is_hidden(&path) {
+                self.app_state.kernel_cache().invalidate_item(item);
+            }
+            if item.is_folder() || old_rules.is_included(&path) || !new_rules.is_included(&path) {
+                continue;
+            }
+            if file_manager
+                .get_local_path_if_file_exists(item.virtual_ino().unwrap_or(0))
+                .is_some()
+            {
+                continue;
+            }
+            let local_path = file_manager.get_download_dir().join(item.id());
+            if let Err(e) = download_queue_repo.add_to_download_queue(item.id(), &local_path).await {
+                warn!("⚠️ Failed to queue {} for download: {}", path, e);
+            }
+        }
+
+        info!("🎯 Updated selective sync rules");
+        Ok(())
+    }
+
+    /// Preview the items a set of selective sync rules includes or excludes
+    async fn preview_sync_rules(&self, rules: SyncRules) -> zbus::fdo::Result<Vec<SyncRuleMatch>> {
+        debug!("DBus: preview_sync_rules called");
+        let download_folders = self.app_state.config().settings.read().await.download_folders.clone();
+        let rule_set = SyncRuleSet::new(
+            &download_folders,
+            &SelectiveSync {
+                include: rules.include,
+                exclude: rules.exclude,
+                hide_excluded: rules.hide_excluded,
+            },
+        )
+        .map_err(|e| zbus::fdo::Error::InvalidArgs(format!("{:#}", e)))?;
+
+        let items = self
+            .app_state
+            .persistency()
+            .drive_item_with_fuse_repository()
+            .get_all_drive_items_with_fuse()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to query items: {}", e)))?;
+        let mut matches: Vec<SyncRuleMatch> = items
+            .iter()
+            // The root has no parent and is never matched
+            .filter(|item| !item.is_deleted() && item.drive_item().parent_reference.is_some())
+            .filter_map(|item| {
+                let path = selective_sync::item_path(item.drive_item());
+                let included = !item.is_folder() && rule_set.is_included(&path);
+                let excluded = rule_set.is_excluded(&path);
+                (included || excluded).then(|| SyncRuleMatch {
+                    hidden: rule_set.is_hidden(&path),
+                    path,
+                    is_folder: item.is_folder(),
+                    included,
+                    excluded,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(matches)
+    }
+
+    /// List the files created through the mount that are kept local by the ignore patterns
+    async fn get_ignored_items(&self) -> zbus::fdo::Result<Vec<SyncQueueItem>> {
+        debug!("DBus: get_ignored_items called");
+        let rules = self.app_state.sync_rules().await;
+        let items = self
+            .app_state
+            .persistency()
+            .drive_item_with_fuse_repository()
+            .get_all_drive_items_with_fuse()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to query items: {}", e)))?;
+
+        let mut ignored: Vec<SyncQueueItem> = items
+            .into_iter()
+            .filter(|item| !item.is_deleted() && !item.is_synced())
+            .filter(|item| rules.is_locally_ignored(&selective_sync::item_path(item.drive_item())))
+            .map(|item| SyncQueueItem {
+                onedrive_id: item.id().to_string(),
+                ino: item.virtual_ino().unwrap_or(0),
+                name: item.name().unwrap_or_default().to_string(),
+                path: item
+                    .drive_item()
+                    .parent_reference
+                    .as_ref()
+                    .and_then(|p| p.path.clone())
+                    .unwrap_or_default()
+                    .replace("/drive/root:", ""),
+            })
+            .collect();
+        ignored.sort_by(|a, b| (&a.path, &a.name).cmp(&(&b.path, &b.name)));
+        Ok(ignored)
+    }
+
     /// Toggle sync pause state
     #[allow(dead_code)]
     async fn toggle_sync_pause(&self) -> zbus::fdo::Result<bool> {
@@ -617,6 +831,55 @@ impl ServiceImpl {
         
         Ok(is_paused)
     }
+
+    /// Start a sync cycle now instead of waiting for the next tick. Returns false if a
+    /// cycle is already running; another one then follows right after it.
+    async fn sync_now(&self) -> zbus::fdo::Result<bool> {
+        debug!("DBus: sync_now called");
+        if self.app_state.run_mode().offline {
+            return Err(zbus::fdo::Error::Failed("Cannot sync while the daemon is offline".into()));
+        }
+        let started = SyncCycle::start_now(self.app_state.clone());
+        if !started {
+            // The sync task runs another cycle right after the running one
+            self.app_state.sync_trigger().trigger();
+        }
+        Ok(started)
+    }
+
+    /// Fetch the metadata of a path (e.g. "/Documents/Work") and everything below it again.
+    /// With `redownload`, its files are downloaded again as well.
+    async fn sync_path(&self, path: String, redownload: bool) -> zbus::fdo::Result<SyncPathResult> {
+        info!("DBus: sync_path called for {} (redownload: {})", path, redownload);
+        if self.app_state.run_mode().offline {
+            return Err(zbus::fdo::Error::Failed("Cannot sync while the daemon is offline".into()));
+        }
+
+        let repo = self.app_state.persistency().drive_item_with_fuse_repository();
+        let mut item = repo
+            .get_drive_item_with_fuse_by_virtual_ino(1)
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to query item: {}", e)))?
+            .ok_or_else(|| zbus::fdo::Error::Failed("Root folder not found".to_string()))?;
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            let parent_ino = item.virtual_ino().unwrap_or(1);
+            item = repo
+                .get_drive_item_with_fuse_by_parent_ino_and_name(parent_ino, name)
+                .await
+                .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to query item: {}", e)))?
+                .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("No item at {}", path)))?;
+        }
+
+        let result = SyncCycle::new(self.app_state.clone())
+            .sync_subtree(&item, redownload)
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Failed to sync {}: {}", path, e)))?;
+        Ok(SyncPathResult {
+            refreshed: result.refreshed,
+            changed: result.changed,
+            queued_downloads: result.queued_downloads,
+        })
+    }
 }
 
 impl ServiceImpl {
@@ -630,64 +893,57 @@ impl ServiceImpl {
         self.resolve_conflict(conflicted_item_db_id, choice).await
     }
 
-    /// Transform operation after conflict resolution based on the specific conflict scenario
-    async fn transform_operation_after_conflict_resolution(
-        &self,
-        winning_item: &crate::persistency::processing_item_repository::ProcessingItem,
-        losing_item: &crate::persistency::processing_item_repository::ProcessingItem,
-        choice: &UserChoice,
-    ) -> Result<Option<(ChangeOperation, String, Option<String>)>> {
-        match (choice, &winning_item.change_type, &winning_item.change_operation, &losing_item.change_operation) {
-            
-            
-            (UserChoice::KeepLocal, ChangeType::Local, ChangeOperation::Update, ChangeOperation::Delete) => {
-                let new_id = format!("local_{}", uuid::Uuid::new_v4());
-                info!("Local update on deleted remote → Create new: {} → {}", winning_item.drive_item.id, new_id);
-                Ok(Some((ChangeOperation::Create, new_id, None)))
-            }
-            
-            (UserChoice::KeepLocal, ChangeType::Local, ChangeOperation::Move, ChangeOperation::Delete) => {
-                let new_id = format!("local_{}", uuid::Uuid::new_v4());
-                info!("Local move on deleted remote → Create at target: {} → {}", winning_item.drive_item.id, new_id);
-                Ok(Some((ChangeOperation::Create, new_id, None)))
-            }
-            
-            (UserChoice::KeepLocal, ChangeType::Local, ChangeOperation::Rename, ChangeOperation::Delete) => {
-                let new_id = format!("local_{}", uuid::Uuid::new_v4());
-                info!("Local rename on deleted remote → Create with new name: {} → {}", winning_item.drive_item.id, new_id);
-                Ok(Some((ChangeOperation::Create, new_id, None)))
-            }
-            
-            
-            (UserChoice::UseRemote, ChangeType::Remote, ChangeOperation::Update, ChangeOperation::Delete) => {
-                info!("Remote update on deleted local → Restore from remote: {}", winning_item.drive_item.id);
-                Ok(Some((ChangeOperation::Create, winning_item.drive_item.id.clone(), None)))
-            }
-            
-            (UserChoice::UseRemote, ChangeType::Remote, ChangeOperation::Move, ChangeOperation::Delete) => {
-                info!("Remote move on deleted local → Create at new location: {}", winning_item.drive_item.id);
-                Ok(Some((ChangeOperation::Create, winning_item.drive_item.id.clone(), None)))
-            }
-            
-            
-            (UserChoice::KeepLocal, ChangeType::Local, ChangeOperation::Create, ChangeOperation::Create) => {
-                info!("Local create on existing remote → Overwrite remote: {}", losing_item.drive_item.id);
-                Ok(Some((ChangeOperation::Update, losing_item.drive_item.id.clone(), None)))
-            }
-            
-            
-            (UserChoice::KeepLocal, ChangeType::Remote, ChangeOperation::Move, _) |
-            (UserChoice::KeepLocal, ChangeType::Remote, ChangeOperation::Rename, _) => {
-                // This case requires identifying the conflicting local item and renaming it
-                // This is complex and should be handled in a separate enhancement
-                warn!("Remote rename/move conflict detected - complex scenario requiring additional implementation");
-                Ok(None)
-            }
-            
-            
-            _ => {
-                Ok(None)
-            }
-        }
+    /// Public wrapper for set_sync_rules for testing purposes
+    #[allow(dead_code)]
+    pub async fn set_sync_rules_for_test(&self, rules: SyncRules) -> zbus::fdo::Result<()> {
+        self.set_sync_rules(rules).await
+    }
+
+    /// Public wrapper for preview_sync_rules for testing purposes
+    #[allow(dead_code)]
+    pub async fn preview_sync_rules_for_test(&self, rules: SyncRules) -> zbus::fdo::Result<Vec<SyncRuleMatch>> {
+        self.preview_sync_rules(rules).await
+    }
+
+    /// Public wrapper for get_ignored_items for testing purposes
+    #[allow(dead_code)]
+    pub async fn get_ignored_items_for_test(&self) -> zbus::fdo::Result<Vec<SyncQueueItem>> {
+        self.get_ignored_items().await
+    }
+
+    /// Public wrapper for get_transfers for testing purposes
+    #[allow(dead_code)]
+    pub async fn get_transfers_for_test(&self) -> zbus::fdo::Result<Vec<TransferItem>> {
+        self.get_transfers().await
+    }
+
+    /// Public wrapper for get_transfer_limits for testing purposes
+    #[allow(dead_code)]
+    pub async fn get_transfer_limits_for_test(&self) -> zbus::fdo::Result<TransferLimits> {
+        self.get_transfer_limits().await
+    }
+
+    /// Public wrapper for set_transfer_limits for testing purposes
+    #[allow(dead_code)]
+    pub async fn set_transfer_limits_for_test(&self, limits: TransferLimits) -> zbus::fdo::Result<()> {
+        self.set_transfer_limits(limits).await
+    }
+
+    /// Public wrapper for sync_now for testing purposes
+    #[allow(dead_code)]
+    pub async fn sync_now_for_test(&self) -> zbus::fdo::Result<bool> {
+        self.sync_now().await
+    }
+
+    /// Public wrapper for sync_path for testing purposes
+    #[allow(dead_code)]
+    pub async fn sync_path_for_test(&self, path: String, redownload: bool) -> zbus::fdo::Result<SyncPathResult> {
+        self.sync_path(path, redownload).await
+    }
+
+    /// Public wrapper for get_conflict_diff for testing purposes
+    #[allow(dead_code)]
+    pub async fn get_conflict_diff_for_test(&self, conflicted_item_db_id: i64) -> zbus::fdo::Result<String> {
+        self.get_conflict_diff(conflicted_item_db_id).await
     }
 }
@@ -1,5 +1,6 @@
 //! File attribute conversion for FUSE filesystem
 
+use crate::fuse::symlink;
 use crate::persistency::types::DriveItemWithFuse;
 use fuser::{FileAttr, FileType};
 use sqlx::types::chrono;
@@ -13,32 +14,56 @@ impl AttributeManager {
     pub fn item_to_file_attr(item: &DriveItemWithFuse) -> FileAttr {
         let now = SystemTime::now();
 
-        let mtime = item
-            .last_modified()
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.into())
+        let meta = item.fuse_metadata();
+        let mtime = meta
+            .mtime
+            .as_deref()
+            .or(item.last_modified())
+            .and_then(Self::parse_time)
             .unwrap_or(now);
+        let atime = meta.atime.as_deref().and_then(Self::parse_time).unwrap_or(now);
+        let default_perm = if item.is_folder() { 0o755 } else { 0o644 };
+        let is_symlink = symlink::is_symlink(item);
 
         FileAttr {
             ino: item.virtual_ino().unwrap_or(0),
             size: item.size(),
             blocks: (item.size() + 511) / 512, // 512-byte blocks
-            atime: now,
+            atime,
             mtime,
             ctime: now,
             crtime: now,
             kind: if item.is_folder() {
                 FileType::Directory
+            } else if is_symlink {
+                FileType::Symlink
             } else {
                 FileType::RegularFile
             },
-            perm: if item.is_folder() { 0o755 } else { 0o644 },
+            perm: if is_symlink {
+                0o777
+            } else {
+                meta.mode.map(|m| (m & 0o7777) as u16).unwrap_or(default_perm)
+            },
             nlink: 1,
-            uid: 1000, // TODO: Get from system
-            gid: 1000, // TODO: Get from system
+            uid: meta.uid.unwrap_or(1000), // TODO: Get default from system
+            gid: meta.gid.unwrap_or(1000), // TODO: Get default from system
             rdev: 0,
             flags: 0,
             blksize: 512,
         }
     }
+
+    /// Parse a timestamp stored as RFC 3339
+    pub fn parse_time(value: &str) -> Option<SystemTime> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.into())
+    }
+
+    /// Format a timestamp for storage, keeping sub-second precision
+    pub fn format_time(time: SystemTime) -> String {
+        ::chrono::DateTime::<::chrono::Utc>::from(time)
+            .to_rfc3339_opts(::chrono::SecondsFormat::Nanos, true)
+    }
 }
@@ -72,12 +72,17 @@ impl DatabaseManager {
 
     /// Mark database item as modified
     pub async fn mark_db_item_as_modified(&self, ino: u64) -> Result<()> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        self.mark_db_item_as_modified_at(ino, now).await
+    }
+
+    /// Mark item as modified with an explicit last modified timestamp (e.g. from `touch -d`)
+    pub async fn mark_db_item_as_modified_at(&self, ino: u64, last_modified: String) -> Result<()> {
         if let Ok(Some(item)) = self.get_item_by_ino(ino).await {
             let mut updated_item = item.clone();
 
             // Update last modified timestamp
-            let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-            updated_item.drive_item_mut().set_last_modified(now);
+            updated_item.drive_item_mut().set_last_modified(last_modified);
 
             // Mark as local source
             updated_item.set_file_source(FileSource::Local);
@@ -143,6 +148,7 @@ impl DatabaseManager {
                     path: parent_path.clone(),
                 }
             }),
+            web_url: None,
         };
 
         let mut item_with_fuse = self
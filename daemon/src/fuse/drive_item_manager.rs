@@ -131,6 +131,7 @@ impl DriveItemManager {
             download_url: None,
             deleted: None,
             parent_reference: None,
+            web_url: None,
         };
 
         let mut root_with_fuse = drive_item_with_fuse_repo.create_from_drive_item(root_drive_item);
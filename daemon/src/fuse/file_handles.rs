@@ -1,56 +1,114 @@
 //! File handle management for FUSE filesystem
 
-use crate::file_manager::DefaultFileManager;
-use crate::fuse::utils::sync_await;
-use crate::persistency::processing_item_repository::{ProcessingItem, ProcessingItemRepository};
-use anyhow::{Context, Result};
-use log::{debug, error};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 // VIRTUAL_FILE_HANDLE_ID is hardcoded as 1 in operations
 
+/// An open file of the FUSE filesystem
+struct OpenFile {
+    ino: u64,
+    file: Arc<File>,
+    /// Written to (or truncated) since the last upload was queued
+    dirty: bool,
+}
+
+/// Result of closing a file handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosedHandle {
+    pub ino: u64,
+    /// True if this was the last handle of a file with unsaved changes,
+    /// i.e. the upload has to be queued now
+    pub upload_needed: bool,
+}
+
+#[derive(Default)]
+struct HandleTable {
+    files: HashMap<u64, OpenFile>,
+    // Inodes whose dirty handles were closed while other handles were still open
+    pending_upload: HashSet<u64>,
+    next_id: u64,
+}
+
 /// File handle manager for the FUSE filesystem
 pub struct FileHandleManager {
-    files: Mutex<HashMap<u64, Arc<File>>>,
-    next_id: Mutex<u64>,
-    
-    
+    table: Mutex<HandleTable>,
+}
+
+impl Default for FileHandleManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FileHandleManager {
-    pub fn new(
-        
-    ) -> Self {
+    pub fn new() -> Self {
         Self {
-            files: Mutex::new(HashMap::new()),
-            next_id: Mutex::new(100000),
-            
+            table: Mutex::new(HandleTable {
+                next_id: 100000,
+                ..Default::default()
+            }),
         }
     }
-    
-    pub fn register_file(&self, file: File) -> u64 {
-        
-        let mut files = self.files.lock().unwrap();
-        let mut next_id = self.next_id.lock().unwrap();
-        
-        let id = *next_id;
-        *next_id += 1;
-        files.insert(id, Arc::new(file));
+
+    pub fn register_file(&self, ino: u64, file: File) -> u64 {
+        let mut table = self.table.lock().unwrap();
+
+        let id = table.next_id;
+        table.next_id += 1;
+        table.files.insert(
+            id,
+            OpenFile {
+                ino,
+                file: Arc::new(file),
+                dirty: false,
+            },
+        );
         id
     }
-    
+
     pub fn get_file(&self, fh: u64) -> Option<Arc<File>> {
-        self.files.lock().unwrap().get(&fh).cloned()
+        self.table.lock().unwrap().files.get(&fh).map(|open| open.file.clone())
     }
-    
-    pub fn close_file(&self, fh: u64) -> bool {
-        self.files.lock().unwrap().remove(&fh).is_some()
+
+    /// Remember that a handle modified its file
+    pub fn mark_dirty(&self, fh: u64) {
+        if let Some(open) = self.table.lock().unwrap().files.get_mut(&fh) {
+            open.dirty = true;
+        }
     }
 
+    /// Reset the dirty state of a file after its upload was queued (fsync).
+    /// Returns true if any handle of the file had unsaved changes.
+    pub fn take_dirty(&self, ino: u64) -> bool {
+        let mut table = self.table.lock().unwrap();
+        let mut dirty = table.pending_upload.remove(&ino);
+        for open in table.files.values_mut().filter(|open| open.ino == ino) {
+            dirty |= open.dirty;
+            open.dirty = false;
+        }
+        dirty
+    }
 
+    /// Close a handle, `None` if it was not open
+    pub fn close_file(&self, fh: u64) -> Option<ClosedHandle> {
+        let mut table = self.table.lock().unwrap();
+        let open = table.files.remove(&fh)?;
 
+        let still_open = table.files.values().any(|other| other.ino == open.ino);
+        let upload_needed = if still_open {
+            if open.dirty {
+                table.pending_upload.insert(open.ino);
+            }
+            false
+        } else {
+            table.pending_upload.remove(&open.ino) || open.dirty
+        };
+
+        Some(ClosedHandle {
+            ino: open.ino,
+            upload_needed,
+        })
+    }
 }
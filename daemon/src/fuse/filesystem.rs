@@ -2,22 +2,37 @@
 
 use crate::file_manager::{DefaultFileManager, FileManager};
 use crate::fuse::attributes::AttributeManager;
+use crate::fuse::drive_item_manager::DriveItemManager;
 use crate::fuse::utils::sync_await;
 use crate::persistency::cached_drive_item_with_fuse_repository::CachedDriveItemWithFuseRepository;
 use crate::persistency::download_queue_repository::DownloadQueueRepository;
 use crate::persistency::drive_item_with_fuse_repository::DriveItemWithFuseRepository;
+use crate::persistency::processing_item_repository::{ChangeType, ProcessingStatus};
 use crate::persistency::types::DriveItemWithFuse;
+use crate::sync::selective_sync::{self, SyncRuleSet};
 use anyhow::Result;
 use log::{debug, info, warn};
+use onedrive_sync_lib::config::{MountConfig, PlaceholderMode};
 use sqlx::Pool;
 use std::sync::Arc;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use std::io::{Read, Seek, SeekFrom, Write};
 use crate::fuse::operations::MetadataToFileAttr;
 
 use crate::fuse::database::DatabaseManager;
 use crate::fuse::file_handles::FileHandleManager;
+use crate::fuse::streaming::StreamingManager;
+use crate::fuse::symlink;
 
+/// Suffix of files that are not downloaded in `PlaceholderMode::Suffix`
+pub const PLACEHOLDER_SUFFIX: &str = ".onedrivedownload";
+
+/// How long fsync waits for an upload when `fsync_waits_for_upload` is set
+pub const FSYNC_UPLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Interval for checking the processing queue while waiting for an upload
+const UPLOAD_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// OneDrive FUSE filesystem implementation using DriveItemWithFuse
 pub struct OneDriveFuse {
@@ -29,6 +44,7 @@ pub struct OneDriveFuse {
     // Managers for different responsibilities
     file_handle_manager: FileHandleManager,
     database_manager: DatabaseManager,
+    streaming_manager: StreamingManager,
 }
 
 impl OneDriveFuse {
@@ -50,12 +66,15 @@ impl OneDriveFuse {
 
         let database_manager = DatabaseManager::new(drive_item_with_fuse_repo.clone());
 
+        let streaming_manager = StreamingManager::new(app_state.clone());
+
         Ok(Self {
             drive_item_with_fuse_repo,
             file_manager,
             app_state,
             file_handle_manager,
             database_manager,
+            streaming_manager,
         })
     }
 
@@ -103,6 +122,36 @@ impl OneDriveFuse {
         &self.database_manager
     }
 
+    /// Get streaming manager for files that are not downloaded yet
+    pub fn streaming(&self) -> &StreamingManager {
+        &self.streaming_manager
+    }
+
+    /// Get the mount-level settings, as currently set in the settings
+    pub fn mount_config(&self) -> MountConfig {
+        sync_await(async { self.app_state.config().settings.read().await.mount_config.clone() })
+    }
+
+    /// Whether the mount rejects every change
+    pub fn is_read_only(&self) -> bool {
+        self.app_state.run_mode().read_only
+    }
+
+    /// Whether only downloaded files can be read (no network use)
+    pub fn is_offline(&self) -> bool {
+        self.app_state.run_mode().offline
+    }
+
+    /// How long the kernel may cache name lookups
+    pub fn entry_ttl(&self) -> Duration {
+        Duration::from_secs(self.mount_config().entry_ttl_seconds)
+    }
+
+    /// How long the kernel may cache file attributes
+    pub fn attr_ttl(&self) -> Duration {
+        Duration::from_secs(self.mount_config().attr_ttl_seconds)
+    }
+
     /// Get drive item with fuse repository
     pub fn drive_item_with_fuse_repo(&self) -> &Arc<CachedDriveItemWithFuseRepository> {
         &self.drive_item_with_fuse_repo
@@ -122,11 +171,86 @@ impl OneDriveFuse {
     pub fn get_attributes_from_local_file_or_from_db(&self, item: &DriveItemWithFuse) -> fuser::FileAttr {
         if let Some(file_path) = self.get_local_file_path(item.virtual_ino().unwrap()) {
             let metadata = std::fs::metadata(&file_path).unwrap();
-            return metadata.try_to_file_attr(item.virtual_ino().unwrap()).unwrap();
+            let mut attr = metadata.try_to_file_attr(item.virtual_ino().unwrap(), Some(item.fuse_metadata())).unwrap();
+            if symlink::is_symlink(item) {
+                attr.kind = fuser::FileType::Symlink;
+                attr.perm = 0o777;
+            }
+            return attr;
         }
         AttributeManager::item_to_file_attr(&item)
     }
 
+    /// Find a child by the name shown in the mount (case-insensitive).
+    /// Strips the placeholder suffix (in suffix mode) and falls back to the symlink marker file.
+    pub fn find_child(&self, parent: u64, name: &str) -> Result<Option<DriveItemWithFuse>> {
+        let lookup_name = self.lookup_name(name);
+        let repo = self.drive_item_with_fuse_repo();
+        let item = match sync_await(
+            repo.get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(parent, lookup_name),
+        )? {
+            Some(item) => Some(item),
+            None => sync_await(repo.get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(
+                parent,
+                &symlink::stored_name(lookup_name),
+            ))?,
+        };
+        let rules = self.sync_rules();
+        Ok(item.filter(|item| !self.is_hidden(&rules, item)))
+    }
+
+    /// Selective sync rules of the current settings
+    pub fn sync_rules(&self) -> Arc<SyncRuleSet> {
+        sync_await(self.app_state.sync_rules())
+    }
+
+    /// Check if an item is left out of the mount by `hide_excluded`.
+    /// Files that exist locally stay visible.
+    pub fn is_hidden(&self, rules: &SyncRuleSet, item: &DriveItemWithFuse) -> bool {
+        rules.is_hidden(&selective_sync::item_path(item.drive_item()))
+            && self.get_local_file_path(item.virtual_ino().unwrap_or(0)).is_none()
+    }
+
+    /// Name of an item as listed in the mount. Listings read `placeholder_mode` once and
+    /// pass it for every entry.
+    pub fn display_name(&self, item: &DriveItemWithFuse, placeholder_mode: PlaceholderMode) -> String {
+        let name = item.name().unwrap_or("unknown");
+        if symlink::is_symlink(item) {
+            symlink::link_name(name).to_string()
+        } else if placeholder_mode == PlaceholderMode::Suffix
+            && !item.is_folder()
+            && self.get_local_file_path(item.virtual_ino().unwrap_or(0)).is_none()
+        {
+            format!("{}{}", name, PLACEHOLDER_SUFFIX)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Name as stored in the database for a name given in a FUSE request
+    pub fn lookup_name<'a>(&self, name: &'a str) -> &'a str {
+        match self.placeholder_mode() {
+            PlaceholderMode::Suffix => name.strip_suffix(PLACEHOLDER_SUFFIX).unwrap_or(name),
+            PlaceholderMode::HydrateOnOpen | PlaceholderMode::Xattr => name,
+        }
+    }
+
+    /// How files that are not downloaded are shown, from the settings
+    pub fn placeholder_mode(&self) -> PlaceholderMode {
+        self.mount_config().placeholder_mode
+    }
+
+    /// File type of an item as listed in the mount
+    pub fn file_type(item: &DriveItemWithFuse) -> fuser::FileType {
+        if item.is_folder() {
+            fuser::FileType::Directory
+        } else if symlink::is_symlink(item) {
+            fuser::FileType::Symlink
+        } else {
+            fuser::FileType::RegularFile
+        }
+    }
+
     // Direct file operations (no wrapper)
     pub fn get_local_file_path(&self, ino: u64) -> Option<PathBuf> {
         self.file_manager.get_local_path_if_file_exists(ino)
@@ -279,7 +403,7 @@ impl OneDriveFuse {
 
         // Get file attributes
         let metadata = std::fs::metadata(file_path)?;
-        let attr = metadata.try_to_file_attr(0).map_err(|_| {
+        let attr = metadata.try_to_file_attr(0, None).map_err(|_| {
             std::io::Error::new(std::io::ErrorKind::Other, "Failed to convert metadata to file attributes")
         })?;
 
@@ -313,6 +437,9 @@ impl OneDriveFuse {
         }
         
         // Create processing item for delete operation
+        if self.is_local_only(original_item) {
+            return Ok(());
+        }
         let delete_processing_item = crate::persistency::processing_item_repository::ProcessingItem::new_local(
             original_item.drive_item().clone(),
             crate::sync::ChangeOperation::Delete
@@ -321,6 +448,8 @@ impl OneDriveFuse {
         let processing_repo = self.app_state.persistency().processing_item_repository();
         if let Err(e) = sync_await(processing_repo.store_processing_item(&delete_processing_item)) {
             warn!("Failed to store processing item: {}", e);
+        } else {
+            self.app_state.sync_trigger().local_change();
         }
         
         Ok(())
@@ -356,15 +485,109 @@ impl OneDriveFuse {
         Ok(())
     }
 
-    // Generic method to create processing items for sync operations
-    pub fn create_processing_item(&self, item: &DriveItemWithFuse, operation: crate::sync::ChangeOperation) -> Result<(), anyhow::Error> {
+    /// Check if an item was never uploaded and matches a local ignore pattern,
+    /// so it lives in the local folder only
+    pub fn is_local_only(&self, item: &DriveItemWithFuse) -> bool {
+        !item.is_synced()
+            && self
+                .sync_rules()
+                .is_locally_ignored(&selective_sync::item_path(item.drive_item()))
+    }
+
+    // Generic method to create processing items for sync operations.
+    // Returns None for local only items, nothing is queued for them.
+    pub fn create_processing_item(&self, item: &DriveItemWithFuse, operation: crate::sync::ChangeOperation) -> Result<Option<i64>, anyhow::Error> {
+        if self.is_read_only() {
+            anyhow::bail!("Read-only mount, not queueing {:?} of {}", operation, item.id());
+        }
+        if self.is_local_only(item) {
+            debug!("🙈 Not queueing {:?} of ignored item {}", operation, item.name().unwrap_or("unknown"));
+            return Ok(None);
+        }
         let processing_item = crate::persistency::processing_item_repository::ProcessingItem::new_local(
             item.drive_item().clone(),
             operation
         );
         let processing_repo = self.app_state.persistency().processing_item_repository();
-        sync_await(processing_repo.store_processing_item(&processing_item))?;
-        Ok(())
+        let id = sync_await(processing_repo.store_processing_item(&processing_item))?;
+        self.app_state.sync_trigger().local_change();
+        Ok(Some(id))
+    }
+
+    /// Refresh a modified file's size and timestamps from its local copy and queue its upload.
+    /// Returns the id of the processing item, None for local only items.
+    pub fn queue_upload(&self, ino: u64) -> Result<Option<i64>> {
+        if self.is_read_only() {
+            anyhow::bail!("Read-only mount, not queueing upload of ino {}", ino);
+        }
+        let item = sync_await(self.database().get_item_by_ino(ino))?
+            .ok_or_else(|| anyhow::anyhow!("Item not found for ino {}", ino))?;
+
+        let mut updated_item = item.clone();
+        if let Some(file_path) = self.get_local_file_path(ino) {
+            sync_await(DriveItemManager::update_drive_item_from_file(
+                updated_item.drive_item_mut(),
+                &file_path,
+            ))?;
+        }
+        updated_item.set_file_source(crate::persistency::types::FileSource::Local);
+        sync_await(self.drive_item_with_fuse_repo().store_drive_item_with_fuse(&updated_item))?;
+
+        let id = self.create_processing_item(&updated_item, crate::sync::ChangeOperation::Update)?;
+        if id.is_some() {
+            debug!("📤 Queued upload of {} (ino={})", updated_item.name().unwrap_or("unknown"), ino);
+        }
+        Ok(id)
+    }
+
+    /// Wait until the local changes of a file, queued as processing item `processing_id`
+    /// or later, have been uploaded. Fails if they end in error or conflict, or on timeout.
+    pub async fn wait_for_upload(
+        app_state: Arc<crate::app_state::AppState>,
+        ino: u64,
+        processing_id: i64,
+        timeout: Duration,
+    ) -> Result<()> {
+        let processing_repo = app_state.persistency().processing_item_repository();
+        let drive_item_repo = app_state.persistency().drive_item_with_fuse_repository();
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            // Look the id up again each time, it changes when a new file is first uploaded
+            let item = drive_item_repo
+                .get_drive_item_with_fuse_by_virtual_ino(ino)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found for ino {}", ino))?;
+            let processing_items = processing_repo
+                .get_processing_items_by_drive_item_id_and_change_type(item.id(), &ChangeType::Local)
+                .await?;
+
+            let mut pending = false;
+            for processing_item in processing_items
+                .iter()
+                .filter(|p| p.id.is_some_and(|id| id >= processing_id))
+            {
+                match processing_item.status {
+                    ProcessingStatus::Error | ProcessingStatus::Conflicted => {
+                        return Err(anyhow::anyhow!(
+                            "Upload of {} ended with status {}",
+                            item.name().unwrap_or("unknown"),
+                            processing_item.status.as_str()
+                        ));
+                    }
+                    ProcessingStatus::Done | ProcessingStatus::Cancelled => {}
+                    _ => pending = true,
+                }
+            }
+            if !pending {
+                return Ok(());
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow::anyhow!("Timed out waiting for upload of ino {}", ino));
+            }
+            tokio::time::sleep(UPLOAD_POLL_INTERVAL).await;
+        }
     }
 
     /// Recursively mark all children of a folder as deleted in the database
@@ -400,6 +623,68 @@ impl OneDriveFuse {
 
         Ok(())
     }
+
+    /// Persist POSIX attributes set via create/mkdir/setattr, failures are only logged
+    pub fn store_posix_attributes(
+        &self,
+        ino: u64,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        atime: Option<SystemTime>,
+        mtime: Option<SystemTime>,
+    ) {
+        let atime = atime.map(AttributeManager::format_time);
+        let mtime = mtime.map(AttributeManager::format_time);
+        if let Err(e) = sync_await(self.drive_item_with_fuse_repo.update_posix_attributes(
+            ino,
+            mode.map(|m| m & 0o7777),
+            uid,
+            gid,
+            atime.as_deref(),
+            mtime.as_deref(),
+        )) {
+            warn!("Failed to store attributes for ino {}: {}", ino, e);
+        }
+    }
+
+    /// Check if a file is pinned for automatic download
+    pub fn is_pinned(&self, ino: u64) -> bool {
+        sync_await(self.app_state.persistency().pinned_item_repository().is_pinned(ino))
+            .unwrap_or(false)
+    }
+
+    /// Pin or unpin a file for automatic download.
+    /// Pinning queues a download if the file is not local yet, unpinning drops a pending
+    /// download but keeps a copy that is already local.
+    pub fn set_pinned(&self, item: &DriveItemWithFuse, pinned: bool) -> Result<(), anyhow::Error> {
+        if self.is_read_only() {
+            anyhow::bail!("Read-only mount, not changing the pin of {}", item.id());
+        }
+        let ino = item.virtual_ino().unwrap_or(0);
+        let pinned_repo = self.app_state.persistency().pinned_item_repository();
+        let download_queue_repo = self.app_state.persistency().download_queue_repository();
+
+        if pinned == sync_await(pinned_repo.is_pinned(ino))? {
+            return Ok(());
+        }
+
+        if pinned {
+            sync_await(pinned_repo.pin(ino))?;
+            if self.get_local_file_path(ino).is_none() {
+                let download_path = self.file_manager.get_download_dir().join(&item.drive_item().id);
+                sync_await(download_queue_repo.add_to_download_queue(&item.drive_item().id, &download_path))?;
+                info!("📌 Pinned {} and queued it for download", item.name().unwrap_or("unknown"));
+            }
+        } else {
+            sync_await(pinned_repo.unpin(ino))?;
+            if self.get_local_file_path(ino).is_none() {
+                sync_await(download_queue_repo.remove_by_drive_item_id(&item.drive_item().id))?;
+            }
+            info!("📌 Unpinned {}", item.name().unwrap_or("unknown"));
+        }
+        Ok(())
+    }
 }
 
 // OpenFlags struct for parsing file open flags
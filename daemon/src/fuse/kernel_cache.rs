@@ -0,0 +1,74 @@
+//! Invalidation of the kernel's FUSE caches
+//!
+//! With entry/attribute TTLs and `FOPEN_KEEP_CACHE` the kernel keeps names,
+//! attributes and file content cached. When the sync processor applies a
+//! remote change it tells the kernel to drop what it cached for the affected
+//! inodes and names. Until the filesystem is mounted there is nothing to
+//! invalidate and the calls do nothing.
+
+use crate::fuse::filesystem::PLACEHOLDER_SUFFIX;
+use crate::fuse::symlink;
+use crate::persistency::types::DriveItemWithFuse;
+use fuser::Notifier;
+use log::debug;
+use std::ffi::OsStr;
+use std::sync::Mutex;
+
+/// Sends cache invalidations to the kernel once the mount is running
+#[derive(Default)]
+pub struct KernelCache {
+    notifier: Mutex<Option<Notifier>>,
+}
+
+impl KernelCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect to the mounted FUSE session
+    pub fn set_notifier(&self, notifier: Notifier) {
+        *self.notifier.lock().unwrap() = Some(notifier);
+    }
+
+    /// Drop the disconnected session (unmount)
+    pub fn clear_notifier(&self) {
+        *self.notifier.lock().unwrap() = None;
+    }
+
+    /// Drop cached attributes and content of an inode
+    pub fn invalidate_inode(&self, ino: u64) {
+        if let Some(notifier) = self.notifier.lock().unwrap().as_ref() {
+            // ENOENT only means the kernel has nothing cached for it
+            if let Err(e) = notifier.inval_inode(ino, 0, 0) {
+                debug!("🧹 Kernel cache invalidation of inode {}: {}", ino, e);
+            }
+        }
+    }
+
+    /// Drop a cached name (or cached "does not exist") in a directory
+    pub fn invalidate_entry(&self, parent_ino: u64, name: &str) {
+        if let Some(notifier) = self.notifier.lock().unwrap().as_ref() {
+            if let Err(e) = notifier.inval_entry(parent_ino, OsStr::new(name)) {
+                debug!("🧹 Kernel cache invalidation of {} in {}: {}", name, parent_ino, e);
+            }
+        }
+    }
+
+    /// Drop everything the kernel may have cached for an item: its inode,
+    /// every name it may be listed under and the parent directory
+    pub fn invalidate_item(&self, item: &DriveItemWithFuse) {
+        if let Some(ino) = item.virtual_ino() {
+            self.invalidate_inode(ino);
+        }
+        let (Some(parent_ino), Some(name)) = (item.parent_ino(), item.name()) else {
+            return;
+        };
+        self.invalidate_entry(parent_ino, name);
+        if symlink::is_symlink(item) {
+            self.invalidate_entry(parent_ino, symlink::link_name(name));
+        } else if !item.is_folder() {
+            self.invalidate_entry(parent_ino, &format!("{}{}", name, PLACEHOLDER_SUFFIX));
+        }
+        self.invalidate_inode(parent_ino);
+    }
+}
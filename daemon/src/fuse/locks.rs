@@ -0,0 +1,184 @@
+//! Advisory file locks for the FUSE filesystem
+//!
+//! Keeps the POSIX byte-range locks (`fcntl`) and BSD `flock` locks taken on
+//! the mount, keyed by inode and lock owner. The kernel sends `flock` requests
+//! as whole-file locks with the open file as owner, so both kinds share one
+//! table. The sync processor checks it to hold back changes to files that are
+//! write-locked.
+
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// A single lock held on a byte range of a file (`end` is inclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileLock {
+    pub owner: u64,
+    pub pid: u32,
+    pub start: u64,
+    pub end: u64,
+    /// `libc::F_RDLCK` or `libc::F_WRLCK`
+    pub typ: i32,
+}
+
+impl FileLock {
+    fn overlaps(&self, start: u64, end: u64) -> bool {
+        self.start <= end && start <= self.end
+    }
+
+    fn conflicts_with(&self, owner: u64, start: u64, end: u64, typ: i32) -> bool {
+        self.owner != owner
+            && self.overlaps(start, end)
+            && (self.typ == libc::F_WRLCK || typ == libc::F_WRLCK)
+    }
+}
+
+/// Lock table shared by the FUSE filesystem and the sync processor
+#[derive(Default)]
+pub struct FileLockManager {
+    locks: Mutex<HashMap<u64, Vec<FileLock>>>,
+    // Blocking requests (F_SETLKW / LOCK_EX without LOCK_NB) still waiting for a lock
+    waiting: Mutex<HashSet<(u64, u64)>>,
+    changed: Notify,
+}
+
+impl FileLockManager {
+    /// Create an empty lock table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// First lock of another owner that conflicts with the given range, if any
+    pub fn test_lock(&self, ino: u64, owner: u64, start: u64, end: u64, typ: i32) -> Option<FileLock> {
+        let locks = self.locks.lock().unwrap();
+        locks
+            .get(&ino)?
+            .iter()
+            .find(|lock| lock.conflicts_with(owner, start, end, typ))
+            .copied()
+    }
+
+    /// Acquire, convert or release (`libc::F_UNLCK`) a lock on a byte range.
+    /// Returns the conflicting lock if the range is held by another owner.
+    pub fn set_lock(
+        &self,
+        ino: u64,
+        owner: u64,
+        pid: u32,
+        start: u64,
+        end: u64,
+        typ: i32,
+    ) -> Result<(), FileLock> {
+        let mut locks = self.locks.lock().unwrap();
+        let file_locks = locks.entry(ino).or_default();
+
+        if typ != libc::F_UNLCK {
+            if let Some(conflict) = file_locks
+                .iter()
+                .find(|lock| lock.conflicts_with(owner, start, end, typ))
+            {
+                return Err(*conflict);
+            }
+        }
+
+        // Drop the owner's previous locks on the range, keeping the parts outside of it
+        let mut remaining = Vec::with_capacity(file_locks.len() + 1);
+        let mut replaced = false;
+        for lock in file_locks.drain(..) {
+            if lock.owner != owner || !lock.overlaps(start, end) {
+                remaining.push(lock);
+                continue;
+            }
+            replaced = true;
+            if lock.start < start {
+                remaining.push(FileLock { end: start - 1, ..lock });
+            }
+            if lock.end > end {
+                remaining.push(FileLock { start: end + 1, ..lock });
+            }
+        }
+        if typ != libc::F_UNLCK {
+            remaining.push(FileLock { owner, pid, start, end, typ });
+        }
+
+        if remaining.is_empty() {
+            locks.remove(&ino);
+        } else {
+            *file_locks = remaining;
+        }
+        drop(locks);
+
+        debug!(
+            "🔒 setlk ino={} owner={} range={}..={} typ={}",
+            ino, owner, start, end, typ
+        );
+        // Unlocks, downgrades and shrunk ranges may let a waiting request through
+        if replaced {
+            self.changed.notify_waiters();
+        }
+        Ok(())
+    }
+
+    /// Wait until a blocking lock request can be granted.
+    /// Returns `false` if the request was cancelled because the owner released its locks.
+    pub async fn wait_for_lock(
+        &self,
+        ino: u64,
+        owner: u64,
+        pid: u32,
+        start: u64,
+        end: u64,
+        typ: i32,
+    ) -> bool {
+        self.waiting.lock().unwrap().insert((ino, owner));
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if !self.waiting.lock().unwrap().contains(&(ino, owner)) {
+                return false;
+            }
+            if self.set_lock(ino, owner, pid, start, end, typ).is_ok() {
+                self.waiting.lock().unwrap().remove(&(ino, owner));
+                return true;
+            }
+            changed.await;
+        }
+    }
+
+    /// Release every lock of an owner on a file (close, process exit)
+    pub fn release_owner(&self, ino: u64, owner: u64) {
+        let removed = {
+            let mut locks = self.locks.lock().unwrap();
+            let removed = match locks.get_mut(&ino) {
+                Some(file_locks) => {
+                    let before = file_locks.len();
+                    file_locks.retain(|lock| lock.owner != owner);
+                    before != file_locks.len()
+                }
+                None => false,
+            };
+            if locks.get(&ino).is_some_and(|file_locks| file_locks.is_empty()) {
+                locks.remove(&ino);
+            }
+            removed
+        };
+        let was_waiting = self.waiting.lock().unwrap().remove(&(ino, owner));
+
+        if removed || was_waiting {
+            debug!("🔓 Released locks of owner {} on inode {}", owner, ino);
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Check if any owner holds a write lock on a file
+    pub fn has_write_lock(&self, ino: u64) -> bool {
+        self.locks
+            .lock()
+            .unwrap()
+            .get(&ino)
+            .is_some_and(|file_locks| file_locks.iter().any(|lock| lock.typ == libc::F_WRLCK))
+    }
+}
@@ -8,7 +8,12 @@ pub mod database;
 pub mod drive_item_manager;
 pub mod file_handles;
 pub mod filesystem;
+pub mod kernel_cache;
+pub mod locks;
 pub mod operations;
+pub mod streaming;
+pub mod symlink;
 pub mod utils;
+pub mod xattr;
 
 pub use filesystem::OneDriveFuse;
@@ -4,16 +4,25 @@ use crate::file_manager::FileManager;
 use crate::fuse::attributes::AttributeManager;
 use crate::fuse::drive_item_manager::DriveItemManager;
 // VIRTUAL_FILE_HANDLE_ID is hardcoded as 1
-use crate::fuse::filesystem::OneDriveFuse;
-use crate::fuse::utils::{sync_await, FUSE_CAP_READDIRPLUS};
-use crate::persistency::types::DriveItemWithFuse;
+use crate::fuse::filesystem::{OneDriveFuse, FSYNC_UPLOAD_TIMEOUT};
+use crate::fuse::symlink;
+use crate::fuse::utils::{
+    sync_await, FUSE_CAP_FLOCK_LOCKS, FUSE_CAP_POSIX_LOCKS, FUSE_CAP_READDIRPLUS,
+    FUSE_CAP_WRITEBACK_CACHE,
+};
+use crate::fuse::xattr;
+use crate::persistency::types::{DriveItemWithFuse, FuseMetadata};
+use onedrive_sync_lib::config::PlaceholderMode;
 use anyhow::Context;
 use fuser::{
-    FileAttr, FileType, KernelConfig, ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyDirectoryPlus, ReplyEntry, ReplyStatfs, ReplyWrite, Request, TimeOrNow
+    FileAttr, FileType, KernelConfig, ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyDirectoryPlus, ReplyEmpty, ReplyEntry, ReplyLock, ReplyStatfs, ReplyWrite, ReplyXattr, Request, TimeOrNow
 };
+use fuser::consts::FOPEN_KEEP_CACHE;
 use libc::c_int;
 use log::{debug, error, info, warn};
 use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::os::unix::fs::MetadataExt;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -21,6 +30,9 @@ use std::fs::{File, Metadata, OpenOptions};
 use libc::{O_RDONLY, O_WRONLY, O_RDWR, O_APPEND, O_CREAT, O_TRUNC, O_EXCL};
 
 
+/// Block size reported by statfs when the drive quota is known
+const STATFS_BLOCK_SIZE: u32 = 4096;
+
 #[derive(Debug)]
 pub enum AttrConversionError {
     MissingMetadata,
@@ -28,11 +40,13 @@ pub enum AttrConversionError {
 }
 
 pub trait MetadataToFileAttr {
-    fn try_to_file_attr(&self, ino: u64) -> Result<FileAttr, AttrConversionError>;
+    /// Convert local file metadata, applying mode and ownership stored in the FUSE metadata.
+    /// Times are taken from the local file, setattr applies them there directly.
+    fn try_to_file_attr(&self, ino: u64, overrides: Option<&FuseMetadata>) -> Result<FileAttr, AttrConversionError>;
 }
 
 impl MetadataToFileAttr for Metadata {
-    fn try_to_file_attr(&self, ino: u64) -> Result<FileAttr, AttrConversionError> {
+    fn try_to_file_attr(&self, ino: u64, overrides: Option<&FuseMetadata>) -> Result<FileAttr, AttrConversionError> {
         let kind = if self.is_dir() {
             FileType::Directory
         } else if self.is_file() {
@@ -52,10 +66,10 @@ impl MetadataToFileAttr for Metadata {
             ctime: SystemTime::now(), // Often use current time as fallback
             crtime: self.created().unwrap_or(UNIX_EPOCH),
             kind,
-            perm: (self.mode() & 0o7777) as u16, // Mask to valid permission bits
+            perm: (overrides.and_then(|o| o.mode).unwrap_or(self.mode()) & 0o7777) as u16, // Mask to valid permission bits
             nlink: self.nlink() as u32,
-            uid: self.uid(),
-            gid: self.gid(),
+            uid: overrides.and_then(|o| o.uid).unwrap_or(self.uid()),
+            gid: overrides.and_then(|o| o.gid).unwrap_or(self.gid()),
             rdev: self.rdev() as u32,
             blksize: self.blksize() as u32,
             flags: 0,
@@ -68,6 +82,17 @@ impl OneDriveFuse{
     pub fn get_item_by_ino(&self, ino: u64) -> DriveItemWithFuse {
         sync_await(self.database().get_item_by_ino(ino)).unwrap().unwrap()
     }
+
+    /// Reply to getxattr/listxattr: report the size when asked for it, otherwise the data
+    fn reply_xattr_data(data: &[u8], size: u32, reply: ReplyXattr) {
+        if size == 0 {
+            reply.size(data.len() as u32);
+        } else if data.len() > size as usize {
+            reply.error(libc::ERANGE);
+        } else {
+            reply.data(data);
+        }
+    }
      fn read_with_handle(
         &mut self,
         fh: u64,
@@ -76,7 +101,13 @@ impl OneDriveFuse{
         reply: ReplyData,
     ) {
         
-        let  mut backend_file = self.file_handles().get_file(fh).unwrap().clone();
+        let mut backend_file = match self.file_handles().get_file(fh) {
+            Some(file) => file,
+            None => {
+                reply.error(libc::EBADF);
+                return;
+            }
+        };
     
         match backend_file.seek(SeekFrom::Start(offset as u64)) {
             Ok(_) => {
@@ -106,9 +137,14 @@ impl OneDriveFuse{
         data: &[u8],
         reply: ReplyWrite,
     ) {
-        let file_ptr = fh as usize as *mut std::fs::File;
-        let  mut backend_file = self.file_handles().get_file(fh).unwrap().clone();
-        
+        let mut backend_file = match self.file_handles().get_file(fh) {
+            Some(file) => file,
+            None => {
+                reply.error(libc::EBADF);
+                return;
+            }
+        };
+        self.file_handles().mark_dirty(fh);
     
         match backend_file.seek(SeekFrom::Start(offset as u64)) {
             Ok(_) => {
@@ -191,7 +227,23 @@ impl OneDriveFuse{
         
         let file_path = match self.get_local_file_path(item.virtual_ino().unwrap_or(0)) {
             Some(path) => path,
-            None => { reply.error(libc::ENOENT); return; }
+            None => {
+                // Not downloaded yet - serve the range from the streaming cache
+                let stream = match self.streaming().get_or_start(&item) {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        error!("Failed to start streaming for ino {}: {}", ino, e);
+                        reply.error(libc::EIO);
+                        return;
+                    }
+                };
+                if let Err(e) = sync_await(stream.ensure_range(self.app_state(), offset as u64, size as u64)) {
+                    error!("Failed to stream range for ino {}: {}", ino, e);
+                    reply.error(libc::EIO);
+                    return;
+                }
+                stream.cache_path().to_path_buf()
+            }
         };
         
         match self.read_file_data(&file_path, offset as u64, size as usize) {
@@ -239,16 +291,56 @@ impl fuser::Filesystem for OneDriveFuse {
     fn open(&mut self, _req: &fuser::Request, ino: u64, flags: i32, reply: fuser::ReplyOpen) {
         debug!("OPEN: ino={}", ino);
         let item = self.get_item_by_ino(ino);
-        let file_path = self.get_local_file_path(item.virtual_ino().unwrap_or(0));
-        if file_path.is_none() {
+        if item.is_folder() {
             reply.error(libc::ENOENT);
             return;
         }
-        if item.is_folder() {
-            reply.error(libc::ENOENT);
+        if self.is_read_only() && (flags & libc::O_ACCMODE) != libc::O_RDONLY {
+            reply.error(libc::EROFS);
+            return;
+        }
+
+        // Files that are not downloaded yet are streamed from OneDrive,
+        // which is impossible in offline mode
+        let local_path = self.get_local_file_path(item.virtual_ino().unwrap_or(0));
+        if local_path.is_none() && self.is_offline() {
+            debug!("📴 Not opening ino {}: not downloaded and offline", ino);
+            reply.error(libc::ENETDOWN);
             return;
         }
-        let file_path = file_path.unwrap();
+        let mut stream = None;
+        let file_path = match local_path {
+            Some(path) => path,
+            None if (flags & libc::O_ACCMODE) != libc::O_RDONLY
+                || self.placeholder_mode() == PlaceholderMode::HydrateOnOpen =>
+            {
+                // Writers need the complete file before they can modify it
+                if let Err(e) = sync_await(self.streaming().hydrate(&item)) {
+                    error!("Failed to hydrate file for ino {}: {}", ino, e);
+                    reply.error(libc::EIO);
+                    return;
+                }
+                match self.get_local_file_path(item.virtual_ino().unwrap_or(0)) {
+                    Some(path) => path,
+                    None => {
+                        reply.error(libc::ENOENT);
+                        return;
+                    }
+                }
+            }
+            None => match self.streaming().get_or_start(&item) {
+                Ok(s) => {
+                    let path = s.cache_path().to_path_buf();
+                    stream = Some(s);
+                    path
+                }
+                Err(e) => {
+                    error!("Failed to start streaming for ino {}: {}", ino, e);
+                    reply.error(libc::EIO);
+                    return;
+                }
+            },
+        };
         
         // Use simple open options for now - we can enhance this later
         let mut open_options = OpenOptions::new();
@@ -260,12 +352,29 @@ impl fuser::Filesystem for OneDriveFuse {
             open_options.append(true);
         }
         
-        match open_options.open(&file_path) {
+        // The stream may have finished and moved its cache file in the meantime
+        let open_result = open_options.open(&file_path).or_else(|e| {
+            match self.get_local_file_path(item.virtual_ino().unwrap_or(0)) {
+                Some(local_path) if stream.is_some() => open_options.open(local_path),
+                _ => Err(e),
+            }
+        });
+
+        match open_result {
             Ok(backend_file) => {
                 // SUCCESS: We can create a stateful session.
-                let fh = self.file_handles().register_file(backend_file);
+                let fh = self.file_handles().register_file(ino, backend_file);
+                // Downloaded files keep their page cache; remote changes invalidate it
+                let open_flags = if stream.is_none() && self.mount_config().keep_cache {
+                    FOPEN_KEEP_CACHE
+                } else {
+                    0
+                };
+                if let Some(stream) = stream {
+                    self.streaming().register_handle(fh, stream);
+                }
                 
-                reply.opened(fh, 0); // Return the valid FH
+                reply.opened(fh, open_flags); // Return the valid FH
             },
             Err(e) => {
                 // FAILURE: We cannot open the file (e.g., permission denied).
@@ -283,93 +392,179 @@ impl fuser::Filesystem for OneDriveFuse {
         ino: u64,
         fh: u64,
         _flags: i32,
-        _lock_owner: Option<u64>,
+        lock_owner: Option<u64>,
         _flush: bool,
         reply: fuser::ReplyEmpty,
     ) {
         debug!("RELEASE: fh={}", fh);
 
-        if fh == 0 {
-            // Directory - nothing to close
+        // Set when the file was flock()ed: the lock goes away with the last handle
+        if let Some(owner) = lock_owner {
+            self.app_state().file_locks().release_owner(ino, owner);
+        }
+
+        // Direct (0) and virtual (1) handles keep no state
+        if fh == 0 || fh == 1 {
             reply.ok();
             return;
         }
 
-        // Handle virtual file handles (no cleanup needed)
-        if fh == 1 {
-            // VIRTUAL_FILE_HANDLE_ID
-            debug!("📂 Released virtual file handle {} for inode {}", fh, ino);
+        self.streaming().release_handle(fh);
+        match self.file_handles().close_file(fh) {
+            Some(closed) if closed.upload_needed => {
+                // Last handle of a modified file: upload the final content once
+                if let Err(e) = self.queue_upload(ino) {
+                    error!("Failed to queue upload for ino {}: {}", ino, e);
+                }
+            }
+            Some(_) => {}
+            None => warn!("Release of unknown file handle {} for ino {}", fh, ino),
+        }
+
+        reply.ok();
+    }
+
+    fn flush(&mut self, _req: &fuser::Request<'_>, ino: u64, fh: u64, lock_owner: u64, reply: ReplyEmpty) {
+        debug!("FLUSH: ino={}, fh={}, lock_owner={}", ino, fh, lock_owner);
+
+        // POSIX locks are released when any descriptor of the owner is closed
+        self.app_state().file_locks().release_owner(ino, lock_owner);
+        reply.ok();
+    }
+
+    fn fsync(&mut self, _req: &fuser::Request<'_>, ino: u64, fh: u64, datasync: bool, reply: ReplyEmpty) {
+        debug!("FSYNC: ino={}, fh={}, datasync={}", ino, fh, datasync);
+
+        if let Some(file) = self.file_handles().get_file(fh) {
+            let result = if datasync { file.sync_data() } else { file.sync_all() };
+            if let Err(e) = result {
+                error!("Failed to sync local file for ino {}: {}", ino, e);
+                reply.error(e.raw_os_error().unwrap_or(libc::EIO));
+                return;
+            }
+        }
+
+        if !self.file_handles().take_dirty(ino) {
             reply.ok();
             return;
         }
 
-        // First, try to update the DriveItem if it's a file
-        let update_result = {
-            let item_result = sync_await(self.database().get_item_by_ino(ino));
-            match item_result {
-                Ok(Some(item)) => {
-                    if item.is_folder() {
-                        Ok(()) // No update needed for folders
-                    } else {
-                        // Get the file path for the item
-                        if let Some(file_path) = self
-                            .get_local_file_path(item.virtual_ino().unwrap_or(0))
-                        {
-                            // Update DriveItem with file metadata
-                            let mut updated_drive_item = item.drive_item().clone();
-                            match sync_await(DriveItemManager::update_drive_item_from_file(
-                                &mut updated_drive_item,
-                                &file_path,
-                            )) {
-                                Ok(_) => {
-                                    // Store the updated item
-                                    let mut updated_item = item.clone();
-                                    *updated_item.drive_item_mut() = updated_drive_item;
-                                    match sync_await(
-                                        self.drive_item_with_fuse_repo()
-                                            .store_drive_item_with_fuse(&updated_item),
-                                    ) {
-                                        Ok(_) => {
-                                            debug!(
-                                                "📂 Updated DriveItem metadata from file: {}",
-                                                file_path.display()
-                                            );
-                                            Ok(())
-                                        }
-                                        Err(e) => {
-                                            error!("Failed to store updated DriveItem: {}", e);
-                                            Err(e)
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Failed to update DriveItem from file: {}", e);
-                                    Err(e)
-                                }
-                            }
-                        } else {
-                            Ok(()) // No local file found, that's okay
-                        }
-                    }
-                }
-                Ok(None) => {
-                    error!("Item not found for ino: {}", ino);
-                    Err(anyhow::anyhow!("Item not found"))
-                }
+        let processing_id = match self.queue_upload(ino) {
+            Ok(Some(id)) => id,
+            // Ignored files are never uploaded, there is nothing to wait for
+            Ok(None) => {
+                reply.ok();
+                return;
+            }
+            Err(e) => {
+                error!("Failed to queue upload for ino {}: {}", ino, e);
+                reply.error(libc::EIO);
+                return;
+            }
+        };
+
+        let wait_for_upload = sync_await(async {
+            self.app_state()
+                .config()
+                .settings
+                .read()
+                .await
+                .mount_config
+                .fsync_waits_for_upload
+        });
+        if !wait_for_upload {
+            reply.ok();
+            return;
+        }
+
+        // Wait in the background so the mount stays responsive during the upload
+        let app_state = self.app_state().clone();
+        tokio::spawn(async move {
+            match OneDriveFuse::wait_for_upload(app_state, ino, processing_id, FSYNC_UPLOAD_TIMEOUT).await {
+                Ok(()) => reply.ok(),
                 Err(e) => {
-                    error!("Failed to get item by ino {}: {}", ino, e);
-                    Err(e)
+                    error!("fsync: upload of ino {} not confirmed: {}", ino, e);
+                    reply.error(libc::EIO);
                 }
             }
-        };
+        });
+    }
 
-        // Then close the file handle
-        self.file_handles().close_file(fh);
-    
-        // When _boxed_file goes out of scope here, the File is closed!
-        
-        reply.ok();
- 
+    fn getlk(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        _fh: u64,
+        lock_owner: u64,
+        start: u64,
+        end: u64,
+        typ: i32,
+        _pid: u32,
+        reply: ReplyLock,
+    ) {
+        debug!(
+            "GETLK: ino={}, lock_owner={}, range={}..={}, typ={}",
+            ino, lock_owner, start, end, typ
+        );
+
+        match self
+            .app_state()
+            .file_locks()
+            .test_lock(ino, lock_owner, start, end, typ)
+        {
+            Some(lock) => reply.locked(lock.start, lock.end, lock.typ, lock.pid),
+            None => reply.locked(start, end, libc::F_UNLCK, 0),
+        }
+    }
+
+    fn setlk(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        _fh: u64,
+        lock_owner: u64,
+        start: u64,
+        end: u64,
+        typ: i32,
+        pid: u32,
+        sleep: bool,
+        reply: ReplyEmpty,
+    ) {
+        debug!(
+            "SETLK: ino={}, lock_owner={}, range={}..={}, typ={}, sleep={}",
+            ino, lock_owner, start, end, typ, sleep
+        );
+
+        if typ != libc::F_RDLCK && typ != libc::F_WRLCK && typ != libc::F_UNLCK {
+            reply.error(libc::EINVAL);
+            return;
+        }
+
+        let file_locks = self.app_state().file_locks.clone();
+        match file_locks.set_lock(ino, lock_owner, pid, start, end, typ) {
+            Ok(()) => reply.ok(),
+            Err(_) if sleep => {
+                // Wait in the background so the lock holder can still use the mount
+                debug!("🔒 Waiting for lock on inode {} (owner {})", ino, lock_owner);
+                tokio::spawn(async move {
+                    if file_locks
+                        .wait_for_lock(ino, lock_owner, pid, start, end, typ)
+                        .await
+                    {
+                        reply.ok();
+                    } else {
+                        reply.error(libc::EINTR);
+                    }
+                });
+            }
+            Err(conflict) => {
+                debug!(
+                    "🔒 Lock on inode {} held by owner {} (pid {})",
+                    ino, conflict.owner, conflict.pid
+                );
+                reply.error(libc::EAGAIN);
+            }
+        }
     }
 
 
@@ -378,25 +573,12 @@ impl fuser::Filesystem for OneDriveFuse {
         let name_str = name.to_string_lossy();
         debug!("LOOKUP: parent={}, name={}", parent, name_str);
 
-        // Strip .onedrivedownload extension if present for lookup
-        let lookup_name = if name_str.ends_with(".onedrivedownload") {
-            &name_str[..name_str.len() - 17] // Remove ".onedrivedownload"
-        } else {
-            &name_str
-        };
-
-        // Use case-insensitive DB query by parent_ino and name
-        if let Ok(Some(item)) = sync_await(
-            self.drive_item_with_fuse_repo()
-                .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(
-                    parent,
-                    lookup_name,
-                ),
-        ) {
+        // Case-insensitive lookup by shown name (handles placeholder and symlink suffixes)
+        if let Ok(Some(item)) = self.find_child(parent, &name_str) {
             let attr = self.get_attributes_from_local_file_or_from_db(&item);
             
                 reply.entry(
-                    &Duration::from_secs(3),
+                    &self.entry_ttl(),
                     &AttributeManager::item_to_file_attr(&item),
                     0,
                 );
@@ -412,7 +594,7 @@ impl fuser::Filesystem for OneDriveFuse {
 
         if let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) {
             reply.attr(
-                &Duration::from_secs(0),
+                &self.attr_ttl(),
                 &self.get_attributes_from_local_file_or_from_db(&item),
             );
         } else {
@@ -487,6 +669,8 @@ impl fuser::Filesystem for OneDriveFuse {
         let mut db_offset = if offset < 2 { 0 } else { (offset - 2) as usize };
         
         let batch_size = 100; // Fetch 100 items at a time
+        let rules = self.sync_rules();
+        let placeholder_mode = self.placeholder_mode();
         
         loop {
             let children = match sync_await(self.database().get_children_by_parent_ino_paginated(
@@ -508,21 +692,11 @@ impl fuser::Filesystem for OneDriveFuse {
             }
             
             for (i, child) in children.iter().enumerate() {
-                let file_type = if child.is_folder() {
-                    fuser::FileType::Directory
-                } else {
-                    fuser::FileType::RegularFile
-                };
-                
-                let name = if self
-                    .get_local_file_path(child.virtual_ino().unwrap_or(0))
-                    .is_none()
-                    && !child.is_folder()
-                {
-                    format!("{}.onedrivedownload", child.name().unwrap_or("unknown"))
-                } else {
-                    child.name().unwrap_or("unknown").to_string()
-                };
+                if self.is_hidden(&rules, child) {
+                    continue;
+                }
+                let file_type = Self::file_type(child);
+                let name = self.display_name(child, placeholder_mode);
                 
 
                 // Calculate the entry offset for this child
@@ -570,7 +744,21 @@ impl fuser::Filesystem for OneDriveFuse {
         match fh {
             0 => self.handle_direct_read(ino, offset, size, reply),
             1 => self.handle_virtual_read(ino, offset, size, reply), // VIRTUAL_FILE_HANDLE_ID
-            _ => self.read_with_handle(fh, offset, size, reply),
+            _ => {
+                // Streamed handles must have the range downloaded before reading the sparse file
+                if let Some(stream) = self.streaming().get_by_handle(fh) {
+                    if let Err(e) = sync_await(stream.ensure_range(
+                        self.app_state(),
+                        offset as u64,
+                        size as u64,
+                    )) {
+                        error!("Failed to stream range for ino {}: {}", ino, e);
+                        reply.error(libc::EIO);
+                        return;
+                    }
+                }
+                self.read_with_handle(fh, offset, size, reply)
+            }
         }
     }
 
@@ -595,19 +783,18 @@ impl fuser::Filesystem for OneDriveFuse {
             offset,
             data.len()
         );
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
         if fh==1 {
             error!("Cannot write to virtual file handle for ino: {}", ino);
             reply.error(libc::EIO);
             return;
         }
         if fh != 0 {
+            // The upload is queued when the last handle is released
             self.write_with_handle(fh, offset, data, reply);
-            // Create processing item for file update after successful write
-            if let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) {
-                if let Err(e) = self.create_processing_item(&item, crate::sync::ChangeOperation::Update) {
-                    error!("Failed to create processing item for file update: {}", e);
-                }
-            }
         } else {
             self.write_direct(ino, offset, data, flags, reply) 
         }
@@ -618,13 +805,17 @@ impl fuser::Filesystem for OneDriveFuse {
         _req: &fuser::Request,
         parent: u64,
         name: &OsStr,
-        _mode: u32,
-        _umask: u32,
+        mode: u32,
+        umask: u32,
         flags: i32,
         reply: ReplyCreate,
     ) {
         let name_str = name.to_string_lossy();
         debug!("CREATE: parent={}, name={}", parent, name_str);
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
         
         // 1. Check for O_EXCL flag and existing file
         let create_new = (flags & libc::O_EXCL) != 0;
@@ -646,18 +837,23 @@ impl fuser::Filesystem for OneDriveFuse {
             }
         };
         
-        // 3. Create physical file using helper
+        // 3. Remember the requested permissions
+        let perm = mode & !umask & 0o7777;
+        self.store_posix_attributes(new_item, Some(perm), None, None, None, None);
+
+        // 4. Create physical file using helper
         let new_file_path = self.file_manager().get_local_dir().join(new_item.to_string());
         match self.create_physical_file(&new_file_path, flags) {
             Ok((backend_file, mut attr)) => {
-                // Update attr with correct inode
+                // Update attr with correct inode and permissions
                 attr.ino = new_item;
+                attr.perm = perm as u16;
                 
                 // Create file handle
-                let fh = self.file_handles().register_file(backend_file);
+                let fh = self.file_handles().register_file(new_item, backend_file);
                 
                 // Reply with created file info
-                reply.created(&Duration::from_secs(1), &attr, 0, fh, 0);
+                reply.created(&self.entry_ttl(), &attr, 0, fh, 0);
                 
                 // Create processing item for new file
                 if let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(new_item)) {
@@ -678,12 +874,16 @@ impl fuser::Filesystem for OneDriveFuse {
         _req: &fuser::Request,
         parent: u64,
         name: &OsStr,
-        _mode: u32,
-        _umask: u32,
+        mode: u32,
+        umask: u32,
         reply: ReplyEntry,
     ) {
         let name_str = name.to_string_lossy();
         debug!("MKDIR: parent={}, name={}", parent, name_str);
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
 
         match sync_await(
             self.database()
@@ -692,12 +892,13 @@ impl fuser::Filesystem for OneDriveFuse {
 
         ) {
             Ok(ino) => {
+                self.store_posix_attributes(ino, Some(mode & !umask & 0o7777), None, None, None, None);
                 if let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) {
                     
 
 
                     reply.entry(
-                        &Duration::from_secs(3),
+                        &self.entry_ttl(),
                         &AttributeManager::item_to_file_attr(&item),
                         0,
                     );
@@ -725,21 +926,13 @@ impl fuser::Filesystem for OneDriveFuse {
     ) {
         let name_str = name.to_string_lossy();
         debug!("UNLINK: parent={}, name={}", parent, name_str);
-
-        // Strip .onedrivedownload extension if present for lookup
-        let lookup_name = if name_str.ends_with(".onedrivedownload") {
-            &name_str[..name_str.len() - 17] // Remove ".onedrivedownload"
-        } else {
-            &name_str
-        };
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
 
         // Get the item to be deleted (case-insensitive lookup)
-        if let Ok(Some(item)) = sync_await(
-            self.drive_item_with_fuse_repo()
-                .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(
-                    parent, lookup_name,
-                ),
-        ) {
+        if let Ok(Some(item)) = self.find_child(parent, &name_str) {
             let onedrive_id = item.id();
 
             // Clean up any open handles for this inode
@@ -780,13 +973,13 @@ impl fuser::Filesystem for OneDriveFuse {
     ) {
         let name_str = name.to_string_lossy();
         debug!("RMDIR: parent={}, name={}", parent, name_str);
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
 
-        // Strip .onedrivedownload extension if present for lookup (shouldn't happen for dirs, but be safe)
-        let lookup_name = if name_str.ends_with(".onedrivedownload") {
-            &name_str[..name_str.len() - 17]
-        } else {
-            &name_str
-        };
+        // Strip the placeholder suffix if present for lookup (shouldn't happen for dirs, but be safe)
+        let lookup_name = self.lookup_name(&name_str);
 
         // Get the directory to be deleted (case-insensitive lookup)
         if let Ok(Some(item)) = sync_await(
@@ -848,24 +1041,17 @@ impl fuser::Filesystem for OneDriveFuse {
             "RENAME: parent={}, name={} -> newparent={}, newname={}",
             parent, name_str, newparent, newname_str
         );
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
 
-        // Strip .onedrivedownload extension if present for lookup
-        let lookup_name = if name_str.ends_with(".onedrivedownload") {
-            &name_str[..name_str.len() - 17]
-        } else {
-            &name_str
-        };
-        let lookup_newname = if newname_str.ends_with(".onedrivedownload") {
-            &newname_str[..newname_str.len() - 17]
-        } else {
-            &newname_str
-        };
+        // Strip the placeholder suffix if present for lookup
+        let lookup_name = self.lookup_name(&name_str);
+        let lookup_newname = self.lookup_name(&newname_str);
 
         // Get the item to be renamed
-        let original_item = match sync_await(
-            self.drive_item_with_fuse_repo()
-                .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(parent, lookup_name)
-        ) {
+        let original_item = match self.find_child(parent, &name_str) {
             Ok(Some(item)) => item,
             Ok(None) => {
                 reply.error(libc::ENOENT);
@@ -879,10 +1065,14 @@ impl fuser::Filesystem for OneDriveFuse {
         };
 
         // Check if target already exists
-        let existing_target = sync_await(
-            self.drive_item_with_fuse_repo()
-                .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(newparent, lookup_newname)
-        ).unwrap_or(None);
+        let existing_target = self.find_child(newparent, &newname_str).unwrap_or(None);
+
+        // Symlinks keep their marker suffix on OneDrive
+        let stored_newname = if symlink::is_symlink(&original_item) {
+            symlink::stored_name(lookup_newname)
+        } else {
+            lookup_newname.to_string()
+        };
 
         // Handle replace operation
         if let Some(target_item) = existing_target {
@@ -918,16 +1108,23 @@ impl fuser::Filesystem for OneDriveFuse {
             return;
         }
 
+        // An ignored file renamed to a name that is not ignored was never uploaded
+        let was_local_only = self.is_local_only(&original_item);
+
         // Normal rename operation - delegate to helper
-        match self.rename_item_in_db(&original_item, newparent, lookup_newname) {
+        match self.rename_item_in_db(&original_item, newparent, &stored_newname) {
             Ok(_) => {
                 debug!("📂 Renamed: {} -> {} ({})", lookup_name, lookup_newname, original_item.id());
                 let original_item = sync_await(
                     self.drive_item_with_fuse_repo()
-                        .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(newparent, lookup_newname)
+                        .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(newparent, &stored_newname)
                 ).unwrap().unwrap();// We need to obtain Modified item to create processing item
                 // Create processing item based on operation type:
-                if parent != newparent {
+                if was_local_only {
+                    if let Err(e) = self.create_processing_item(&original_item, crate::sync::ChangeOperation::Create) {
+                        error!("Failed to create CREATE processing item for renamed ignored file: {}", e);
+                    }
+                } else if parent != newparent {
                     // Different parent = Move operation
                     if let Err(e) = self.create_processing_item(&original_item, crate::sync::ChangeOperation::Move) {
                         error!("Failed to create MOVE processing item: {}", e);
@@ -967,15 +1164,31 @@ impl fuser::Filesystem for OneDriveFuse {
         reply: ReplyAttr,
     ) {
         debug!("SETATTR: ino={}", ino);
-        
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
 
         if let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) {
+            let to_system_time = |t: TimeOrNow| match t {
+                TimeOrNow::SpecificTime(time) => time,
+                TimeOrNow::Now => SystemTime::now(),
+            };
+            let atime = atime.map(to_system_time);
+            let mtime = mtime.map(to_system_time);
+
             // Mark as modified if any attributes changed
-            if size.is_some() || mtime.is_some() {
+            if size.is_some() {
                 if let Err(e) = sync_await(self.database().mark_db_item_as_modified(ino)) {
                     warn!("Failed to mark item as modified: {}", e);
                 }
             }
+            if let Some(mtime) = mtime {
+                let last_modified = AttributeManager::format_time(mtime);
+                if let Err(e) = sync_await(self.database().mark_db_item_as_modified_at(ino, last_modified)) {
+                    warn!("Failed to mark item as modified: {}", e);
+                }
+            }
             let path = self.get_local_file_path(item.virtual_ino().unwrap_or(0));
             if path.is_some() {
                 let file_path = path.unwrap();
@@ -995,19 +1208,55 @@ impl fuser::Filesystem for OneDriveFuse {
                         }
                     }
                 }
-            
+
+                // Times live on the local file so later writes keep updating them
+                if atime.is_some() || mtime.is_some() {
+                    let mut times = std::fs::FileTimes::new();
+                    if let Some(atime) = atime {
+                        times = times.set_accessed(atime);
+                    }
+                    if let Some(mtime) = mtime {
+                        times = times.set_modified(mtime);
+                    }
+                    if let Err(e) = File::open(&file_path).and_then(|f| f.set_times(times)) {
+                        error!("Failed to set times on {}: {}", file_path.display(), e);
+                        reply.error(e.raw_os_error().unwrap_or(libc::EIO));
+                        return;
+                    }
+                }
             }
- 
 
+            // Persist mode, ownership and times so they survive getattr and remounts
+            if mode.is_some() || uid.is_some() || gid.is_some() || atime.is_some() || mtime.is_some() {
+                self.store_posix_attributes(ino, mode, uid, gid, atime, mtime);
+            }
 
+            let item = sync_await(self.database().get_item_by_ino(ino))
+                .ok()
+                .flatten()
+                .unwrap_or(item);
             reply.attr(
-                &Duration::from_secs(1),
+                &self.attr_ttl(),
                 &self.get_attributes_from_local_file_or_from_db(&item)
             );
             
-            // Create processing item for attribute update if any attributes changed
-            if size.is_some() || mtime.is_some() {
-                if let Err(e) = self.create_processing_item(&item, crate::sync::ChangeOperation::Update) {
+            // A truncate through an open handle is uploaded on release like a write
+            let open_handle = fh.filter(|fh| self.file_handles().get_file(*fh).is_some());
+            if let (Some(fh), Some(_)) = (open_handle, size) {
+                self.file_handles().mark_dirty(fh);
+            }
+
+            // A truncate uploads the content, a new modification time alone only updates
+            // fileSystemInfo on OneDrive
+            let operation = if size.is_some() && open_handle.is_none() {
+                Some(crate::sync::ChangeOperation::Update)
+            } else if mtime.is_some() && !item.is_folder() {
+                Some(crate::sync::ChangeOperation::Touch)
+            } else {
+                None
+            };
+            if let Some(operation) = operation {
+                if let Err(e) = self.create_processing_item(&item, operation) {
                     error!("Failed to create processing item for attribute update: {}", e);
                 }
             }
@@ -1019,18 +1268,265 @@ impl fuser::Filesystem for OneDriveFuse {
     fn statfs(&mut self, _req: &fuser::Request, _ino: u64, reply: ReplyStatfs) {
         debug!("STATFS");
 
-        // Return dummy filesystem statistics
+        // Report the OneDrive quota cached by the sync cycle
+        let quota = sync_await(
+            self.app_state()
+                .persistency()
+                .drive_quota_repository()
+                .get_quota(),
+        )
+        .unwrap_or_else(|e| {
+            warn!("Failed to read cached drive quota: {}", e);
+            None
+        });
+
+        let Some(quota) = quota else {
+            // Quota not fetched yet - return dummy filesystem statistics
+            reply.statfs(
+                1_000_000_000, // Total blocks
+                500_000_000,   // Free blocks
+                500_000_000,   // Available blocks
+                1_000_000,     // Total files
+                500_000,       // Free files
+                512,           // Block size
+                255,           // Max filename length
+                0,             // Fragment size
+            );
+            return;
+        };
+
+        let block_size = STATFS_BLOCK_SIZE as u64;
+        let total_blocks = quota.total / block_size;
+        let free_blocks = quota.remaining.min(quota.total) / block_size;
         reply.statfs(
-            1_000_000_000, // Total blocks
-            500_000_000,   // Free blocks
-            500_000_000,   // Available blocks
-            1_000_000,     // Total files
-            500_000,       // Free files
-            512,           // Block size
-            255,           // Max filename length
-            0,             // Fragment size
+            total_blocks,       // Total blocks
+            free_blocks,        // Free blocks
+            free_blocks,        // Available blocks
+            1_000_000,          // Total files
+            500_000,            // Free files
+            STATFS_BLOCK_SIZE,  // Block size
+            255,                // Max filename length
+            STATFS_BLOCK_SIZE,  // Fragment size
         );
     }
+
+    fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+        debug!("READLINK: ino={}", ino);
+
+        let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        if !symlink::is_symlink(&item) {
+            reply.error(libc::EINVAL);
+            return;
+        }
+
+        // Links synced from another machine are fetched on first use
+        if self.get_local_file_path(ino).is_none() {
+            if self.is_offline() {
+                reply.error(libc::ENETDOWN);
+                return;
+            }
+            if let Err(e) = sync_await(self.streaming().hydrate(&item)) {
+                error!("Failed to download symlink target for ino {}: {}", ino, e);
+                reply.error(libc::EIO);
+                return;
+            }
+        }
+
+        match self.get_local_file_path(ino).map(std::fs::read) {
+            Some(Ok(target)) => reply.data(&target),
+            Some(Err(e)) => reply.error(e.raw_os_error().unwrap_or(libc::EIO)),
+            None => reply.error(libc::EIO),
+        }
+    }
+
+    fn symlink(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        link_name: &OsStr,
+        target: &Path,
+        reply: ReplyEntry,
+    ) {
+        let link_name = link_name.to_string_lossy();
+        debug!("SYMLINK: parent={}, name={} -> {}", parent, link_name, target.display());
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
+
+        if self.find_child(parent, &link_name).unwrap_or(None).is_some() {
+            reply.error(libc::EEXIST);
+            return;
+        }
+
+        // The link is stored as a marker file whose content is the target
+        let stored_name = symlink::stored_name(&link_name);
+        let ino = match sync_await(
+            self.database()
+                .apply_local_change_to_db_repository("create", parent, &stored_name, false),
+        ) {
+            Ok(ino) => ino,
+            Err(e) => {
+                error!("Failed to create symlink in database: {}", e);
+                reply.error(libc::EIO);
+                return;
+            }
+        };
+
+        let local_path = self.file_manager().get_local_dir().join(ino.to_string());
+        if let Err(e) = std::fs::write(&local_path, target.as_os_str().as_bytes()) {
+            error!("Failed to write symlink target {}: {}", local_path.display(), e);
+            reply.error(e.raw_os_error().unwrap_or(libc::EIO));
+            return;
+        }
+
+        match sync_await(self.database().get_item_by_ino(ino)) {
+            Ok(Some(item)) => {
+                reply.entry(
+                    &self.entry_ttl(),
+                    &self.get_attributes_from_local_file_or_from_db(&item),
+                    0,
+                );
+                if let Err(e) = self.create_processing_item(&item, crate::sync::ChangeOperation::Create) {
+                    error!("Failed to create processing item for symlink: {}", e);
+                }
+            }
+            _ => {
+                error!("Failed to get created symlink by ino: {}", ino);
+                reply.error(libc::EIO);
+            }
+        }
+    }
+
+    fn getxattr(&mut self, _req: &Request<'_>, ino: u64, name: &OsStr, size: u32, reply: ReplyXattr) {
+        let name = name.to_string_lossy();
+        debug!("GETXATTR: ino={}, name={}", ino, name);
+
+        // Answer foreign namespaces (security.*, system.*) without touching the database
+        if !xattr::is_onedrive_xattr(&name) {
+            reply.error(libc::ENODATA);
+            return;
+        }
+
+        let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let hydrated = self.get_local_file_path(ino).is_some();
+        match xattr::xattr_value(&name, &item, hydrated, self.is_pinned(ino)) {
+            Some(value) => Self::reply_xattr_data(&value, size, reply),
+            None => reply.error(libc::ENODATA),
+        }
+    }
+
+    fn listxattr(&mut self, _req: &Request<'_>, ino: u64, size: u32, reply: ReplyXattr) {
+        debug!("LISTXATTR: ino={}", ino);
+
+        let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let hydrated = self.get_local_file_path(ino).is_some();
+        let list = xattr::xattr_list(&item, hydrated, self.is_pinned(ino));
+        Self::reply_xattr_data(&list, size, reply);
+    }
+
+    fn setxattr(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        name: &OsStr,
+        value: &[u8],
+        flags: i32,
+        _position: u32,
+        reply: ReplyEmpty,
+    ) {
+        let name = name.to_string_lossy();
+        debug!("SETXATTR: ino={}, name={}", ino, name);
+
+        if name != xattr::XATTR_PIN {
+            // OneDrive metadata is read-only, other namespaces are not supported
+            let errno = if xattr::is_onedrive_xattr(&name) { libc::EPERM } else { libc::ENOTSUP };
+            reply.error(errno);
+            return;
+        }
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
+
+        let Some(pinned) = xattr::parse_pin_value(value) else {
+            reply.error(libc::EINVAL);
+            return;
+        };
+
+        let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        if item.is_folder() {
+            reply.error(libc::EISDIR);
+            return;
+        }
+
+        let was_pinned = self.is_pinned(ino);
+        if flags & libc::XATTR_CREATE != 0 && was_pinned {
+            reply.error(libc::EEXIST);
+            return;
+        }
+        if flags & libc::XATTR_REPLACE != 0 && !was_pinned {
+            reply.error(libc::ENODATA);
+            return;
+        }
+
+        match self.set_pinned(&item, pinned) {
+            Ok(()) => reply.ok(),
+            Err(e) => {
+                error!("Failed to update pin for ino {}: {}", ino, e);
+                reply.error(libc::EIO);
+            }
+        }
+    }
+
+    fn removexattr(&mut self, _req: &Request<'_>, ino: u64, name: &OsStr, reply: ReplyEmpty) {
+        let name = name.to_string_lossy();
+        debug!("REMOVEXATTR: ino={}, name={}", ino, name);
+
+        if name != xattr::XATTR_PIN {
+            let errno = if xattr::is_onedrive_xattr(&name) { libc::EPERM } else { libc::ENODATA };
+            reply.error(errno);
+            return;
+        }
+        if self.is_read_only() {
+            reply.error(libc::EROFS);
+            return;
+        }
+
+        let Ok(Some(item)) = sync_await(self.database().get_item_by_ino(ino)) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        if !self.is_pinned(ino) {
+            reply.error(libc::ENODATA);
+            return;
+        }
+
+        match self.set_pinned(&item, false) {
+            Ok(()) => reply.ok(),
+            Err(e) => {
+                error!("Failed to remove pin for ino {}: {}", ino, e);
+                reply.error(libc::EIO);
+            }
+        }
+    }
+
     fn readdirplus(
         &mut self,
         _req: &fuser::Request<'_>,
@@ -1089,21 +1585,12 @@ impl fuser::Filesystem for OneDriveFuse {
         debug!("Adding .. entry: {}", dotdot_ino);
 
         // Add child entries
-        for (i, child) in children.iter().enumerate() {
-            let file_type = if child.is_folder() {
-                fuser::FileType::Directory
-            } else {
-                fuser::FileType::RegularFile
-            };
-            let name = if self
-                .get_local_file_path(child.virtual_ino().unwrap_or(0))
-                .is_none()
-                && !child.is_folder()
-            {
-                format!("{}.onedrivedownload", child.name().unwrap_or("unknown"))
-            } else {
-                child.name().unwrap_or("unknown").to_string()
-            };
+        let rules = self.sync_rules();
+        let mount_config = self.mount_config();
+        let entry_ttl = Duration::from_secs(mount_config.entry_ttl_seconds);
+        for (i, child) in children.iter().filter(|child| !self.is_hidden(&rules, child)).enumerate() {
+            let file_type = Self::file_type(child);
+            let name = self.display_name(child, mount_config.placeholder_mode);
             let attr = AttributeManager::item_to_file_attr(&child);
             entries.push((
                 child.virtual_ino().unwrap_or(0),
@@ -1123,7 +1610,7 @@ impl fuser::Filesystem for OneDriveFuse {
                     *ino,
                     i as i64 + 1,
                     name,
-                    &Duration::from_secs(5),
+                    &entry_ttl,
                     &attr,
                     geno.clone(),
                 ) {
@@ -1141,6 +1628,22 @@ impl fuser::Filesystem for OneDriveFuse {
         config
             .add_capabilities(FUSE_CAP_READDIRPLUS)
             .expect("Failed to add capabilities");
+        if let Err(unsupported) = config.add_capabilities(FUSE_CAP_POSIX_LOCKS | FUSE_CAP_FLOCK_LOCKS) {
+            warn!("Kernel does not support file locking capabilities {:#x}", unsupported);
+        }
+        let mount_config = self.mount_config();
+        if mount_config.writeback_cache {
+            if let Err(unsupported) = config.add_capabilities(FUSE_CAP_WRITEBACK_CACHE) {
+                warn!("Kernel does not support writeback caching {:#x}", unsupported);
+            }
+        }
+        info!(
+            "FUSE caching: entry TTL {:?}, attr TTL {:?}, keep cache {}, writeback {}",
+            self.entry_ttl(),
+            self.attr_ttl(),
+            mount_config.keep_cache,
+            mount_config.writeback_cache
+        );
         Ok(())
     }
 }
@@ -0,0 +1,438 @@
+//! On-demand streaming of files that are not hydrated yet
+//!
+//! Reads on a file that is not in the local folder are served from a sparse
+//! cache file in the downloads directory. The requested byte ranges are fetched
+//! with ranged GETs, while a background task keeps downloading the remaining
+//! chunks. Once the whole file is present it is moved into the local folder and
+//! becomes a regular hydrated file.
+
+use crate::app_state::AppState;
+use crate::persistency::types::DriveItemWithFuse;
+use crate::sync::text_merge;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Size of a single ranged download; reads are rounded up to whole chunks
+pub const STREAM_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Consecutive chunk failures after which the background download gives up
+const MAX_BACKGROUND_FAILURES: u32 = 3;
+
+/// Sparse cache of a single remote file that is being streamed
+pub struct StreamingFile {
+    ino: u64,
+    onedrive_id: String,
+    name: String,
+    etag: Option<String>,
+    ctag: Option<String>,
+    last_modified: Option<String>,
+    size: u64,
+    cache_path: PathBuf,
+    file: File,
+    chunks: Mutex<Vec<bool>>,
+    download_url: Mutex<Option<String>>,
+    // Serializes chunk fetches and the final move to the local folder
+    fetch_lock: tokio::sync::Mutex<()>,
+    read_hint: AtomicU64,
+    completed: AtomicBool,
+    cancelled: AtomicBool,
+    background_running: AtomicBool,
+}
+
+impl StreamingFile {
+    fn create(item: &DriveItemWithFuse, cache_path: PathBuf) -> Result<Self> {
+        let ino = item
+            .virtual_ino()
+            .ok_or_else(|| anyhow!("Item {} has no inode", item.id()))?;
+        let size = item.size();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&cache_path)
+            .with_context(|| format!("Failed to create cache file {}", cache_path.display()))?;
+        // Sparse file: holes are only filled once their chunk has been fetched
+        file.set_len(size)?;
+
+        let chunk_count = size.div_ceil(STREAM_CHUNK_SIZE) as usize;
+
+        Ok(Self {
+            ino,
+            onedrive_id: item.id().to_string(),
+            name: item.name().unwrap_or("unnamed").to_string(),
+            etag: item.etag().map(|e| e.to_string()),
+            ctag: item.ctag().map(|c| c.to_string()),
+            last_modified: item.drive_item().last_modified.clone(),
+            size,
+            cache_path,
+            file,
+            chunks: Mutex::new(vec![false; chunk_count]),
+            download_url: Mutex::new(None),
+            fetch_lock: tokio::sync::Mutex::new(()),
+            read_hint: AtomicU64::new(0),
+            completed: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            background_running: AtomicBool::new(false),
+        })
+    }
+
+    /// Path of the sparse cache file
+    pub fn cache_path(&self) -> &Path {
+        &self.cache_path
+    }
+
+    /// Whether every chunk has been downloaded
+    pub fn is_complete(&self) -> bool {
+        self.chunks.lock().unwrap().iter().all(|present| *present)
+    }
+
+    fn is_chunk_present(&self, index: u64) -> bool {
+        self.chunks.lock().unwrap()[index as usize]
+    }
+
+    fn chunk_bounds(&self, index: u64) -> (u64, u64) {
+        let start = index * STREAM_CHUNK_SIZE;
+        let end = (start + STREAM_CHUNK_SIZE).min(self.size) - 1;
+        (start, end)
+    }
+
+    /// Next missing chunk, starting at the position of the latest read so that
+    /// sequential readers (video players) stay ahead of the background download
+    fn next_missing_chunk(&self) -> Option<u64> {
+        let chunks = self.chunks.lock().unwrap();
+        let count = chunks.len();
+        if count == 0 {
+            return None;
+        }
+        let hint = (self.read_hint.load(Ordering::Relaxed) as usize).min(count);
+        (hint..count)
+            .chain(0..hint)
+            .find(|index| !chunks[*index])
+            .map(|index| index as u64)
+    }
+
+    async fn resolve_download_url(&self, app_state: &AppState, refresh: bool) -> Result<String> {
+        if !refresh {
+            if let Some(url) = self.download_url.lock().unwrap().clone() {
+                return Ok(url);
+            }
+        }
+
+        // Download URLs are short-lived, so ask the API for a fresh one
+        let item = app_state
+            .onedrive()
+            .get_item_by_id(&self.onedrive_id)
+            .await
+            .context("Failed to get item by ID")?;
+        let url = item
+            .download_url
+            .ok_or_else(|| anyhow!("No download URL available for {}", self.onedrive_id))?;
+        *self.download_url.lock().unwrap() = Some(url.clone());
+        Ok(url)
+    }
+
+    async fn download_range(&self, app_state: &AppState, start: u64, end: u64) -> Result<Vec<u8>> {
+        let url = self.resolve_download_url(app_state, false).await?;
+        let result = match app_state
+            .onedrive()
+            .download_range(&url, &self.onedrive_id, &self.name, start, end)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                // The cached URL may have expired - retry once with a fresh one
+                warn!(
+                    "⚠️ Ranged download failed for {} ({}-{}), refreshing URL: {}",
+                    self.onedrive_id, start, end, e
+                );
+                let url = self.resolve_download_url(app_state, true).await?;
+                app_state
+                    .onedrive()
+                    .download_range(&url, &self.onedrive_id, &self.name, start, end)
+                    .await
+                    .context("Failed to download file range")?
+            }
+        };
+
+        let expected = (end - start + 1) as usize;
+        let data = result.file_data;
+        if data.len() == expected {
+            Ok(data)
+        } else if data.len() as u64 == self.size {
+            // Server ignored the Range header and sent the whole body
+            Ok(data[start as usize..=end as usize].to_vec())
+        } else {
+            Err(anyhow!(
+                "Unexpected range length for {}: expected {} bytes, got {}",
+                self.onedrive_id,
+                expected,
+                data.len()
+            ))
+        }
+    }
+
+    async fn fetch_chunk(&self, app_state: &AppState, index: u64) -> Result<()> {
+        let _guard = self.fetch_lock.lock().await;
+        if self.is_chunk_present(index) {
+            return Ok(());
+        }
+
+        let (start, end) = self.chunk_bounds(index);
+        let data = self.download_range(app_state, start, end).await?;
+        self.file
+            .write_all_at(&data, start)
+            .with_context(|| format!("Failed to write cache file {}", self.cache_path.display()))?;
+        self.chunks.lock().unwrap()[index as usize] = true;
+
+        debug!(
+            "📥 Streamed chunk {} of {} ({}-{})",
+            index, self.onedrive_id, start, end
+        );
+        Ok(())
+    }
+
+    /// Make sure the byte range `[offset, offset + len)` is present in the cache file
+    pub async fn ensure_range(&self, app_state: &AppState, offset: u64, len: u64) -> Result<()> {
+        if offset >= self.size || len == 0 {
+            return Ok(());
+        }
+        let end = (offset + len).min(self.size);
+        let first = offset / STREAM_CHUNK_SIZE;
+        let last = (end - 1) / STREAM_CHUNK_SIZE;
+        self.read_hint.store(last + 1, Ordering::Relaxed);
+
+        for index in first..=last {
+            if !self.is_chunk_present(index) {
+                self.fetch_chunk(app_state, index).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Download every missing chunk in the foreground
+    async fn fetch_all(&self, app_state: &AppState) -> Result<()> {
+        while let Some(index) = self.next_missing_chunk() {
+            self.fetch_chunk(app_state, index).await?;
+        }
+        Ok(())
+    }
+
+    /// Move the fully downloaded cache file into the local folder
+    async fn finish(&self, app_state: &AppState) -> Result<()> {
+        let _guard = self.fetch_lock.lock().await;
+        if self.completed.load(Ordering::SeqCst) || self.cancelled.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if !self.is_complete() {
+            return Err(anyhow!("Cache file for {} is not complete", self.onedrive_id));
+        }
+
+        self.file.sync_all()?;
+        // Keep the remote modification time so the item is not seen as locally modified
+        if let Some(modified) = self.last_modified.as_deref().and_then(parse_timestamp) {
+            if let Err(e) = self.file.set_modified(modified) {
+                warn!("⚠️ Failed to set mtime on {}: {}", self.cache_path.display(), e);
+            }
+        }
+
+        let local_path = app_state.config().local_dir().join(self.ino.to_string());
+        std::fs::rename(&self.cache_path, &local_path).with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                self.cache_path.display(),
+                local_path.display()
+            )
+        })?;
+        self.completed.store(true, Ordering::SeqCst);
+
+        // The streamed version is the base for merging later changes made through the mount
+        text_merge::remember_base_from_file(app_state, &self.onedrive_id, self.ctag.as_deref(), &local_path)
+            .await;
+
+        // The file is local now, a queued full download would only repeat the work
+        let download_queue_repo = app_state.persistency().download_queue_repository();
+        if let Err(e) = download_queue_repo
+            .remove_by_drive_item_id(&self.onedrive_id)
+            .await
+        {
+            warn!(
+                "⚠️ Failed to remove {} from download queue: {}",
+                self.onedrive_id, e
+            );
+        }
+
+        info!(
+            "✅ Streaming download completed: {} ({} bytes)",
+            self.name, self.size
+        );
+        Ok(())
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if !self.completed.load(Ordering::SeqCst) {
+            let _ = std::fs::remove_file(&self.cache_path);
+        }
+    }
+}
+
+/// Tracks files that are being streamed from OneDrive, keyed by inode
+pub struct StreamingManager {
+    app_state: Arc<AppState>,
+    files: Arc<Mutex<HashMap<u64, Arc<StreamingFile>>>>,
+    // Open file handles reading from a stream, so a restarted stream never
+    // serves holes through a handle that still points at the old cache file
+    handles: Mutex<HashMap<u64, Arc<StreamingFile>>>,
+}
+
+impl StreamingManager {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self {
+            app_state,
+            files: Arc::new(Mutex::new(HashMap::new())),
+            handles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Attach an open file handle to a stream
+    pub fn register_handle(&self, fh: u64, stream: Arc<StreamingFile>) {
+        self.handles.lock().unwrap().insert(fh, stream);
+    }
+
+    /// Stream backing an open file handle, if any
+    pub fn get_by_handle(&self, fh: u64) -> Option<Arc<StreamingFile>> {
+        self.handles.lock().unwrap().get(&fh).cloned()
+    }
+
+    /// Detach a file handle on release
+    pub fn release_handle(&self, fh: u64) -> bool {
+        self.handles.lock().unwrap().remove(&fh).is_some()
+    }
+
+    /// Location of the sparse cache file for an inode
+    pub fn cache_path(&self, ino: u64) -> PathBuf {
+        self.app_state
+            .config()
+            .download_dir()
+            .join(format!("{}.stream", ino))
+    }
+
+    /// Get the stream for an item, starting a new one (and its background
+    /// download) if the item is not being streamed yet
+    pub fn get_or_start(&self, item: &DriveItemWithFuse) -> Result<Arc<StreamingFile>> {
+        let ino = item
+            .virtual_ino()
+            .ok_or_else(|| anyhow!("Item {} has no inode", item.id()))?;
+
+        let mut files = self.files.lock().unwrap();
+        if let Some(existing) = files.get(&ino) {
+            let same_version = existing.etag.as_deref() == item.etag();
+            if same_version && !existing.cancelled.load(Ordering::SeqCst) {
+                let existing = existing.clone();
+                drop(files);
+                self.spawn_background_download(&existing);
+                return Ok(existing);
+            }
+            // Remote content changed since the stream started - start over
+            existing.cancel();
+            files.remove(&ino);
+        }
+
+        let stream = Arc::new(StreamingFile::create(item, self.cache_path(ino))?);
+        files.insert(ino, stream.clone());
+        drop(files);
+
+        info!(
+            "📡 Streaming {} on demand (ino={}, {} bytes)",
+            stream.name, ino, stream.size
+        );
+
+        self.spawn_background_download(&stream);
+        Ok(stream)
+    }
+
+    /// Start the background download of a stream unless it is already running
+    fn spawn_background_download(&self, stream: &Arc<StreamingFile>) {
+        if stream.background_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tokio::spawn(Self::run_background_download(
+            self.app_state.clone(),
+            stream.clone(),
+            self.files.clone(),
+        ));
+    }
+
+    /// Download the whole item in the foreground and move it to the local folder
+    pub async fn hydrate(&self, item: &DriveItemWithFuse) -> Result<()> {
+        let stream = self.get_or_start(item)?;
+        stream.fetch_all(&self.app_state).await?;
+        stream.finish(&self.app_state).await?;
+        self.files.lock().unwrap().remove(&stream.ino);
+        Ok(())
+    }
+
+    async fn run_background_download(
+        app_state: Arc<AppState>,
+        stream: Arc<StreamingFile>,
+        files: Arc<Mutex<HashMap<u64, Arc<StreamingFile>>>>,
+    ) {
+        let mut failures = 0;
+        while let Some(index) = stream.next_missing_chunk() {
+            if stream.cancelled.load(Ordering::SeqCst) {
+                debug!("🛑 Background download cancelled for {}", stream.onedrive_id);
+                stream.background_running.store(false, Ordering::SeqCst);
+                return;
+            }
+            match stream.fetch_chunk(&app_state, index).await {
+                Ok(_) => failures = 0,
+                Err(e) => {
+                    failures += 1;
+                    warn!(
+                        "⚠️ Background chunk {} of {} failed ({}/{}): {}",
+                        index, stream.onedrive_id, failures, MAX_BACKGROUND_FAILURES, e
+                    );
+                    if failures >= MAX_BACKGROUND_FAILURES {
+                        // Reads keep fetching their own ranges; the next open restarts the download
+                        error!(
+                            "❌ Giving up background download of {} after {} failures",
+                            stream.onedrive_id, failures
+                        );
+                        stream.background_running.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                    tokio::time::sleep(Duration::from_secs(1 << failures)).await;
+                }
+            }
+        }
+
+        stream.background_running.store(false, Ordering::SeqCst);
+        match stream.finish(&app_state).await {
+            Ok(_) => {
+                let mut files = files.lock().unwrap();
+                if files.get(&stream.ino).is_some_and(|s| Arc::ptr_eq(s, &stream)) {
+                    files.remove(&stream.ino);
+                }
+            }
+            Err(e) => error!(
+                "❌ Failed to finish streaming download of {}: {}",
+                stream.onedrive_id, e
+            ),
+        }
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<SystemTime> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| SystemTime::from(dt.with_timezone(&chrono::Utc)))
+}
@@ -0,0 +1,28 @@
+//! Symlink support for the FUSE filesystem
+//!
+//! OneDrive has no symlinks, so a link is stored as a small file named
+//! `<link name>.onedrive-symlink` whose content is the link target. Any machine
+//! running the daemon shows these files as symlinks named `<link name>`.
+
+use crate::persistency::types::DriveItemWithFuse;
+
+/// Suffix marking a OneDrive file as a symlink
+pub const SYMLINK_SUFFIX: &str = ".onedrive-symlink";
+
+/// Check if an item is stored as a symlink
+pub fn is_symlink(item: &DriveItemWithFuse) -> bool {
+    !item.is_folder()
+        && item
+            .name()
+            .is_some_and(|name| name.len() > SYMLINK_SUFFIX.len() && name.ends_with(SYMLINK_SUFFIX))
+}
+
+/// Name shown in the mount for a symlink item (marker suffix removed)
+pub fn link_name(stored_name: &str) -> &str {
+    stored_name.strip_suffix(SYMLINK_SUFFIX).unwrap_or(stored_name)
+}
+
+/// Name stored on OneDrive for a symlink called `link_name`
+pub fn stored_name(link_name: &str) -> String {
+    format!("{}{}", link_name, SYMLINK_SUFFIX)
+}
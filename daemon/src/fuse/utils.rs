@@ -13,3 +13,12 @@ where
 
 /// FUSE capability for readdirplus
 pub const FUSE_CAP_READDIRPLUS: u32 = 0x00000010;
+
+/// FUSE capability for POSIX byte-range locks (fcntl)
+pub const FUSE_CAP_POSIX_LOCKS: u32 = 0x00000002;
+
+/// FUSE capability for BSD-style locks (flock)
+pub const FUSE_CAP_FLOCK_LOCKS: u32 = 0x00000400;
+
+/// FUSE capability for writeback caching of buffered writes
+pub const FUSE_CAP_WRITEBACK_CACHE: u32 = 0x00010000;
@@ -0,0 +1,79 @@
+//! Extended attributes exposing OneDrive metadata on the mount
+//!
+//! All attributes live in the `user.onedrive.` namespace. Everything except
+//! `user.onedrive.pin` is read-only and derived from `DriveItemWithFuse`.
+
+use crate::persistency::types::DriveItemWithFuse;
+
+pub const XATTR_ID: &str = "user.onedrive.id";
+pub const XATTR_ETAG: &str = "user.onedrive.etag";
+pub const XATTR_CTAG: &str = "user.onedrive.ctag";
+pub const XATTR_SYNC_STATUS: &str = "user.onedrive.sync_status";
+pub const XATTR_WEB_URL: &str = "user.onedrive.web_url";
+pub const XATTR_HYDRATED: &str = "user.onedrive.hydrated";
+/// Writable: `1` keeps the file downloaded, `0` or removing it unpins
+pub const XATTR_PIN: &str = "user.onedrive.pin";
+
+/// Read-only attributes in the order listxattr reports them
+const READ_ONLY_XATTRS: [&str; 6] = [
+    XATTR_ID,
+    XATTR_ETAG,
+    XATTR_CTAG,
+    XATTR_SYNC_STATUS,
+    XATTR_WEB_URL,
+    XATTR_HYDRATED,
+];
+
+/// Check if an attribute name belongs to this filesystem
+pub fn is_onedrive_xattr(name: &str) -> bool {
+    name == XATTR_PIN || READ_ONLY_XATTRS.contains(&name)
+}
+
+/// Value of an attribute, `None` if the item has no value for it
+pub fn xattr_value(
+    name: &str,
+    item: &DriveItemWithFuse,
+    hydrated: bool,
+    pinned: bool,
+) -> Option<Vec<u8>> {
+    let value = match name {
+        XATTR_ID => Some(item.drive_item.id.clone()),
+        XATTR_ETAG => item.drive_item.etag.clone(),
+        XATTR_CTAG => item
+            .fuse_metadata
+            .ctag
+            .clone()
+            .or_else(|| item.drive_item.ctag.clone()),
+        XATTR_SYNC_STATUS => item.fuse_metadata.sync_status.clone(),
+        XATTR_WEB_URL => item.drive_item.web_url.clone(),
+        XATTR_HYDRATED => Some(bool_value(hydrated).to_string()),
+        XATTR_PIN if pinned => Some(bool_value(true).to_string()),
+        _ => None,
+    };
+    value.map(String::into_bytes)
+}
+
+/// Null-separated list of the attributes the item has a value for
+pub fn xattr_list(item: &DriveItemWithFuse, hydrated: bool, pinned: bool) -> Vec<u8> {
+    let mut list = Vec::new();
+    for name in READ_ONLY_XATTRS.iter().chain(std::iter::once(&XATTR_PIN)) {
+        if xattr_value(name, item, hydrated, pinned).is_some() {
+            list.extend_from_slice(name.as_bytes());
+            list.push(0);
+        }
+    }
+    list
+}
+
+/// Parse a value written to `user.onedrive.pin`
+pub fn parse_pin_value(value: &[u8]) -> Option<bool> {
+    match String::from_utf8_lossy(value).trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn bool_value(value: bool) -> &'static str {
+    if value { "1" } else { "0" }
+}
@@ -18,15 +18,16 @@ mod scheduler;
 mod sync;
 mod tasks;
 
-use crate::app_state::{app_state_factory, AppState};
+use crate::app_state::{app_state_factory, AppState, RunMode};
 use crate::file_manager::{DefaultFileManager, FileManager};
 use crate::fuse::OneDriveFuse;
 use crate::log_appender::setup_logging;
 use crate::persistency::download_queue_repository::DownloadQueueRepository;
 use crate::persistency::profile_repository::ProfileRepository;
+use crate::tasks::change_notifications::SocketIoTransport;
 use crate::tasks::delta_update::SyncCycle;
 use anyhow::{Context, Result};
-use clap::Arg;
+use clap::{Arg, ArgAction};
 use clap::Command;
 use fuser::MountOption;
 use log::{error, info, warn};
@@ -70,21 +71,37 @@ struct AppSetup {
 }
 
 impl AppSetup {
-    /// Initialize the application with all required components
-    async fn initialize() -> Result<Self> {
+    /// Initialize the application with all required components.
+    /// `cli_mode` holds the modes requested on the command line, the settings can enable them too.
+    async fn initialize(cli_mode: RunMode) -> Result<Self> {
         info!("🚀 Initializing OneDrive FUSE daemon...");
 
         // Initialize project configuration
-        let app_state = app_state_factory()
+        let mut app_state = app_state_factory()
             .await
             .context("Failed to initialize application state")?;
 
+        let run_mode = {
+            let settings = app_state.config().settings.read().await;
+            RunMode {
+                read_only: cli_mode.read_only || settings.mount_config.read_only,
+                offline: cli_mode.offline || settings.offline,
+            }
+        };
+        app_state.run_mode = run_mode;
+
         // Setup logging
         let log_dir = app_state.config().project_dirs.data_dir().to_path_buf();
         setup_logging(&log_dir)
             .await
             .context("Failed to setup logging")?;
 
+        if run_mode.read_only {
+            info!("🔒 Read-only mode: changes through the mount are rejected");
+        }
+        if run_mode.offline {
+            info!("📴 Offline mode: no network use, only downloaded files are readable");
+        }
         info!("✅ Application state initialized successfully");
         Ok(Self {
             app_state: Arc::new(app_state),
@@ -103,6 +120,11 @@ impl AppSetup {
                 info!("✅ Existing tokens loaded successfully");
                 Ok(())
             }
+            Err(e) if self.app_state.run_mode().offline => {
+                // Authorization needs the network, the mount works from the database
+                warn!("⚠️ No valid tokens found, skipping authorization in offline mode: {}", e);
+                Ok(())
+            }
             Err(_) => {
                 info!("🔑 No valid tokens found, starting authorization flow...");
                 auth.authorize().await.context("Authorization failed")?;
@@ -127,6 +149,12 @@ impl AppSetup {
             .await
             .context("Failed to initialize database schema")?;
 
+        if self.app_state.run_mode().offline {
+            info!("📴 Skipping connectivity check in offline mode");
+            info!("✅ Infrastructure setup completed");
+            return Ok(());
+        }
+
         // Verify connectivity
         let connectivity_status = self.app_state.connectivity().check_connectivity().await;
         info!("📡 Connectivity status: {}", connectivity_status);
@@ -154,10 +182,16 @@ impl AppSetup {
                     profile.mail.as_deref().unwrap_or("No email")
                 );
             }
+            Ok(None) if self.app_state.run_mode().offline => {
+                warn!("⚠️ No stored profile found, not fetching it in offline mode");
+            }
             Ok(None) => {
                 info!("📋 No stored profile found, fetching from API...");
                 self.fetch_and_store_profile(&profile_repo).await?;
             }
+            Err(e) if self.app_state.run_mode().offline => {
+                error!("⚠️ Error retrieving stored profile: {}", e);
+            }
             Err(e) => {
                 error!("⚠️ Error retrieving stored profile: {}", e);
                 info!("🔄 Attempting to fetch fresh profile...");
@@ -233,6 +267,18 @@ async fn main() -> Result<()> {
                 .long("readdirplus")
                 .help("Advertise readdirplus support to the kernel"),
         )
+        .arg(
+            Arg::new("read-only")
+                .long("read-only")
+                .action(ArgAction::SetTrue)
+                .help("Mount read-only: reject every change made through the mount"),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .action(ArgAction::SetTrue)
+                .help("Never use the network: serve downloaded files and stored metadata only"),
+        )
         .get_matches();
 
     // If launched as a file handler, only handle the file and exit
@@ -289,7 +335,11 @@ async fn main() -> Result<()> {
     let mut shutdown_rx = shutdown_manager.subscribe();
 
     // Initialize application
-    let app = AppSetup::initialize().await?;
+    let app = AppSetup::initialize(RunMode {
+        read_only: matches.get_flag("read-only"),
+        offline: matches.get_flag("offline"),
+    })
+    .await?;
     app.authenticate().await?;
     app.setup_infrastructure().await?;
     app.setup_user_profile().await?;
@@ -329,25 +379,32 @@ async fn main() -> Result<()> {
     let mount_point_for_unmount = mount_point.clone();
     let (fuse_tx, fuse_rx) = std::sync::mpsc::channel();
     let mut fuse_shutdown_rx = shutdown_manager.subscribe();
+    let app_state_for_mount = app.app_state.clone();
+    let mut mount_options = vec![
+        MountOption::FSName("onedrive".to_string()),
+        MountOption::NoExec,
+        MountOption::NoSuid,
+        MountOption::NoDev,
+        MountOption::DefaultPermissions,
+        MountOption::NoAtime,
+        MountOption::CUSTOM("case_insensitive".to_string()),
+    ];
+    if app.app_state.run_mode().read_only {
+        mount_options.push(MountOption::RO);
+    }
     let fuse_handle = thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async move {
             // Start FUSE mount in a separate task
             let mount_task = tokio::spawn(async move {
-                let result = fuser::mount2(
-                    fuse_fs,
-                    &mount_point_for_mount,
-                    &[
-                        MountOption::FSName("onedrive".to_string()),
-                        MountOption::NoExec,
-                        MountOption::NoSuid,
-                        MountOption::NoDev,
-                        MountOption::DefaultPermissions,
-                        MountOption::NoAtime,
-                        MountOption::CUSTOM("case_insensitive".to_string()),
-                        
-                    ],
-                );
+                let result = fuser::Session::new(fuse_fs, &mount_point_for_mount, &mount_options)
+                .and_then(|mut session| {
+                    // Let the sync processor invalidate kernel caches on remote changes
+                    app_state_for_mount.kernel_cache().set_notifier(session.notifier());
+                    let result = session.run();
+                    app_state_for_mount.kernel_cache().clear_notifier();
+                    result
+                });
                 if let Err(e) = result {
                     error!("FUSE mount error: {}", e);
                 }
@@ -382,8 +439,22 @@ async fn main() -> Result<()> {
     // Start simple task manager with shutdown handling
     let mut task_manager = crate::scheduler::simple_scheduler::SimpleTaskManager::new();
     
-    // Start sync task
-    task_manager.start_sync_task(app.app_state.clone()).await?;
+    // Start sync and transfer tasks; offline mode never talks to OneDrive
+    if app.app_state.run_mode().offline {
+        info!("📴 Offline mode: sync and transfer tasks not started");
+    } else {
+        task_manager.start_sync_task(app.app_state.clone()).await?;
+        task_manager.start_transfer_task(app.app_state.clone()).await?;
+        task_manager
+            .start_network_conditions_task(app.app_state.clone())
+            .await?;
+        if app.app_state.config().settings.read().await.sync_config.push_notifications {
+            let transport = Arc::new(SocketIoTransport::new(app.app_state.onedrive_client.clone()));
+            task_manager
+                .start_change_notification_task(app.app_state.clone(), transport)
+                .await?;
+        }
+    }
 
     let task_manager_shutdown_rx = shutdown_manager.subscribe();
     let task_manager_handle = tokio::spawn(async move {
@@ -443,6 +514,9 @@ async fn main() -> Result<()> {
     })
     .await;
 
+    // Interrupted downloads resume from their .part file on the next start
+    app.app_state.transfers().cancel_downloads().await;
+
     // Wait for FUSE thread to finish
     let _ = fuse_rx.recv();
     let _ = fuse_handle.join();
@@ -461,7 +535,7 @@ async fn handle_file_path(file_path: &str) -> Result<()> {
     info!("🚀 OneDrive file handler launched for: {}", file_path);
 
     // Initialize minimal app state for database access
-    let app = AppSetup::initialize().await?;
+    let app = AppSetup::initialize(RunMode::default()).await?;
 
     // Check if this is a .onedrivedownload file (new virtual file system)
     if file_path.ends_with(".onedrivedownload") {
@@ -0,0 +1,76 @@
+//! Upload and download rate limits
+//!
+//! All transfers of a direction share one limit. Callers report the bytes they
+//! are about to send (or have just received) and are delayed so the average
+//! rate stays below the limit. The limits can change at any time, 0 means
+//! unlimited.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use onedrive_sync_lib::config::TransferConfig;
+use tokio::time::Instant;
+
+/// Paces the transfers of one direction to a rate in bytes per second
+#[derive(Default)]
+pub struct RateLimiter {
+    bytes_per_sec: AtomicU64,
+    /// When the bytes accounted so far have been transferred at the limit
+    next_free: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Change the limit, 0 for unlimited
+    pub fn set_rate(&self, bytes_per_sec: u64) {
+        self.bytes_per_sec.store(bytes_per_sec, Ordering::Relaxed);
+        // Start over, a backlog from a lower limit must not delay the new one
+        *self.next_free.lock().unwrap() = None;
+    }
+
+    pub fn rate(&self) -> u64 {
+        self.bytes_per_sec.load(Ordering::Relaxed)
+    }
+
+    /// How long a transfer of `bytes` has to wait, accounting for it
+    pub fn reserve(&self, bytes: u64) -> Duration {
+        let rate = self.rate();
+        if rate == 0 || bytes == 0 {
+            return Duration::ZERO;
+        }
+        let now = Instant::now();
+        let mut next_free = self.next_free.lock().unwrap();
+        let start = next_free.map_or(now, |next| next.max(now));
+        *next_free = Some(start + Duration::from_secs_f64(bytes as f64 / rate as f64));
+        start - now
+    }
+
+    /// Wait until `bytes` may be transferred
+    pub async fn consume(&self, bytes: u64) {
+        let wait = self.reserve(bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Rate limits for uploads and downloads
+#[derive(Default)]
+pub struct BandwidthLimiter {
+    pub upload: RateLimiter,
+    pub download: RateLimiter,
+}
+
+impl BandwidthLimiter {
+    pub fn new(config: &TransferConfig) -> Self {
+        let limiter = Self::default();
+        limiter.apply(config);
+        limiter
+    }
+
+    /// Take over the limits of the settings
+    pub fn apply(&self, config: &TransferConfig) {
+        self.upload.set_rate(config.max_upload_bytes_per_sec);
+        self.download.set_rate(config.max_download_bytes_per_sec);
+    }
+}
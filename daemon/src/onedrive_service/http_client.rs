@@ -1,20 +1,235 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
-use log::debug;
-use reqwest::Client;
+use log::{debug, warn};
+use rand::Rng;
+use reqwest::{Client, Method, StatusCode};
 use serde::Serialize;
+use thiserror::Error;
 
 const GRAPH_API_BASE: &str = "https://graph.microsoft.com/v1.0";
 
+/// Error responses of Microsoft Graph the sync logic reacts to
+#[derive(Debug, Error)]
+pub enum GraphError {
+    /// 429, or 503 with a Retry-After header
+    #[error("Throttled by Microsoft Graph ({status}), retry after {retry_after:?}")]
+    Throttled {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The item changed since the eTag sent in `If-Match`
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+    /// 410, e.g. an expired delta token
+    #[error("Gone: {0}")]
+    Gone(String),
+    #[error("Request failed with status {status}: {message}")]
+    Status { status: StatusCode, message: String },
+}
+
+impl GraphError {
+    /// Classify an error status and its Retry-After header
+    pub fn from_status(status: StatusCode, retry_after: Option<Duration>, message: String) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => Self::Throttled { status, retry_after },
+            StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {
+                Self::Throttled { status, retry_after }
+            }
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::PRECONDITION_FAILED => Self::PreconditionFailed(message),
+            StatusCode::GONE => Self::Gone(message),
+            _ => Self::Status { status, message },
+        }
+    }
+
+    /// Classify an error response, reading the message from its body
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.unwrap_or_default();
+        // Graph errors look like {"error":{"code":"...","message":"..."}}
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|json| json["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or(body);
+        Self::from_status(status, retry_after, message)
+    }
+
+    /// Whether trying again later can succeed: throttling and server errors
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Throttled { .. } => true,
+            Self::Status { status, .. } => {
+                status.is_server_error() && *status != StatusCode::NOT_IMPLEMENTED
+            }
+            _ => false,
+        }
+    }
+
+    /// How long the server asked us to wait
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Throttled { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// The `GraphError` behind an error, if a Graph request failed with one
+pub fn graph_error(error: &anyhow::Error) -> Option<&GraphError> {
+    error.chain().find_map(|cause| cause.downcast_ref::<GraphError>())
+}
+
+/// Parse a Retry-After header: seconds or an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// When requests are tried again: throttling, 5xx responses and broken connections
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Longest wait for a Retry-After header
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_retry_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry `attempt` (from 0): the server's Retry-After if it sent one (up to
+    /// `max_retry_after`), otherwise exponential backoff with jitter so clients don't retry in lockstep
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_retry_after);
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_delay);
+        backoff.mul_f64(rand::rng().random_range(0.5..=1.0))
+    }
+}
+
+/// Whether sending a request twice has the same effect as sending it once
+fn is_idempotent(method: &Method) -> bool {
+    !matches!(*method, Method::POST | Method::PATCH)
+}
+
+/// Whether a request failed in a way that may work when sent again. POST and PATCH may
+/// have reached the server before a timeout, so they are only retried if it never got them.
+fn is_transient_send_error(method: &Method, error: &reqwest::Error) -> bool {
+    error.is_connect() || (is_idempotent(method) && (error.is_timeout() || error.is_request()))
+}
+
+/// Whether an error response may be followed by a successful retry. POST and PATCH are
+/// only retried when the server turned them away: 429 and 503.
+fn is_transient_status(method: &Method, error: &GraphError) -> bool {
+    if is_idempotent(method) {
+        return error.is_transient();
+    }
+    match error {
+        GraphError::Throttled { .. } => true,
+        GraphError::Status { status, .. } => *status == StatusCode::SERVICE_UNAVAILABLE,
+        _ => false,
+    }
+}
+
 /// HTTP client for Microsoft Graph API operations
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
+    retry_policy: RetryPolicy,
 }
 
 impl HttpClient {
     pub fn new() -> Self {
+        Self::with_retry_policy(RetryPolicy::default())
+    }
+
+    /// Client with another retry policy, e.g. shorter delays in tests
+    pub fn with_retry_policy(retry_policy: RetryPolicy) -> Self {
         Self {
             client: Client::new(),
+            retry_policy,
+        }
+    }
+
+    /// Send a request under the retry policy. Error statuses that remain become a `GraphError`.
+    /// Requests with a streamed body can't be cloned and are sent once.
+    pub async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let (client, request) = request.build_split();
+        let request = request.context("Failed to build request")?;
+        let method = request.method().clone();
+        let mut attempt = 0;
+        loop {
+            let Some(current) = request.try_clone() else {
+                let response = client.execute(request).await.context("Failed to send request")?;
+                return Self::check_status(response).await;
+            };
+
+            let delay = match client.execute(current).await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let error = GraphError::from_response(response).await;
+                    if !is_transient_status(&method, &error) || attempt >= self.retry_policy.max_retries {
+                        return Err(error.into());
+                    }
+                    let delay = self.retry_policy.delay(attempt, error.retry_after());
+                    warn!("⏳ {}, retrying in {:?}", error, delay);
+                    delay
+                }
+                Err(e) if is_transient_send_error(&method, &e) && attempt < self.retry_policy.max_retries => {
+                    let delay = self.retry_policy.delay(attempt, None);
+                    warn!("⏳ Request failed: {}, retrying in {:?}", e, delay);
+                    delay
+                }
+                Err(e) => return Err(anyhow::Error::new(e).context("Failed to send request")),
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Add an `If-Match` header, so OneDrive answers 412 if the item no longer has this eTag
+    fn with_if_match(request: reqwest::RequestBuilder, if_match: Option<&str>) -> reqwest::RequestBuilder {
+        match if_match {
+            Some(etag) => request.header(reqwest::header::IF_MATCH, etag),
+            None => request,
+        }
+    }
+
+    /// Turn an error status into a `GraphError`
+    async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(GraphError::from_response(response).await.into())
         }
     }
 
@@ -36,14 +251,9 @@ impl HttpClient {
         debug!("Getting url: {}", url);
 
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", auth_header)
-            .send()
+            .send(self.client.get(&url).header("Authorization", auth_header))
             .await
-            .context("Failed to get response")?
-            .error_for_status()
-            .context("Not a success status")?;
+            .context("Failed to get response")?;
 
         let response_json = response
             .json::<T>()
@@ -60,17 +270,16 @@ impl HttpClient {
         B: Serialize,
     {
         let url = self.get_full_url(url)?;
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("Authorization", auth_header)
             .header("Content-Type", "application/json")
-            .json(body)
-            .send()
+            .json(body);
+        let response = self
+            .send(request)
             .await
             .context("Failed to get response for post")?
-            .error_for_status()
-            .context("Not a success status")?
             .json::<T>()
             .await
             .context("Failed to deserialize response to type T")?;
@@ -79,16 +288,12 @@ impl HttpClient {
 
     /// Make a DELETE request with authorization header
     #[allow(dead_code)]
-    pub async fn delete(&self, url: &str, auth_header: &str) -> Result<()> {
+    pub async fn delete(&self, url: &str, auth_header: &str, if_match: Option<&str>) -> Result<()> {
         let url = self.get_full_url(url)?;
-        self.client
-            .delete(&url)
-            .header("Authorization", auth_header)
-            .send()
+        let request = self.client.delete(&url).header("Authorization", auth_header);
+        self.send(Self::with_if_match(request, if_match))
             .await
-            .context("Failed to get response for delete")?
-            .error_for_status()
-            .context("Not a success status")?;
+            .context("Failed to get response for delete")?;
         Ok(())
     }
 
@@ -99,17 +304,16 @@ impl HttpClient {
         T: Serialize + serde::de::DeserializeOwned + std::fmt::Debug,
     {
         let url = self.get_full_url(url)?;
-        let response = self
+        let request = self
             .client
             .put(&url)
             .header("Authorization", auth_header)
             .header("Content-Type", "application/json")
-            .json(body)
-            .send()
+            .json(body);
+        let response = self
+            .send(request)
             .await
             .context("Failed to get response for put")?
-            .error_for_status()
-            .context("Not a success status")?
             .json::<T>()
             .await
             .context("Failed to deserialize response to type T")?;
@@ -118,23 +322,28 @@ impl HttpClient {
 
     /// Make a PATCH request with authorization header
     #[allow(dead_code)]
-    pub async fn patch<T, B>(&self, url: &str, body: &B, auth_header: &str) -> Result<T>
+    pub async fn patch<T, B>(
+        &self,
+        url: &str,
+        body: &B,
+        auth_header: &str,
+        if_match: Option<&str>,
+    ) -> Result<T>
     where
         T: serde::de::DeserializeOwned + std::fmt::Debug,
         B: Serialize,
     {
         let url = self.get_full_url(url)?;
-        let response = self
+        let request = self
             .client
             .patch(&url)
             .header("Authorization", auth_header)
             .header("Content-Type", "application/json")
-            .json(body)
-            .send()
+            .json(body);
+        let response = self
+            .send(Self::with_if_match(request, if_match))
             .await
             .context("Failed to get response for patch")?
-            .error_for_status()
-            .context("Not a success status")?
             .json::<T>()
             .await
             .context("Failed to deserialize response to type T")?;
@@ -144,13 +353,9 @@ impl HttpClient {
     /// Download file content with custom headers
     pub async fn download_file(&self, download_url: &str) -> Result<reqwest::Response> {
         let response = self
-            .client
-            .get(download_url)
-            .send()
+            .send(self.client.get(download_url))
             .await
-            .context("Failed to get response for download")?
-            .error_for_status()
-            .context("Not a success status")?;
+            .context("Failed to get response for download")?;
 
         Ok(response)
     }
@@ -174,17 +379,17 @@ impl HttpClient {
         url: &str,
         file_data: &[u8],
         auth_header: &str,
+        if_match: Option<&str>,
     ) -> Result<reqwest::Response> {
         let url = self.get_full_url(url)?;
-        let response = self
+        let request = self
             .client
             .put(&url)
             .header("Authorization", auth_header)
             .header("Content-Type", "application/octet-stream")
             .header("Content-Length", file_data.len().to_string())
-            .body(file_data.to_vec())
-            .send()
-            .await?;
+            .body(file_data.to_vec());
+        let response = self.send(Self::with_if_match(request, if_match)).await?;
 
         Ok(response)
     }
@@ -195,19 +400,19 @@ impl HttpClient {
         url: &str,
         body: &crate::onedrive_service::onedrive_models::UploadSessionRequest,
         auth_header: &str,
+        if_match: Option<&str>,
     ) -> Result<crate::onedrive_service::onedrive_models::UploadSessionResponse> {
         let url = self.get_full_url(url)?;
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("Authorization", auth_header)
             .header("Content-Type", "application/json")
-            .json(body)
-            .send()
+            .json(body);
+        let response = self
+            .send(Self::with_if_match(request, if_match))
             .await
             .context("Failed to create upload session")?
-            .error_for_status()
-            .context("Not a success status")?
             .json::<crate::onedrive_service::onedrive_models::UploadSessionResponse>()
             .await
             .context("Failed to deserialize upload session response")?;
@@ -222,13 +427,14 @@ impl HttpClient {
         chunk_data: &[u8],
         content_range: &str,
     ) -> Result<reqwest::Response> {
-        let response = self
+        let request = self
             .client
             .put(upload_url)
             .header("Content-Length", chunk_data.len().to_string())
             .header("Content-Range", content_range)
-            .body(chunk_data.to_vec())
-            .send()
+            .body(chunk_data.to_vec());
+        let response = self
+            .send(request)
             .await
             .context("Failed to upload file chunk")?;
 
@@ -241,13 +447,9 @@ impl HttpClient {
         upload_url: &str,
     ) -> Result<crate::onedrive_service::onedrive_models::UploadSessionStatus> {
         let response = self
-            .client
-            .get(upload_url)
-            .send()
+            .send(self.client.get(upload_url))
             .await
             .context("Failed to get upload session status")?
-            .error_for_status()
-            .context("Not a success status")?
             .json::<crate::onedrive_service::onedrive_models::UploadSessionStatus>()
             .await
             .context("Failed to deserialize upload session status")?;
@@ -257,13 +459,9 @@ impl HttpClient {
     #[allow(dead_code)]
     /// Cancel an upload session
     pub async fn cancel_upload_session(&self, upload_url: &str) -> Result<()> {
-        self.client
-            .delete(upload_url)
-            .send()
+        self.send(self.client.delete(upload_url))
             .await
-            .context("Failed to cancel upload session")?
-            .error_for_status()
-            .context("Not a success status")?;
+            .context("Failed to cancel upload session")?;
 
         Ok(())
     }
@@ -300,4 +498,59 @@ mod tests {
     fn test_graph_api_base_constant() {
         assert_eq!(GRAPH_API_BASE, "https://graph.microsoft.com/v1.0");
     }
+
+    #[test]
+    fn test_graph_error_from_status() {
+        let retry_after = Some(Duration::from_secs(7));
+        let throttled = GraphError::from_status(StatusCode::TOO_MANY_REQUESTS, retry_after, String::new());
+        assert!(matches!(throttled, GraphError::Throttled { .. }));
+        assert_eq!(throttled.retry_after(), retry_after);
+        assert!(matches!(
+            GraphError::from_status(StatusCode::SERVICE_UNAVAILABLE, retry_after, String::new()),
+            GraphError::Throttled { .. }
+        ));
+        let unavailable = GraphError::from_status(StatusCode::SERVICE_UNAVAILABLE, None, String::new());
+        assert!(matches!(unavailable, GraphError::Status { .. }));
+        assert!(unavailable.is_transient());
+        assert!(matches!(
+            GraphError::from_status(StatusCode::PRECONDITION_FAILED, None, String::new()),
+            GraphError::PreconditionFailed(_)
+        ));
+        let not_found = GraphError::from_status(StatusCode::NOT_FOUND, None, String::new());
+        assert!(!not_found.is_transient());
+        assert!(!GraphError::from_status(StatusCode::NOT_IMPLEMENTED, None, String::new()).is_transient());
+
+        let error = anyhow::Error::new(not_found).context("Failed to get item");
+        assert!(matches!(graph_error(&error), Some(GraphError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_non_idempotent_requests_retry_only_when_turned_away() {
+        let bad_gateway = GraphError::from_status(StatusCode::BAD_GATEWAY, None, String::new());
+        let unavailable = GraphError::from_status(StatusCode::SERVICE_UNAVAILABLE, None, String::new());
+        let throttled = GraphError::from_status(StatusCode::TOO_MANY_REQUESTS, None, String::new());
+        for method in [Method::GET, Method::PUT, Method::DELETE] {
+            assert!(is_transient_status(&method, &bad_gateway));
+        }
+        for method in [Method::POST, Method::PATCH] {
+            assert!(!is_transient_status(&method, &bad_gateway));
+            assert!(is_transient_status(&method, &unavailable));
+            assert!(is_transient_status(&method, &throttled));
+        }
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(3, Some(Duration::from_secs(120))), Duration::from_secs(120));
+        assert_eq!(policy.delay(3, Some(Duration::from_secs(3600))), policy.max_retry_after);
+        for attempt in 0..10 {
+            let delay = policy.delay(attempt, None);
+            let backoff = Duration::from_secs(1 << attempt).min(policy.max_delay);
+            assert!(delay >= backoff / 2 && delay <= backoff, "{:?} for attempt {}", delay, attempt);
+        }
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
 }
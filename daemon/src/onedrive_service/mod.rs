@@ -1,3 +1,4 @@
+pub mod bandwidth;
 pub mod http_client;
 pub mod onedrive_client;
 pub mod onedrive_models;
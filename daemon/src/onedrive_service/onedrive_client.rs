@@ -1,113 +1,231 @@
 use crate::auth::onedrive_auth::OneDriveAuth;
-use crate::onedrive_service::http_client::HttpClient;
+use crate::onedrive_service::bandwidth::BandwidthLimiter;
+use crate::onedrive_service::http_client::{graph_error, GraphError, HttpClient};
 use crate::onedrive_service::onedrive_models::{
-    CreateFolderResult, DeleteResult, DownloadResult, DriveItem, DeltaResponseApi, FileChunk,
-    UploadProgress, UploadResult, UploadSessionConfig, UploadSessionItem, UploadSessionRequest,
+    CreateFolderResult, DeleteResult, Drive, DownloadResult, DriveItem, DriveQuota,
+    DeltaResponseApi, FileChunk, SocketIoSubscription,
+    UploadProgress, UploadProgressFn, UploadResult, UploadSessionConfig, UploadSessionItem, UploadSessionRequest,
     UploadSessionResponse, UploadSessionStatus, UserProfile,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
 use serde_json;
+use std::io::SeekFrom;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::time::sleep;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use urlencoding;
 
-/// Trait defining the interface for OneDrive client operations
+/// Files up to this size are uploaded in one request, larger ones through an upload session
+pub const LARGE_FILE_THRESHOLD: u64 = 4 * 1024 * 1024; // 4MB
+
+/// Upload session chunks have to be a multiple of 320 KiB
+const UPLOAD_CHUNK_MULTIPLE: u64 = 327680;
+
+/// Trait defining the interface for OneDrive client operations.
+/// Calls changing an existing item take `if_match`, the eTag the change builds on: OneDrive
+/// refuses them with 412 (`GraphError::PreconditionFailed`) if the item changed since.
 #[async_trait]
 pub trait OneDriveClientTrait: Send + Sync {
     
-    // File upload operations
+    // File upload operations, streamed from a local file
     #[allow(dead_code)]
     async fn upload_large_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult>;
     #[allow(dead_code)]
     async fn update_large_file(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         item_id: &str,
+        if_match: Option<&str>,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult>;
-    #[allow(dead_code)]
     async fn upload_file_smart(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<UploadResult>;
+    async fn update_file_smart(
+        &self,
+        file_path: &Path,
+        item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult>;
-    #[allow(dead_code)]
-    async fn update_file_smart(&self, file_data: &[u8], item_id: &str) -> Result<UploadResult>;
-    #[allow(dead_code)]
     async fn resume_large_file_upload(
         &self,
         upload_url: &str,
-        file_data: &[u8],
+        file_path: &Path,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<DriveItem>;
+    /// Upload sessions created separately from the upload, so they can be stored and resumed
+    async fn create_upload_session(&self, parent_id: &str, file_name: &str) -> Result<UploadSessionResponse>;
+    async fn create_update_upload_session(
+        &self,
+        item_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<UploadSessionResponse>;
+    /// Upload a whole file to a new upload session
+    async fn upload_to_session(
+        &self,
+        upload_url: &str,
+        file_path: &Path,
+        config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<DriveItem>;
     #[allow(dead_code)]
     async fn upload_new_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<UploadResult>;
+    #[allow(dead_code)]
+    async fn upload_updated_file(
+        &self,
+        file_path: &Path,
+        item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult>;
-
-    async fn upload_updated_file(&self, file_data: &[u8], item_id: &str) -> Result<UploadResult>;
 
     // File operations
     async fn get_item_by_id(&self, item_id: &str) -> Result<DriveItem>;
-    async fn delete_item(&self, path: &str) -> Result<DeleteResult>;
+    async fn delete_item(&self, path: &str, if_match: Option<&str>) -> Result<DeleteResult>;
     async fn create_folder(&self, parent_path: &str, folder_name: &str) -> Result<CreateFolderResult>;
-    async fn move_item(&self, item_id: &str, new_parent_id: &str) -> Result<DriveItem>;
-    async fn rename_item(&self, item_id: &str, new_name: &str) -> Result<DriveItem>;
+    async fn move_item(&self, item_id: &str, new_parent_id: &str, if_match: Option<&str>) -> Result<DriveItem>;
+    async fn rename_item(&self, item_id: &str, new_name: &str, if_match: Option<&str>) -> Result<DriveItem>;
+    async fn update_last_modified(&self, item_id: &str, last_modified: &str) -> Result<DriveItem>;
 
     // Delta synchronization
     async fn get_delta_changes(&self, delta_token: Option<&str>) -> Result<DeltaResponseApi>;
 
     // Download operations
     async fn download_thumbnail_medium(&self, item_id: &str) -> Result<Vec<u8>>;
-    #[allow(dead_code)]
-    async fn download_file_with_options(
+    /// Download the bytes `start..=end` of a file. Whole files are streamed with `open_download`.
+    async fn download_range(
         &self,
         download_url: &str,
         item_id: &str,
         filename: &str,
-        range: Option<(u64, u64)>,
+        start: u64,
+        end: u64,
     ) -> Result<DownloadResult>;
-    #[allow(dead_code)]
-    async fn download_file(
+    /// Open a download at `offset` to read it chunk by chunk. `if_range` is the ETag of the
+    /// bytes before `offset`; the server sends the whole file if it changed since, and
+    /// without an ETag the download always starts at 0.
+    async fn open_download(
         &self,
         download_url: &str,
         item_id: &str,
-        filename: &str,
-    ) -> Result<DownloadResult>;
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> Result<DownloadStream>;
 
     // User profile
     async fn get_user_profile(&self) -> Result<UserProfile>;
 
+    // Drive quota
+    async fn get_drive_quota(&self) -> Result<DriveQuota>;
+
+    // Change notifications
+    async fn get_socket_io_endpoint(&self) -> Result<String>;
+
     // Test operations
     #[allow(dead_code)]
     async fn test_resumable_upload(&self) -> Result<()>;
 }
 
+/// Body of a download, read chunk by chunk
+#[async_trait]
+pub trait DownloadBody: Send {
+    /// The next part of the body, None at the end; an error means the connection is gone
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>>;
+}
+
+/// An open download, possibly starting in the middle of the file
+pub struct DownloadStream {
+    /// Where the body starts in the file, 0 if the server sends the whole file
+    pub offset: u64,
+    /// ETag of the version being downloaded, as sent by the server
+    pub etag: Option<String>,
+    /// Size of the whole file, if known
+    pub total_size: Option<u64>,
+    pub body: Box<dyn DownloadBody>,
+}
+
+/// Download body read from an HTTP response under the download rate limit
+struct HttpDownloadBody {
+    response: reqwest::Response,
+    bandwidth: Arc<BandwidthLimiter>,
+}
+
+#[async_trait]
+impl DownloadBody for HttpDownloadBody {
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        let chunk = self
+            .response
+            .chunk()
+            .await
+            .context("Failed to read response bytes")?;
+        if let Some(chunk) = &chunk {
+            self.bandwidth.download.consume(chunk.len() as u64).await;
+        }
+        Ok(chunk.map(|chunk| chunk.to_vec()))
+    }
+}
+
+/// Parse a Content-Range header like "bytes 1000-4999/5000" into the start and the total size
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.trim().parse().ok()?;
+    Some((start, total.trim().parse().ok()))
+}
+
+/// Parse range string like "12345-" or "12345-55232"; an open range runs to the end of the file
+pub fn parse_range_string(range_str: &str, total_size: u64) -> Option<(u64, u64)> {
+    let parts: Vec<&str> = range_str.split('-').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let start = parts[0].parse::<u64>().ok()?;
+    let last = total_size.checked_sub(1)?;
+    let end = if parts[1].is_empty() {
+        last
+    } else {
+        parts[1].parse::<u64>().ok()?.min(last)
+    };
+
+    (start <= end).then_some((start, end))
+}
+
 /// OneDrive API client that handles API operations
 /// File system operations are handled by the FileManager trait
 pub struct OneDriveClient {
     http_client: HttpClient,
     auth: Arc<OneDriveAuth>,
+    bandwidth: Arc<BandwidthLimiter>,
 }
 #[allow(dead_code)]
 impl OneDriveClient {
-    pub fn new(auth: Arc<OneDriveAuth>) -> Result<Self> {
+    pub fn new(auth: Arc<OneDriveAuth>, bandwidth: Arc<BandwidthLimiter>) -> Result<Self> {
         Ok(Self {
             http_client: HttpClient::new(),
             auth,
+            bandwidth,
         })
     }
 
@@ -149,7 +267,7 @@ impl OneDriveClient {
 
         let session = self
             .http_client
-            .create_upload_session(&url, &request_body, &auth_header)
+            .create_upload_session(&url, &request_body, &auth_header, None)
             .await?;
 
         info!("Created upload session: {}", session.upload_url);
@@ -157,7 +275,11 @@ impl OneDriveClient {
     }
 
     /// Create an upload session for updating existing files
-    async fn create_update_upload_session(&self, item_id: &str) -> Result<UploadSessionResponse> {
+    async fn create_update_upload_session(
+        &self,
+        item_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<UploadSessionResponse> {
         let auth_header = self.auth_header().await?;
         let url = format!("/me/drive/items/{}/createUploadSession", item_id);
 
@@ -172,143 +294,157 @@ impl OneDriveClient {
 
         let session = self
             .http_client
-            .create_upload_session(&url, &request_body, &auth_header)
+            .create_upload_session(&url, &request_body, &auth_header, if_match)
             .await?;
 
         info!("Created update upload session: {}", session.upload_url);
         Ok(session)
     }
 
-    /// Split file data into chunks
-    fn split_file_into_chunks(&self, file_data: &[u8], chunk_size: u64) -> Vec<FileChunk> {
-        let mut chunks = Vec::new();
-        let total_size = file_data.len() as u64;
-
-        let mut start = 0;
-        while start < total_size {
-            let end = std::cmp::min(start + chunk_size - 1, total_size - 1);
-            let chunk_data = file_data[start as usize..=end as usize].to_vec();
-
-            chunks.push(FileChunk {
-                start,
-                end,
-                data: chunk_data,
-            });
-
-            start = end + 1;
+    /// Chunk size for upload sessions, a multiple of 320 KiB as required by Microsoft
+    fn adjusted_chunk_size(config: &UploadSessionConfig) -> u64 {
+        let adjusted_chunk_size = ((config.chunk_size / UPLOAD_CHUNK_MULTIPLE) * UPLOAD_CHUNK_MULTIPLE)
+            .max(UPLOAD_CHUNK_MULTIPLE);
+        if adjusted_chunk_size != config.chunk_size {
+            warn!(
+                "Adjusted chunk size from {} to {} to meet 320KB requirement",
+                config.chunk_size, adjusted_chunk_size
+            );
         }
+        adjusted_chunk_size
+    }
+
+    /// Size of a file to upload
+    async fn upload_file_size(file_path: &Path) -> Result<u64> {
+        let metadata = tokio::fs::metadata(file_path)
+            .await
+            .with_context(|| format!("Failed to read metadata of {}", file_path.display()))?;
+        Ok(metadata.len())
+    }
 
-        chunks
+    /// Read `len` bytes at `start` of a file
+    async fn read_chunk(file: &mut tokio::fs::File, start: u64, len: u64) -> Result<FileChunk> {
+        file.seek(SeekFrom::Start(start))
+            .await
+            .context("Failed to seek in file to upload")?;
+        let mut data = vec![0u8; len as usize];
+        // Fails if the file was truncated since the upload started
+        file.read_exact(&mut data)
+            .await
+            .context("Failed to read chunk of file to upload")?;
+        Ok(FileChunk {
+            start,
+            end: start + len - 1,
+            data,
+        })
     }
 
-    /// Upload a single chunk with retry logic
-    async fn upload_chunk_with_retry(
+    /// Upload a single chunk. `HttpClient::send` retries it when throttled or on server errors.
+    async fn upload_chunk(
         &self,
         upload_url: &str,
         chunk: &FileChunk,
         total_size: u64,
-        config: &UploadSessionConfig,
     ) -> Result<reqwest::Response> {
         let content_range = format!("bytes {}-{}/{}", chunk.start, chunk.end, total_size);
+        self.bandwidth.upload.consume(chunk.data.len() as u64).await;
+        self.http_client
+            .upload_file_chunk(upload_url, &chunk.data, &content_range)
+            .await
+    }
 
-        for attempt in 0..=config.max_retries {
-            match self
-                .http_client
-                .upload_file_chunk(upload_url, &chunk.data, &content_range)
-                .await
-            {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() || status.as_u16() == 202 {
-                        return Ok(response);
-                    } else {
-                        let error_text = response.text().await.unwrap_or_default();
-                        warn!("Upload chunk failed with status {}: {}", status, error_text);
-                    }
-                }
-                Err(e) => {
-                    warn!("Upload chunk attempt {} failed: {}", attempt + 1, e);
-                }
-            }
+    /// Upload bytes `start..=end` of a file to an upload session. Only one chunk is
+    /// in memory at a time. Returns the response to the last chunk.
+    async fn upload_file_range(
+        &self,
+        upload_url: &str,
+        file: &mut tokio::fs::File,
+        (start, end): (u64, u64),
+        config: &UploadSessionConfig,
+        progress: &mut UploadProgress,
+        on_progress: Option<&UploadProgressFn>,
+    ) -> Result<Option<reqwest::Response>> {
+        let chunk_size = Self::adjusted_chunk_size(config);
+        let mut last_response = None;
+        let mut offset = start;
+
+        while offset <= end {
+            let len = chunk_size.min(end - offset + 1);
+            let chunk = Self::read_chunk(file, offset, len).await?;
+            let response = self
+                .upload_chunk(upload_url, &chunk, progress.total_bytes)
+                .await?;
 
-            if attempt < config.max_retries {
-                let delay = config.retry_delay_ms * (1 << attempt) as u64;
-                sleep(Duration::from_millis(delay)).await;
+            progress.bytes_uploaded += len;
+            progress.chunks_completed += 1;
+            info!(
+                "Upload progress: {:.1}% ({}/{})",
+                progress.bytes_uploaded as f64 / progress.total_bytes as f64 * 100.0,
+                progress.chunks_completed,
+                progress.total_chunks
+            );
+            if let Some(on_progress) = on_progress {
+                on_progress(&*progress).await;
             }
+
+            last_response = Some(response);
+            offset += len;
         }
 
-        Err(anyhow!(
-            "Failed to upload chunk after {} attempts",
-            config.max_retries + 1
-        ))
+        Ok(last_response)
+    }
+
+    /// The uploaded item from the response to the last chunk of an upload session
+    async fn final_chunk_drive_item(response: reqwest::Response) -> Result<DriveItem> {
+        response
+            .json()
+            .await
+            .context("Failed to parse final upload response")
     }
 
-    /// Upload large file using resumable upload session
+    /// Upload large file using resumable upload session, streaming it from disk
     async fn upload_large_file(
         &self,
         upload_url: &str,
-        file_data: &[u8],
+        file_path: &Path,
         config: Option<UploadSessionConfig>,
+        on_progress: Option<UploadProgressFn>,
     ) -> Result<DriveItem> {
         let config = config.unwrap_or_default();
-        let total_size = file_data.len() as u64;
-
-        // Ensure chunk size is a multiple of 320KB as required by Microsoft
-        let adjusted_chunk_size = (config.chunk_size / 327680) * 327680;
-        if adjusted_chunk_size != config.chunk_size {
-            warn!(
-                "Adjusted chunk size from {} to {} to meet 320KB requirement",
-                config.chunk_size, adjusted_chunk_size
-            );
-        }
-
-        let chunks = self.split_file_into_chunks(file_data, adjusted_chunk_size);
+        let mut file = tokio::fs::File::open(file_path)
+            .await
+            .with_context(|| format!("Failed to open {} for upload", file_path.display()))?;
+        let total_size = file.metadata().await?.len();
+        let chunk_size = Self::adjusted_chunk_size(&config);
+
+        let mut progress = UploadProgress {
+            bytes_uploaded: 0,
+            total_bytes: total_size,
+            chunks_completed: 0,
+            total_chunks: total_size.div_ceil(chunk_size) as usize,
+        };
         info!(
-            "Split file into {} chunks of {} bytes each",
-            chunks.len(),
-            adjusted_chunk_size
+            "Uploading {} bytes in {} chunks of {} bytes each",
+            total_size, progress.total_chunks, chunk_size
         );
 
-        let mut completed_chunks = 0;
-        let mut final_response: Option<reqwest::Response> = None;
-
-        for (index, chunk) in chunks.iter().enumerate() {
-            let response = self
-                .upload_chunk_with_retry(upload_url, chunk, total_size, &config)
-                .await?;
-            completed_chunks += 1;
-
-            let progress = (completed_chunks as f64 / chunks.len() as f64) * 100.0;
-            info!(
-                "Upload progress: {:.1}% ({}/{})",
-                progress,
-                completed_chunks,
-                chunks.len()
-            );
-
-            // Store the final response (last chunk)
-            if index == chunks.len() - 1 {
-                final_response = Some(response);
-            }
-        }
+        let final_response = if total_size > 0 {
+            self.upload_file_range(
+                upload_url,
+                &mut file,
+                (0, total_size - 1),
+                &config,
+                &mut progress,
+                on_progress.as_ref(),
+            )
+            .await?
+        } else {
+            None
+        };
 
         // Parse the final response to get the DriveItem
         if let Some(response) = final_response {
-            let status = response.status();
-            if status.is_success() || status.as_u16() == 201 {
-                let drive_item: DriveItem = response
-                    .json()
-                    .await
-                    .context("Failed to parse final upload response")?;
-                return Ok(drive_item);
-            } else {
-                let error_text = response.text().await.unwrap_or_default();
-                return Err(anyhow!(
-                    "Final upload failed with status {}: {}",
-                    status,
-                    error_text
-                ));
-            }
+            return Self::final_chunk_drive_item(response).await;
         }
 
         // Fallback: create a basic DriveItem if we can't parse the response
@@ -326,16 +462,18 @@ impl OneDriveClient {
             download_url: None,
             deleted: None,
             parent_reference: None,
+            web_url: None,
         })
     }
 
     /// Upload large file to parent folder using resumable upload
     pub async fn upload_large_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
         info!(
             "Starting large file upload: {} to parent {}",
@@ -345,7 +483,7 @@ impl OneDriveClient {
         let session = self.create_upload_session(parent_id, file_name).await?;
 
         let drive_item = self
-            .upload_large_file(&session.upload_url, file_data, config)
+            .upload_large_file(&session.upload_url, file_path, config, progress)
             .await?;
 
         let result = UploadResult {
@@ -366,16 +504,18 @@ impl OneDriveClient {
     /// Update large existing file using resumable upload
     pub async fn update_large_file(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         item_id: &str,
+        if_match: Option<&str>,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
         info!("Starting large file update: {}", item_id);
 
-        let session = self.create_update_upload_session(item_id).await?;
+        let session = self.create_update_upload_session(item_id, if_match).await?;
 
         let drive_item = self
-            .upload_large_file(&session.upload_url, file_data, config)
+            .upload_large_file(&session.upload_url, file_path, config, progress)
             .await?;
 
         let result = UploadResult {
@@ -396,49 +536,56 @@ impl OneDriveClient {
     /// Smart upload that automatically chooses between simple and resumable upload
     pub async fn upload_file_smart(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
-        const LARGE_FILE_THRESHOLD: usize = 4 * 1024 * 1024; // 4MB
+        let file_size = Self::upload_file_size(file_path).await?;
 
-        if file_data.len() > LARGE_FILE_THRESHOLD {
+        if file_size > LARGE_FILE_THRESHOLD {
             info!(
                 "File size {} bytes exceeds {} bytes, using resumable upload",
-                file_data.len(),
+                file_size,
                 LARGE_FILE_THRESHOLD
             );
-            self.upload_large_file_to_parent(file_data, file_name, parent_id, None)
+            self.upload_large_file_to_parent(file_path, file_name, parent_id, None, progress)
                 .await
         } else {
             info!(
                 "File size {} bytes is under {} bytes, using simple upload",
-                file_data.len(),
+                file_size,
                 LARGE_FILE_THRESHOLD
             );
-            self.upload_new_file_to_parent(file_data, file_name, parent_id)
+            self.upload_new_file_to_parent(file_path, file_name, parent_id, progress)
                 .await
         }
     }
 
     /// Smart update that automatically chooses between simple and resumable upload
-    pub async fn update_file_smart(&self, file_data: &[u8], item_id: &str) -> Result<UploadResult> {
-        const LARGE_FILE_THRESHOLD: usize = 4 * 1024 * 1024; // 4MB
+    pub async fn update_file_smart(
+        &self,
+        file_path: &Path,
+        item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<UploadResult> {
+        let file_size = Self::upload_file_size(file_path).await?;
 
-        if file_data.len() > LARGE_FILE_THRESHOLD {
+        if file_size > LARGE_FILE_THRESHOLD {
             info!(
                 "File size {} bytes exceeds {} bytes, using resumable update",
-                file_data.len(),
+                file_size,
                 LARGE_FILE_THRESHOLD
             );
-            self.update_large_file(file_data, item_id, None).await
+            self.update_large_file(file_path, item_id, if_match, None, progress).await
         } else {
             info!(
                 "File size {} bytes is under {} bytes, using simple update",
-                file_data.len(),
+                file_size,
                 LARGE_FILE_THRESHOLD
             );
-            self.upload_updated_file(file_data, item_id).await
+            self.upload_updated_file(file_path, item_id, if_match, progress).await
         }
     }
 
@@ -446,8 +593,9 @@ impl OneDriveClient {
     pub async fn resume_large_file_upload(
         &self,
         upload_url: &str,
-        file_data: &[u8],
+        file_path: &Path,
         config: Option<UploadSessionConfig>,
+        on_progress: Option<UploadProgressFn>,
     ) -> Result<DriveItem> {
         info!("Attempting to resume upload at: {}", upload_url);
 
@@ -460,12 +608,15 @@ impl OneDriveClient {
         info!("Upload session status: {:?}", status);
 
         let config = config.unwrap_or_default();
-        let total_size = file_data.len() as u64;
+        let mut file = tokio::fs::File::open(file_path)
+            .await
+            .with_context(|| format!("Failed to open {} for upload", file_path.display()))?;
+        let total_size = file.metadata().await?.len();
 
         // Parse next expected ranges to determine what's missing
         let mut missing_ranges = Vec::new();
         for range_str in &status.next_expected_ranges {
-            if let Some((start, end)) = self.parse_range_string(range_str) {
+            if let Some((start, end)) = parse_range_string(range_str, total_size) {
                 missing_ranges.push((start, end));
             }
         }
@@ -478,40 +629,44 @@ impl OneDriveClient {
 
         info!("Found {} missing ranges to upload", missing_ranges.len());
 
+        let chunk_size = Self::adjusted_chunk_size(&config);
+        let missing_bytes: u64 = missing_ranges.iter().map(|(start, end)| end - start + 1).sum();
+        let mut progress = UploadProgress {
+            bytes_uploaded: total_size - missing_bytes.min(total_size),
+            total_bytes: total_size,
+            chunks_completed: 0,
+            total_chunks: missing_ranges
+                .iter()
+                .map(|(start, end)| (end - start + 1).div_ceil(chunk_size) as usize)
+                .sum(),
+        };
+
         // Upload missing chunks
+        let mut final_response = None;
         for (start, end) in missing_ranges {
-            let chunk_data = file_data[start as usize..=end as usize].to_vec();
-            let chunk = FileChunk {
-                start,
-                end,
-                data: chunk_data,
-            };
-
-            self.upload_chunk_with_retry(upload_url, &chunk, total_size, &config)
-                .await?;
-            info!("Uploaded missing chunk: bytes {}-{}", start, end);
+            if let Some(response) = self
+                .upload_file_range(
+                    upload_url,
+                    &mut file,
+                    (start, end),
+                    &config,
+                    &mut progress,
+                    on_progress.as_ref(),
+                )
+                .await?
+            {
+                final_response = Some(response);
+            }
+            info!("Uploaded missing range: bytes {}-{}", start, end);
         }
 
-        // Get final result
-        self.get_final_upload_result(upload_url).await
-    }
-
-    /// Parse range string like "12345-" or "12345-55232"
-    fn parse_range_string(&self, range_str: &str) -> Option<(u64, u64)> {
-        let parts: Vec<&str> = range_str.split('-').collect();
-        if parts.len() != 2 {
-            return None;
+        // The last chunk answers with the uploaded item
+        match final_response {
+            Some(response) if response.status().as_u16() != 202 => {
+                Self::final_chunk_drive_item(response).await
+            }
+            _ => self.get_final_upload_result(upload_url).await,
         }
-
-        let start = parts[0].parse::<u64>().ok()?;
-        let end = if parts[1].is_empty() {
-            // Open range like "12345-", use a reasonable default
-            start + 10 * 1024 * 1024 - 1 // 10MB chunk
-        } else {
-            parts[1].parse::<u64>().ok()?
-        };
-
-        Some((start, end))
     }
 
     /// Get the final upload result after all chunks are uploaded
@@ -544,6 +699,7 @@ impl OneDriveClient {
             download_url: None,
             deleted: None,
             parent_reference: None,
+            web_url: None,
         })
     }
 
@@ -551,28 +707,59 @@ impl OneDriveClient {
     /// Test function to verify resumable upload functionality
     pub async fn test_resumable_upload(&self) -> Result<()> {
         // Create a test file larger than 4MB
-        let test_data = vec![0u8; 5 * 1024 * 1024]; // 5MB file
         let test_filename = "test_large_file.bin";
+        let test_path = std::env::temp_dir().join(test_filename);
+        tokio::fs::write(&test_path, vec![0u8; 5 * 1024 * 1024]).await?; // 5MB file
         let test_parent_id = "root"; // Use root as parent
 
-        info!("Testing resumable upload with {} bytes", test_data.len());
+        info!("Testing resumable upload with {}", test_path.display());
 
         // Test smart upload (should automatically choose resumable)
         let result = self
-            .upload_file_smart(&test_data, test_filename, test_parent_id)
-            .await?;
+            .upload_file_smart(&test_path, test_filename, test_parent_id, None)
+            .await;
+        let _ = tokio::fs::remove_file(&test_path).await;
+        let result = result?;
 
         info!("Smart upload test completed: {:?}", result);
         Ok(())
     }
 
-    /// Upload a file to a specific parent folder by parent ID (correct Microsoft Graph API format)
+    /// Read a file for a simple upload; larger files have to go through an upload session
+    async fn read_small_file(file_path: &Path) -> Result<Vec<u8>> {
+        let file_size = Self::upload_file_size(file_path).await?;
+        if file_size > LARGE_FILE_THRESHOLD {
+            return Err(anyhow!(
+                "{} has {} bytes, too large for a simple upload",
+                file_path.display(),
+                file_size
+            ));
+        }
+        tokio::fs::read(file_path)
+            .await
+            .with_context(|| format!("Failed to read {} for upload", file_path.display()))
+    }
+
+    /// Progress of a simple upload, sent in one request
+    fn simple_upload_progress(bytes: u64) -> UploadProgress {
+        UploadProgress {
+            bytes_uploaded: bytes,
+            total_bytes: bytes,
+            chunks_completed: 1,
+            total_chunks: 1,
+        }
+    }
+
+    /// Upload a file to a specific parent folder by parent ID (correct Microsoft Graph API format).
+    /// Only for files up to 4MB, `upload_file_smart` picks an upload session for larger ones.
     pub async fn upload_new_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
+        let file_data = Self::read_small_file(file_path).await?;
         let auth_header = self.auth_header().await?;
         let upload_url = format!("/me/drive/items/{}:/{}:/content", parent_id, file_name);
         info!(
@@ -580,14 +767,14 @@ impl OneDriveClient {
             file_name, parent_id, upload_url
         );
 
+        self.bandwidth.upload.consume(file_data.len() as u64).await;
         let response = self
             .http_client
-            .upload_file(&upload_url, file_data, &auth_header)
+            .upload_file(&upload_url, &file_data, &auth_header, None)
             .await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to upload file: {}", error_text));
+        if let Some(progress) = progress {
+            progress(&Self::simple_upload_progress(file_data.len() as u64)).await;
         }
 
         let item: DriveItem = response.json().await?;
@@ -607,24 +794,27 @@ impl OneDriveClient {
         Ok(result)
     }
 
-    /// Update an existing file on OneDrive and return the update result
-
+    /// Update an existing file on OneDrive and return the update result.
+    /// Only for files up to 4MB, `update_file_smart` picks an upload session for larger ones.
     pub async fn upload_updated_file(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
+        let file_data = Self::read_small_file(file_path).await?;
         let auth_header = self.auth_header().await?;
         let url = format!("/me/drive/items/{}/content", item_id);
 
+        self.bandwidth.upload.consume(file_data.len() as u64).await;
         let response = self
             .http_client
-            .upload_file(&url, file_data, &auth_header)
+            .upload_file(&url, &file_data, &auth_header, if_match)
             .await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow!("Failed to update file: {}", error_text));
+        if let Some(progress) = progress {
+            progress(&Self::simple_upload_progress(file_data.len() as u64)).await;
         }
 
         let item: DriveItem = response.json().await?;
@@ -657,7 +847,7 @@ impl OneDriveClient {
 
     /// Delete an item by path and return the delete result
 
-    pub async fn delete_item(&self, path: &str) -> Result<DeleteResult> {
+    pub async fn delete_item(&self, path: &str, if_match: Option<&str>) -> Result<DeleteResult> {
         let auth_header = self.auth_header().await?;
 
         // Strip /drive/root: prefix if present and encode the relative path
@@ -671,7 +861,7 @@ impl OneDriveClient {
         let url = format!("/me/drive/root:{}", encoded_path);
 
         self.http_client
-            .delete(&url, &auth_header)
+            .delete(&url, &auth_header, if_match)
             .await
             .context("Failed to delete item")?;
 
@@ -714,14 +904,19 @@ impl OneDriveClient {
 
     /// Move an item to a new parent folder (PATCH to update parentReference)
 
-    pub async fn move_item(&self, item_id: &str, new_parent_id: &str) -> Result<DriveItem> {
+    pub async fn move_item(
+        &self,
+        item_id: &str,
+        new_parent_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<DriveItem> {
         let auth_header = self.auth_header().await?;
         let url = format!("/me/drive/items/{}", item_id);
         let body = self.build_move_item_body(new_parent_id);
 
         let item: DriveItem = self
             .http_client
-            .patch(&url, &body, &auth_header)
+            .patch(&url, &body, &auth_header, if_match)
             .await
             .context("Failed to move item")?;
 
@@ -771,14 +966,14 @@ impl OneDriveClient {
 
     /// Rename an item (change its name)
 
-    pub async fn rename_item(&self, item_id: &str, new_name: &str) -> Result<DriveItem> {
+    pub async fn rename_item(&self, item_id: &str, new_name: &str, if_match: Option<&str>) -> Result<DriveItem> {
         let auth_header = self.auth_header().await?;
         let url = format!("/me/drive/items/{}", item_id);
         let body = self.build_rename_item_body(new_name);
 
         // Send PATCH request to update the name
         self.http_client
-            .patch::<serde_json::Value, _>(&url, &body, &auth_header)
+            .patch::<serde_json::Value, _>(&url, &body, &auth_header, if_match)
             .await
             .context("Failed to rename item")?;
 
@@ -789,6 +984,31 @@ impl OneDriveClient {
         Ok(updated_item)
     }
 
+    /// Set the client-side modification time (fileSystemInfo.lastModifiedDateTime) of an item
+    pub async fn update_last_modified(&self, item_id: &str, last_modified: &str) -> Result<DriveItem> {
+        let auth_header = self.auth_header().await?;
+        let url = format!("/me/drive/items/{}", item_id);
+        let body = self.build_file_system_info_body(last_modified);
+
+        let item: DriveItem = self
+            .http_client
+            .patch(&url, &body, &auth_header, None)
+            .await
+            .context("Failed to update fileSystemInfo")?;
+
+        debug!("Updated lastModifiedDateTime of {} to {}", item_id, last_modified);
+        Ok(item)
+    }
+
+    /// Build fileSystemInfo request body
+    fn build_file_system_info_body(&self, last_modified: &str) -> serde_json::Value {
+        serde_json::json!({
+            "fileSystemInfo": {
+                "lastModifiedDateTime": last_modified
+            }
+        })
+    }
+
     /// Build rename item request body
 
     fn build_rename_item_body(&self, new_name: &str) -> serde_json::Value {
@@ -840,48 +1060,38 @@ impl OneDriveClient {
             .http_client
             .get_full_url(&rel)
             .context("Failed to build thumbnail url")?;
-        let response = self
+        let request = self
             .http_client
             .request_builder("GET", &url)
-            .header("Authorization", auth_header)
-            .send()
+            .header("Authorization", auth_header);
+        let response = self
+            .http_client
+            .send(request)
             .await
-            .context("Failed to send thumbnail request")?;
-        if !response.status().is_success() {
-            return Err(anyhow!("Thumbnail download failed with status: {}", response.status()));
-        }
+            .context("Failed to download thumbnail")?;
         let bytes = response.bytes().await.context("Failed to read thumbnail bytes")?;
         Ok(bytes.to_vec())
     }
 
-    /// Download file with optional range and thumbnail support
-    pub async fn download_file_with_options(
+    /// Download a byte range of a file, see `OneDriveClientTrait::download_range`
+    pub async fn download_range(
         &self,
         download_url: &str,
         item_id: &str,
         filename: &str,
-        range: Option<(u64, u64)>, // (start, end) bytes
+        start: u64,
+        end: u64,
     ) -> Result<DownloadResult> {
-        // Build the request using the request builder
-        let mut request = self.http_client.request_builder("GET", download_url);
-
-        // Add Range header if specified
-        if let Some((start, end)) = range {
-            let range_header = format!("bytes={}-{}", start, end);
-            request = request.header("Range", range_header);
-        }
+        let request = self
+            .http_client
+            .request_builder("GET", download_url)
+            .header("Range", format!("bytes={}-{}", start, end));
 
-        let response = request
-            .send()
+        let mut response = self
+            .http_client
+            .send(request)
             .await
-            .context("Failed to send download request")?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Download failed with status: {}",
-                response.status()
-            ));
-        }
+            .context("Failed to download file")?;
 
         // Extract headers before consuming the response
         let etag = response
@@ -908,13 +1118,19 @@ impl OneDriveClient {
             .and_then(|v| v.to_str().ok())
             .map(|s| s.to_string());
 
-        let file_data = response
-            .bytes()
+        // Read the body in chunks so the download rate limit can slow it down
+        let mut file_data = Vec::with_capacity(content_length.unwrap_or(0) as usize);
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .context("Failed to read response bytes")?;
+            .context("Failed to read response bytes")?
+        {
+            file_data.extend_from_slice(&chunk);
+            self.bandwidth.download.consume(chunk.len() as u64).await;
+        }
 
         Ok(DownloadResult {
-            file_data: file_data.to_vec(),
+            file_data,
             file_name: filename.to_string(),
             onedrive_id: item_id.to_string(),
             etag,
@@ -924,20 +1140,81 @@ impl OneDriveClient {
         })
     }
 
-    /// Download full file (existing method, updated to use new function)
-    pub async fn download_file(
+    /// GET a download URL from `offset` on. A range is only asked for together with the
+    /// ETag of the bytes before it, without one the whole file is sent.
+    async fn send_download_request(
+        &self,
+        download_url: &str,
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> Result<reqwest::Response> {
+        let mut request = self.http_client.request_builder("GET", download_url);
+        if let Some(etag) = if_range.filter(|_| offset > 0) {
+            request = request
+                .header("Range", format!("bytes={}-", offset))
+                .header("If-Range", etag);
+        }
+        self.http_client.send(request).await
+    }
+
+    /// Open a download at `offset`, see `OneDriveClientTrait::open_download`
+    pub async fn open_download(
         &self,
         download_url: &str,
         item_id: &str,
-        filename: &str,
-    ) -> Result<DownloadResult> {
-        self.download_file_with_options(
-            download_url,
-            item_id,
-            filename,
-            None, // no range = full download
-        )
-        .await
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> Result<DownloadStream> {
+        debug!("Opening download of {} at byte {}", item_id, offset);
+        let response = match self.send_download_request(download_url, offset, if_range).await {
+            Err(e)
+                if offset > 0
+                    && matches!(
+                        graph_error(&e),
+                        Some(GraphError::Status { status, .. })
+                            if *status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE
+                    ) =>
+            {
+                // The file got shorter than what we have, start over
+                warn!("Range from {} not satisfiable for {}, downloading it again", offset, item_id);
+                self.send_download_request(download_url, 0, None).await
+            }
+            result => result,
+        }
+        .context("Failed to download file")?;
+
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+
+        let (offset, total_size) = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            response
+                .headers()
+                .get("content-range")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range)
+                .ok_or_else(|| anyhow!("Partial download of {} without a valid Content-Range", item_id))?
+        } else {
+            // The whole file, also when it changed since `if_range`
+            let content_length = response
+                .headers()
+                .get("content-length")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok());
+            (0, content_length)
+        };
+
+        Ok(DownloadStream {
+            offset,
+            etag,
+            total_size,
+            body: Box::new(HttpDownloadBody {
+                response,
+                bandwidth: self.bandwidth.clone(),
+            }),
+        })
     }
 
     /// Get user profile information from Microsoft Graph API
@@ -961,82 +1238,165 @@ impl OneDriveClient {
         );
         Ok(profile)
     }
+
+    /// Get the storage quota of the user's drive from Microsoft Graph API
+    pub async fn get_drive_quota(&self) -> Result<DriveQuota> {
+        let auth_header = self
+            .auth_header()
+            .await
+            .context("Failed to get auth header")?;
+        let url = "/me/drive?$select=id,driveType,quota";
+
+        let drive: Drive = self
+            .http_client
+            .get(url, &auth_header)
+            .await
+            .context("Failed to get drive")?;
+
+        let quota = drive
+            .quota
+            .ok_or_else(|| anyhow!("Drive {} has no quota facet", drive.id))?;
+        debug!(
+            "Retrieved drive quota: {} of {} bytes used ({} remaining)",
+            quota.used, quota.total, quota.remaining
+        );
+        Ok(quota)
+    }
+
+    /// Get the Socket.IO URL that notifies about changes in the drive
+    pub async fn get_socket_io_endpoint(&self) -> Result<String> {
+        let auth_header = self
+            .auth_header()
+            .await
+            .context("Failed to get auth header")?;
+        let subscription: SocketIoSubscription = self
+            .http_client
+            .get("/me/drive/root/subscriptions/socketIo", &auth_header)
+            .await
+            .context("Failed to get Socket.IO endpoint")?;
+        debug!("Retrieved Socket.IO endpoint {:?}", subscription.id);
+        Ok(subscription.notification_url)
+    }
 }
 
 #[async_trait]
 impl OneDriveClientTrait for OneDriveClient {
     async fn upload_large_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
-        self.upload_large_file_to_parent(file_data, file_name, parent_id, config).await
+        self.upload_large_file_to_parent(file_path, file_name, parent_id, config, progress).await
     }
 
     async fn update_large_file(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         item_id: &str,
+        if_match: Option<&str>,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
-        self.update_large_file(file_data, item_id, config).await
+        self.update_large_file(file_path, item_id, if_match, config, progress).await
     }
 
     async fn upload_file_smart(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
-        self.upload_file_smart(file_data, file_name, parent_id).await
+        self.upload_file_smart(file_path, file_name, parent_id, progress).await
     }
 
-    async fn update_file_smart(&self, file_data: &[u8], item_id: &str) -> Result<UploadResult> {
-        self.update_file_smart(file_data, item_id).await
+    async fn update_file_smart(
+        &self,
+        file_path: &Path,
+        item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<UploadResult> {
+        self.update_file_smart(file_path, item_id, if_match, progress).await
     }
 
     async fn resume_large_file_upload(
         &self,
         upload_url: &str,
-        file_data: &[u8],
+        file_path: &Path,
+        config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<DriveItem> {
+        self.resume_large_file_upload(upload_url, file_path, config, progress).await
+    }
+
+    async fn create_upload_session(&self, parent_id: &str, file_name: &str) -> Result<UploadSessionResponse> {
+        self.create_upload_session(parent_id, file_name).await
+    }
+
+    async fn create_update_upload_session(
+        &self,
+        item_id: &str,
+        if_match: Option<&str>,
+    ) -> Result<UploadSessionResponse> {
+        self.create_update_upload_session(item_id, if_match).await
+    }
+
+    async fn upload_to_session(
+        &self,
+        upload_url: &str,
+        file_path: &Path,
         config: Option<UploadSessionConfig>,
+        progress: Option<UploadProgressFn>,
     ) -> Result<DriveItem> {
-        self.resume_large_file_upload(upload_url, file_data, config).await
+        self.upload_large_file(upload_url, file_path, config, progress).await
     }
 
     async fn upload_new_file_to_parent(
         &self,
-        file_data: &[u8],
+        file_path: &Path,
         file_name: &str,
         parent_id: &str,
+        progress: Option<UploadProgressFn>,
     ) -> Result<UploadResult> {
-        self.upload_new_file_to_parent(file_data, file_name, parent_id).await
+        self.upload_new_file_to_parent(file_path, file_name, parent_id, progress).await
     }
 
-    async fn upload_updated_file(&self, file_data: &[u8], item_id: &str) -> Result<UploadResult> {
-        self.upload_updated_file(file_data, item_id).await
+    async fn upload_updated_file(
+        &self,
+        file_path: &Path,
+        item_id: &str,
+        if_match: Option<&str>,
+        progress: Option<UploadProgressFn>,
+    ) -> Result<UploadResult> {
+        self.upload_updated_file(file_path, item_id, if_match, progress).await
     }
 
     async fn get_item_by_id(&self, item_id: &str) -> Result<DriveItem> {
         self.get_item_by_id(item_id).await
     }
 
-    async fn delete_item(&self, path: &str) -> Result<DeleteResult> {
-        self.delete_item(path).await
+    async fn delete_item(&self, path: &str, if_match: Option<&str>) -> Result<DeleteResult> {
+        self.delete_item(path, if_match).await
     }
 
     async fn create_folder(&self, parent_path: &str, folder_name: &str) -> Result<CreateFolderResult> {
         self.create_folder(parent_path, folder_name).await
     }
 
-    async fn move_item(&self, item_id: &str, new_parent_id: &str) -> Result<DriveItem> {
-        self.move_item(item_id, new_parent_id).await
+    async fn move_item(&self, item_id: &str, new_parent_id: &str, if_match: Option<&str>) -> Result<DriveItem> {
+        self.move_item(item_id, new_parent_id, if_match).await
     }
 
-    async fn rename_item(&self, item_id: &str, new_name: &str) -> Result<DriveItem> {
-        self.rename_item(item_id, new_name).await
+    async fn rename_item(&self, item_id: &str, new_name: &str, if_match: Option<&str>) -> Result<DriveItem> {
+        self.rename_item(item_id, new_name, if_match).await
+    }
+
+    async fn update_last_modified(&self, item_id: &str, last_modified: &str) -> Result<DriveItem> {
+        self.update_last_modified(item_id, last_modified).await
     }
 
     async fn get_delta_changes(&self, delta_token: Option<&str>) -> Result<DeltaResponseApi> {
@@ -1047,29 +1407,39 @@ impl OneDriveClientTrait for OneDriveClient {
         self.download_thumbnail_medium(item_id).await
     }
 
-    async fn download_file_with_options(
+    async fn download_range(
         &self,
         download_url: &str,
         item_id: &str,
         filename: &str,
-        range: Option<(u64, u64)>,
+        start: u64,
+        end: u64,
     ) -> Result<DownloadResult> {
-        self.download_file_with_options(download_url, item_id, filename, range).await
+        self.download_range(download_url, item_id, filename, start, end).await
     }
 
-    async fn download_file(
+    async fn open_download(
         &self,
         download_url: &str,
         item_id: &str,
-        filename: &str,
-    ) -> Result<DownloadResult> {
-        self.download_file(download_url, item_id, filename).await
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> Result<DownloadStream> {
+        self.open_download(download_url, item_id, offset, if_range).await
     }
 
     async fn get_user_profile(&self) -> Result<UserProfile> {
         self.get_user_profile().await
     }
 
+    async fn get_drive_quota(&self) -> Result<DriveQuota> {
+        self.get_drive_quota().await
+    }
+
+    async fn get_socket_io_endpoint(&self) -> Result<String> {
+        self.get_socket_io_endpoint().await
+    }
+
     async fn test_resumable_upload(&self) -> Result<()> {
         self.test_resumable_upload().await
     }
@@ -1080,6 +1450,7 @@ impl Clone for OneDriveClient {
         Self {
             http_client: self.http_client.clone(),
             auth: self.auth.clone(),
+            bandwidth: self.bandwidth.clone(),
         }
     }
 }
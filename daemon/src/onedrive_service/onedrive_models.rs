@@ -58,6 +58,8 @@ pub struct DriveItem {
     pub deleted: Option<DeletedFacet>,
     #[serde(rename = "parentReference")]
     pub parent_reference: Option<ParentReference>,
+    #[serde(rename = "webUrl")]
+    pub web_url: Option<String>,
 }
 #[allow(dead_code)]
 impl DriveItem {
@@ -184,6 +186,18 @@ pub struct UploadResult {
     pub size: Option<u64>,
 }
 
+impl From<DriveItem> for UploadResult {
+    fn from(item: DriveItem) -> Self {
+        Self {
+            onedrive_id: item.id,
+            etag: item.etag,
+            ctag: item.ctag,
+            web_url: item.web_url,
+            size: item.size,
+        }
+    }
+}
+
 /// Represents the result of a folder creation operation
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -228,6 +242,38 @@ pub struct UserProfile {
     pub preferred_language: Option<String>,
 }
 
+/// Storage quota of a drive from Microsoft Graph API
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct DriveQuota {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub used: u64,
+    #[serde(default)]
+    pub remaining: u64,
+    #[serde(default)]
+    pub deleted: u64,
+    pub state: Option<String>,
+}
+
+/// Drive resource returned by `/me/drive`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Drive {
+    pub id: String,
+    #[serde(rename = "driveType")]
+    pub drive_type: Option<String>,
+    pub quota: Option<DriveQuota>,
+}
+
+/// Socket.IO endpoint for change notifications of a drive, from
+/// `/me/drive/root/subscriptions/socketIo`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SocketIoSubscription {
+    pub id: Option<String>,
+    #[serde(rename = "notificationUrl")]
+    pub notification_url: String,
+}
+
 /// Upload session response from Microsoft Graph API
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UploadSessionResponse {
@@ -268,7 +314,6 @@ pub struct FileChunk {
 }
 
 /// Upload progress information
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct UploadProgress {
     pub bytes_uploaded: u64,
@@ -277,20 +322,20 @@ pub struct UploadProgress {
     pub total_chunks: usize,
 }
 
+/// Called after every uploaded chunk; the upload goes on once the returned future is done
+pub type UploadProgressFn =
+    std::sync::Arc<dyn Fn(&UploadProgress) -> futures_util::future::BoxFuture<'static, ()> + Send + Sync>;
+
 /// Upload session configuration
 #[derive(Debug, Clone)]
 pub struct UploadSessionConfig {
     pub chunk_size: u64,
-    pub max_retries: u32,
-    pub retry_delay_ms: u64,
 }
 
 impl Default for UploadSessionConfig {
     fn default() -> Self {
         Self {
             chunk_size: 10 * 1024 * 1024, // 10MB
-            max_retries: 3,
-            retry_delay_ms: 1000,
         }
     }
 }
@@ -305,6 +305,23 @@ impl CachedDriveItemWithFuseRepository {
         Ok(())
     }
 
+    /// Update POSIX attributes set via setattr
+    pub async fn update_posix_attributes(
+        &self,
+        virtual_ino: u64,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        atime: Option<&str>,
+        mtime: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .update_posix_attributes(virtual_ino, mode, uid, gid, atime, mtime)
+            .await?;
+        self.invalidate_cache(virtual_ino).await;
+        Ok(())
+    }
+
     /// Delete a drive item with Fuse metadata by OneDrive ID
     pub async fn delete_drive_item_with_fuse(&self, onedrive_id: &str) -> Result<()> {
         // Get the item first to know its inode for cache invalidation
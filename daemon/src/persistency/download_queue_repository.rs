@@ -1,4 +1,5 @@
 //! DownloadQueueRepository: Handles download_queue table operations
+use crate::persistency::types::PendingDownload;
 use anyhow::{Context, Result};
 use log::{debug, warn};
 use sqlx::{Pool, Row, Sqlite};
@@ -41,13 +42,15 @@ impl DownloadQueueRepository {
         Ok(())
     }
 
-    /// Get pending download items
+    /// Get pending download items. Retried downloads wait `retry_count` times 30 seconds.
     pub async fn get_pending_downloads(&self) -> Result<Vec<(i64, String, PathBuf)>> {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, local_path
             FROM download_queue 
             WHERE status = 'pending' 
+              AND (retry_count = 0
+                   OR updated_at <= datetime('now', printf('-%d seconds', retry_count * 30)))
             ORDER BY priority DESC, created_at ASC
             "#,
         )
@@ -65,6 +68,48 @@ impl DownloadQueueRepository {
         Ok(items)
     }
 
+    /// Get the next `limit` pending downloads with the name and size of their items.
+    /// Files of `max_size` bytes or more are left out.
+    pub async fn get_next_pending_downloads(
+        &self,
+        limit: usize,
+        max_size: Option<u64>,
+    ) -> Result<Vec<PendingDownload>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT q.id, q.drive_item_id, q.local_path, d.name, COALESCE(d.size, 0) AS size
+            FROM download_queue q
+            LEFT JOIN drive_items_with_fuse d ON d.onedrive_id = q.drive_item_id
+            WHERE q.status = 'pending'
+              AND (q.retry_count = 0
+                   OR q.updated_at <= datetime('now', printf('-%d seconds', q.retry_count * 30)))
+              AND (? IS NULL OR COALESCE(d.size, 0) < ?)
+            ORDER BY q.priority DESC, q.created_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(max_size.map(|size| size as i64))
+        .bind(max_size.map(|size| size as i64))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut items = Vec::new();
+        for row in rows {
+            let local_path: String = row.try_get("local_path")?;
+            let size: i64 = row.try_get("size")?;
+            items.push(PendingDownload {
+                queue_id: row.try_get("id")?,
+                drive_item_id: row.try_get("drive_item_id")?,
+                local_path: PathBuf::from(local_path),
+                name: row.try_get("name")?,
+                size: size.max(0) as u64,
+            });
+        }
+
+        Ok(items)
+    }
+
     /// Mark download as completed
     pub async fn mark_download_completed(&self, queue_id: i64) -> Result<()> {
         sqlx::query(
@@ -103,6 +148,66 @@ impl DownloadQueueRepository {
         Ok(())
     }
 
+    /// Bytes of a download already in its `.part` file and the ETag of that version
+    pub async fn get_download_offset(&self, queue_id: i64) -> Result<(u64, Option<String>)> {
+        let row = sqlx::query("SELECT bytes_received, etag FROM download_queue WHERE id = ?")
+            .bind(queue_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match row {
+            Some(row) => {
+                let bytes_received: Option<i64> = row.try_get("bytes_received")?;
+                let etag: Option<String> = row.try_get("etag")?;
+                Ok((bytes_received.unwrap_or(0).max(0) as u64, etag))
+            }
+            None => Ok((0, None)),
+        }
+    }
+
+    /// Record how much of a download is safely in its `.part` file
+    pub async fn set_download_offset(
+        &self,
+        queue_id: i64,
+        bytes_received: u64,
+        etag: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE download_queue 
+            SET bytes_received = ?, etag = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(bytes_received as i64)
+        .bind(etag)
+        .bind(queue_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue an interrupted download again to resume it. Returns false if nothing
+    /// was received yet or it was already retried `max_retries` times.
+    pub async fn retry_download(&self, queue_id: i64, max_retries: i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE download_queue 
+            SET status = 'pending', retry_count = retry_count + 1, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND bytes_received > 0 AND retry_count < ?
+            "#,
+        )
+        .bind(queue_id)
+        .bind(max_retries)
+        .execute(&self.pool)
+        .await?;
+
+        let retried = result.rows_affected() > 0;
+        if retried {
+            debug!("Download {} queued again to resume", queue_id);
+        }
+        Ok(retried)
+    }
+
     /// Remove item from download queue by drive_item_id
     pub async fn remove_by_drive_item_id(&self, drive_item_id: &str) -> Result<()> {
         sqlx::query(
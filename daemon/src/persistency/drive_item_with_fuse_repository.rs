@@ -58,7 +58,7 @@ impl DriveItemWithFuseRepository {
                 r#"
                 UPDATE drive_items_with_fuse SET
                     name = ?, etag = ?, ctag = ?, last_modified = ?, created_date = ?, size = ?, is_folder = ?,
-                    mime_type = ?, download_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?, 
+                    mime_type = ?, download_url = ?, web_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?, 
                     parent_ino = ?, virtual_path = ?,  file_source = ?, sync_status = ?,
                     updated_at = CURRENT_TIMESTAMP
                 WHERE onedrive_id = ?
@@ -73,6 +73,7 @@ impl DriveItemWithFuseRepository {
             .bind(item.drive_item.folder.is_some())
             .bind(item.drive_item.file.as_ref().and_then(|f| f.mime_type.clone()))
             .bind(&item.drive_item.download_url)
+            .bind(&item.drive_item.web_url)
             .bind(item.drive_item.deleted.is_some())
             .bind(parent_id)
             .bind(parent_path)
@@ -97,9 +98,9 @@ impl DriveItemWithFuseRepository {
                 r#"
                 INSERT INTO drive_items_with_fuse (
                     onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                    mime_type, download_url, is_deleted, parent_id, parent_path, 
+                    mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
                     parent_ino, virtual_path, file_source, sync_status
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
             )
             .bind(&item.drive_item.id)
@@ -117,6 +118,7 @@ impl DriveItemWithFuseRepository {
                     .and_then(|f| f.mime_type.clone()),
             )
             .bind(&item.drive_item.download_url)
+            .bind(&item.drive_item.web_url)
             .bind(item.drive_item.deleted.is_some())
             .bind(parent_id)
             .bind(parent_path)
@@ -147,8 +149,8 @@ impl DriveItemWithFuseRepository {
         let row = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE onedrive_id = ?
             "#,
         )
@@ -171,8 +173,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
         SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-               mime_type, download_url, is_deleted, parent_id, parent_path, 
-               parent_ino, virtual_path,  file_source, sync_status
+               mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+               parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
         FROM drive_items_with_fuse WHERE 
         onedrive_id  in 
 	    (
@@ -198,8 +200,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse ORDER BY name
             "#,
         )
@@ -224,8 +226,8 @@ impl DriveItemWithFuseRepository {
             sqlx::query(
                 r#"
                 SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                       mime_type, download_url, is_deleted, parent_id, parent_path, 
-                       parent_ino, virtual_path,  file_source, sync_status
+                       mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                       parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
                 FROM drive_items_with_fuse where parent_path = '/drive/root:' AND is_deleted = 0 ORDER BY name
                 "#,
             )
@@ -235,8 +237,8 @@ impl DriveItemWithFuseRepository {
             sqlx::query(
                 r#"
                 SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                       mime_type, download_url, is_deleted, parent_id, parent_path, 
-                       parent_ino, virtual_path,  file_source, sync_status
+                       mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                       parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
                 FROM drive_items_with_fuse where REPLACE(parent_path , '/drive/root:' , '') = ? AND is_deleted = 0 ORDER BY name
                 "#,
             )
@@ -262,8 +264,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_id = ? AND is_deleted = 0 ORDER BY name
             "#,
         )
@@ -288,8 +290,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_ino = ? AND is_deleted = 0 ORDER BY name
             "#,
         )
@@ -316,8 +318,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_ino = ? AND is_deleted = 0 ORDER BY virtual_ino LIMIT ? OFFSET ?
             "#,
         )
@@ -346,8 +348,8 @@ impl DriveItemWithFuseRepository {
     ) -> Result<Vec<DriveItemWithFuse>> {
         let mut base = String::from(
             r#"SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                       mime_type, download_url, is_deleted, parent_id, parent_path, 
-                       parent_ino, virtual_path,  file_source, sync_status
+                       mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                       parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
                 FROM drive_items_with_fuse 
                 WHERE is_deleted = 0 AND is_folder = 0 AND (
                     name like '%.jpg' OR name like '%.png' OR name like '%.mp4' OR name like '%.mov' OR name like '%.heic'
@@ -417,8 +419,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE file_source = ? ORDER BY name
             "#,
         )
@@ -443,8 +445,8 @@ impl DriveItemWithFuseRepository {
         let row = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE virtual_path = ? AND is_deleted = 0
             "#,
         )
@@ -468,8 +470,8 @@ impl DriveItemWithFuseRepository {
         let row = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE virtual_ino = ?
             "#,
         )
@@ -510,6 +512,58 @@ impl DriveItemWithFuseRepository {
         debug!("Updated Fuse metadata for drive item: {}", onedrive_id);
         Ok(())
     }
+
+    /// Update POSIX attributes set via setattr. Only the given values are changed.
+    pub async fn update_posix_attributes(
+        &self,
+        virtual_ino: u64,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        atime: Option<&str>,
+        mtime: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE drive_items_with_fuse
+            SET mode = COALESCE(?, mode), uid = COALESCE(?, uid), gid = COALESCE(?, gid),
+                atime = COALESCE(?, atime), mtime = COALESCE(?, mtime),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE virtual_ino = ?
+            "#,
+        )
+        .bind(mode.map(|m| m as i64))
+        .bind(uid.map(|u| u as i64))
+        .bind(gid.map(|g| g as i64))
+        .bind(atime)
+        .bind(mtime)
+        .bind(virtual_ino as i64)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("Updated POSIX attributes for inode: {}", virtual_ino);
+        Ok(())
+    }
+
+    /// Drop a locally set modification time once it has been uploaded
+    pub async fn clear_mtime_override(&self, virtual_ino: u64) -> Result<()> {
+        sqlx::query("UPDATE drive_items_with_fuse SET mtime = NULL WHERE virtual_ino = ?")
+            .bind(virtual_ino as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop locally set access/modification times, e.g. when the file changed remotely
+    pub async fn clear_time_overrides(&self, virtual_ino: u64) -> Result<()> {
+        sqlx::query(
+            "UPDATE drive_items_with_fuse SET atime = NULL, mtime = NULL WHERE virtual_ino = ?",
+        )
+        .bind(virtual_ino as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
     
 
     /// Delete a drive item with Fuse metadata by OneDrive ID
@@ -571,8 +625,8 @@ impl DriveItemWithFuseRepository {
         let rows = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_id = ? AND is_deleted = 0
             "#,
         )
@@ -619,8 +673,8 @@ impl DriveItemWithFuseRepository {
         let row = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_ino = ? AND name = ? AND is_deleted = 0
             "#,
         )
@@ -646,8 +700,8 @@ impl DriveItemWithFuseRepository {
         let row = sqlx::query(
             r#"
             SELECT virtual_ino, onedrive_id, name, etag, ctag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path, 
-                   parent_ino, virtual_path,  file_source, sync_status
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path, 
+                   parent_ino, virtual_path,  file_source, sync_status, mode, uid, gid, atime, mtime
             FROM drive_items_with_fuse WHERE parent_ino = ? AND LOWER(name) = LOWER(?) AND is_deleted = 0
             "#,
         )
@@ -705,6 +759,7 @@ impl DriveItemWithFuseRepository {
         let is_folder: bool = row.try_get("is_folder")?;
         let mime_type: Option<String> = row.try_get("mime_type")?;
         let download_url: Option<String> = row.try_get("download_url")?;
+        let web_url: Option<String> = row.try_get("web_url")?;
         let is_deleted: bool = row.try_get("is_deleted")?;
         let parent_id: Option<String> = row.try_get("parent_id")?;
         let parent_path: Option<String> = row.try_get("parent_path")?;
@@ -754,6 +809,7 @@ impl DriveItemWithFuseRepository {
             download_url,
             deleted,
             parent_reference,
+            web_url,
         };
 
         // Extract Fuse metadata fields
@@ -761,6 +817,11 @@ impl DriveItemWithFuseRepository {
         let virtual_path: Option<String> = row.try_get("virtual_path")?;
         let file_source_str: Option<String> = row.try_get("file_source")?;
         let sync_status: Option<String> = row.try_get("sync_status")?;
+        let mode: Option<i64> = row.try_get("mode")?;
+        let uid: Option<i64> = row.try_get("uid")?;
+        let gid: Option<i64> = row.try_get("gid")?;
+        let atime: Option<String> = row.try_get("atime")?;
+        let mtime: Option<String> = row.try_get("mtime")?;
 
         // Convert file source string to enum
         let file_source = file_source_str.and_then(|s| match s.as_str() {
@@ -778,6 +839,11 @@ impl DriveItemWithFuseRepository {
             file_source,
             sync_status,
             ctag,
+            mode: mode.map(|m| m as u32),
+            uid: uid.map(|u| u as u32),
+            gid: gid.map(|g| g as u32),
+            atime,
+            mtime,
         };
 
         Ok(DriveItemWithFuse {
@@ -0,0 +1,79 @@
+//! DriveQuotaRepository: Handles drive_quota table operations
+use crate::onedrive_service::onedrive_models::DriveQuota;
+use anyhow::Result;
+use log::debug;
+use sqlx::{Pool, Row, Sqlite};
+
+/// Database operations for the cached drive quota
+#[derive(Clone)]
+pub struct DriveQuotaRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl DriveQuotaRepository {
+    /// Create a new drive quota repository
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Store drive quota (always overwrites - only one record)
+    pub async fn store_quota(&self, quota: &DriveQuota) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO drive_quota (id, total, used, remaining, deleted, state, updated_at)
+            VALUES (1, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(quota.total as i64)
+        .bind(quota.used as i64)
+        .bind(quota.remaining as i64)
+        .bind(quota.deleted as i64)
+        .bind(&quota.state)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "Stored drive quota: {} of {} bytes used",
+            quota.used, quota.total
+        );
+        Ok(())
+    }
+
+    /// Get the cached drive quota
+    pub async fn get_quota(&self) -> Result<Option<DriveQuota>> {
+        let row = sqlx::query(
+            r#"
+            SELECT total, used, remaining, deleted, state
+            FROM drive_quota WHERE id = 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(row) = row {
+            let total: i64 = row.try_get("total")?;
+            let used: i64 = row.try_get("used")?;
+            let remaining: i64 = row.try_get("remaining")?;
+            let deleted: i64 = row.try_get("deleted")?;
+            let state: Option<String> = row.try_get("state")?;
+
+            Ok(Some(DriveQuota {
+                total: total as u64,
+                used: used as u64,
+                remaining: remaining as u64,
+                deleted: deleted as u64,
+                state,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Clear the cached drive quota
+    pub async fn clear_quota(&self) -> Result<()> {
+        sqlx::query("DELETE FROM drive_quota")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
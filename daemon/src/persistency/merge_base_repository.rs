@@ -0,0 +1,76 @@
+//! MergeBaseRepository: Handles merge_bases table operations
+use anyhow::Result;
+use log::debug;
+use sqlx::{Pool, Row, Sqlite};
+
+/// Last synced content of a text file, the common ancestor for a three-way merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeBase {
+    pub ctag: String,
+    pub content: String,
+}
+
+/// Database operations for the last synced version of text files.
+/// A base is only valid while its ctag matches the ctag stored for the item.
+#[derive(Clone)]
+pub struct MergeBaseRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl MergeBaseRepository {
+    /// Create a new merge base repository
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Store the synced content of an item, replacing the previous base
+    pub async fn store_base(&self, drive_item_id: &str, ctag: &str, content: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO merge_bases (drive_item_id, ctag, content, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(drive_item_id)
+        .bind(ctag)
+        .bind(content)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("🧬 Stored merge base of {} ({})", drive_item_id, ctag);
+        Ok(())
+    }
+
+    /// Get the stored base of an item
+    pub async fn get_base(&self, drive_item_id: &str) -> Result<Option<MergeBase>> {
+        let row = sqlx::query("SELECT ctag, content FROM merge_bases WHERE drive_item_id = ?")
+            .bind(drive_item_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(MergeBase {
+                ctag: row.try_get("ctag")?,
+                content: row.try_get("content")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove the base of an item
+    pub async fn remove_base(&self, drive_item_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM merge_bases WHERE drive_item_id = ?")
+            .bind(drive_item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Clear all bases
+    pub async fn clear_all_items(&self) -> Result<()> {
+        sqlx::query("DELETE FROM merge_bases")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -6,7 +6,11 @@
 pub mod cached_drive_item_with_fuse_repository;
 pub mod download_queue_repository;
 pub mod drive_item_with_fuse_repository;
+pub mod drive_quota_repository;
+pub mod merge_base_repository;
+pub mod pinned_item_repository;
 pub mod sync_state_repository;
+pub mod upload_session_repository;
 
 pub mod processing_item_repository;
 pub mod profile_repository;
@@ -29,6 +33,10 @@ pub struct PersistencyManager {
     drive_item_with_fuse_repo: OnceLock<drive_item_with_fuse_repository::DriveItemWithFuseRepository>,
     download_queue_repo: OnceLock<download_queue_repository::DownloadQueueRepository>,
     user_profile_repo: OnceLock<profile_repository::ProfileRepository>,
+    drive_quota_repo: OnceLock<drive_quota_repository::DriveQuotaRepository>,
+    pinned_item_repo: OnceLock<pinned_item_repository::PinnedItemRepository>,
+    merge_base_repo: OnceLock<merge_base_repository::MergeBaseRepository>,
+    upload_session_repo: OnceLock<upload_session_repository::UploadSessionRepository>,
 }
 
 impl PersistencyManager {
@@ -87,6 +95,10 @@ impl PersistencyManager {
             drive_item_with_fuse_repo: OnceLock::new(),
             download_queue_repo: OnceLock::new(),
             user_profile_repo: OnceLock::new(),
+            drive_quota_repo: OnceLock::new(),
+            pinned_item_repo: OnceLock::new(),
+            merge_base_repo: OnceLock::new(),
+            upload_session_repo: OnceLock::new(),
         })
     }
 
@@ -111,6 +123,11 @@ impl PersistencyManager {
         self.create_download_queue_table().await?;
         self.create_user_profiles_table().await?;
         self.create_processing_items_table().await?;
+        self.create_drive_quota_table().await?;
+        self.create_pinned_items_table().await?;
+        self.create_merge_bases_table().await?;
+        self.create_upload_sessions_table().await?;
+        self.migrate_schema().await?;
 
         info!("Database schema initialized successfully");
         Ok(())
@@ -132,6 +149,7 @@ impl PersistencyManager {
                 is_folder BOOLEAN,
                 mime_type TEXT,
                 download_url TEXT,
+                web_url TEXT,
                 is_deleted BOOLEAN DEFAULT FALSE,
                 parent_id TEXT,
                 parent_path TEXT,
@@ -139,6 +157,11 @@ impl PersistencyManager {
                 virtual_path TEXT,
                 file_source TEXT,
                 sync_status TEXT,
+                mode INTEGER,
+                uid INTEGER,
+                gid INTEGER,
+                atime TEXT,
+                mtime TEXT,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )
@@ -213,6 +236,8 @@ impl PersistencyManager {
                 priority INTEGER DEFAULT 0,
                 status TEXT DEFAULT 'pending',
                 retry_count INTEGER DEFAULT 0,
+                bytes_received INTEGER DEFAULT 0,
+                etag TEXT,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (drive_item_id) REFERENCES drive_items_with_fuse(onedrive_id)
@@ -267,6 +292,7 @@ impl PersistencyManager {
                 is_folder BOOLEAN,
                 mime_type TEXT,
                 download_url TEXT,
+                web_url TEXT,
                 is_deleted BOOLEAN,
                 parent_id TEXT,
                 parent_path TEXT,
@@ -314,6 +340,133 @@ impl PersistencyManager {
         Ok(())
     }
 
+    /// Create the upload_sessions table for resuming large uploads after a restart
+    async fn create_upload_sessions_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS upload_sessions (
+                processing_item_id INTEGER PRIMARY KEY,
+                drive_item_id TEXT NOT NULL,
+                upload_url TEXT NOT NULL,
+                expiration TEXT NOT NULL,
+                file_size INTEGER NOT NULL,
+                file_modified INTEGER NOT NULL,
+                committed_ranges TEXT NOT NULL DEFAULT '',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Add columns introduced after a table was first created.
+    /// `CREATE TABLE IF NOT EXISTS` leaves existing databases untouched, so new
+    /// columns have to be added explicitly.
+    async fn migrate_schema(&self) -> Result<()> {
+        self.add_column_if_missing("drive_items_with_fuse", "web_url", "TEXT")
+            .await?;
+        self.add_column_if_missing("processing_items", "web_url", "TEXT")
+            .await?;
+        for (column, definition) in [
+            ("mode", "INTEGER"),
+            ("uid", "INTEGER"),
+            ("gid", "INTEGER"),
+            ("atime", "TEXT"),
+            ("mtime", "TEXT"),
+        ] {
+            self.add_column_if_missing("drive_items_with_fuse", column, definition)
+                .await?;
+        }
+        self.add_column_if_missing("download_queue", "bytes_received", "INTEGER DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("download_queue", "etag", "TEXT")
+            .await?;
+        Ok(())
+    }
+
+    /// Add a column to a table unless it already exists
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?",
+        )
+        .bind(table)
+        .bind(column)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if exists == 0 {
+            sqlx::query(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("Failed to add column {}.{}", table, column))?;
+            info!("Added column {}.{} to existing database", table, column);
+        }
+
+        Ok(())
+    }
+
+    /// Create the drive_quota table for caching the OneDrive storage quota
+    async fn create_drive_quota_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS drive_quota (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                total INTEGER NOT NULL DEFAULT 0,
+                used INTEGER NOT NULL DEFAULT 0,
+                remaining INTEGER NOT NULL DEFAULT 0,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                state TEXT,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create the pinned_items table for files pinned for automatic download
+    async fn create_pinned_items_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pinned_items (
+                virtual_ino INTEGER PRIMARY KEY,
+                pinned_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create the merge_bases table for the last synced content of text files
+    async fn create_merge_bases_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS merge_bases (
+                drive_item_id TEXT PRIMARY KEY,
+                ctag TEXT NOT NULL,
+                content TEXT NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Get the processing item repository (singleton)
     pub fn processing_item_repository(
         &self,
@@ -352,6 +505,34 @@ impl PersistencyManager {
             profile_repository::ProfileRepository::new(self.pool.clone())
         }).clone()
     }
+
+    /// Get the drive quota repository (singleton)
+    pub fn drive_quota_repository(&self) -> drive_quota_repository::DriveQuotaRepository {
+        self.drive_quota_repo.get_or_init(|| {
+            drive_quota_repository::DriveQuotaRepository::new(self.pool.clone())
+        }).clone()
+    }
+
+    /// Get the pinned item repository (singleton)
+    pub fn pinned_item_repository(&self) -> pinned_item_repository::PinnedItemRepository {
+        self.pinned_item_repo.get_or_init(|| {
+            pinned_item_repository::PinnedItemRepository::new(self.pool.clone())
+        }).clone()
+    }
+
+    /// Get the merge base repository (singleton)
+    pub fn merge_base_repository(&self) -> merge_base_repository::MergeBaseRepository {
+        self.merge_base_repo.get_or_init(|| {
+            merge_base_repository::MergeBaseRepository::new(self.pool.clone())
+        }).clone()
+    }
+
+    /// Get the upload session repository (singleton)
+    pub fn upload_session_repository(&self) -> upload_session_repository::UploadSessionRepository {
+        self.upload_session_repo.get_or_init(|| {
+            upload_session_repository::UploadSessionRepository::new(self.pool.clone())
+        }).clone()
+    }
 }
 
 impl Drop for PersistencyManager {
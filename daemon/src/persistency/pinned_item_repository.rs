@@ -0,0 +1,73 @@
+//! PinnedItemRepository: Handles pinned_items table operations
+use anyhow::Result;
+use log::debug;
+use sqlx::{Pool, Sqlite};
+
+/// Database operations for files pinned for automatic download.
+/// Pins are keyed by inode because OneDrive ids change when a local file is first uploaded.
+#[derive(Clone)]
+pub struct PinnedItemRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl PinnedItemRepository {
+    /// Create a new pinned item repository
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Pin an item so it is kept downloaded
+    pub async fn pin(&self, virtual_ino: u64) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO pinned_items (virtual_ino) VALUES (?)")
+            .bind(virtual_ino as i64)
+            .execute(&self.pool)
+            .await?;
+
+        debug!("📌 Pinned item with inode {}", virtual_ino);
+        Ok(())
+    }
+
+    /// Remove the pin from an item
+    pub async fn unpin(&self, virtual_ino: u64) -> Result<()> {
+        sqlx::query("DELETE FROM pinned_items WHERE virtual_ino = ?")
+            .bind(virtual_ino as i64)
+            .execute(&self.pool)
+            .await?;
+
+        debug!("📌 Unpinned item with inode {}", virtual_ino);
+        Ok(())
+    }
+
+    /// Check if an item is pinned by inode
+    pub async fn is_pinned(&self, virtual_ino: u64) -> Result<bool> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pinned_items WHERE virtual_ino = ?")
+                .bind(virtual_ino as i64)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(count > 0)
+    }
+
+    /// Check if an item is pinned by OneDrive id
+    pub async fn is_pinned_by_onedrive_id(&self, onedrive_id: &str) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM pinned_items p
+            JOIN drive_items_with_fuse d ON d.virtual_ino = p.virtual_ino
+            WHERE d.onedrive_id = ?
+            "#,
+        )
+        .bind(onedrive_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count > 0)
+    }
+
+    /// Clear all pins
+    pub async fn clear_all_items(&self) -> Result<()> {
+        sqlx::query("DELETE FROM pinned_items")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -77,6 +77,8 @@ pub enum ChangeOperation {
     Delete,
     Move , 
     Rename , 
+    /// Only the modification time changed, e.g. via `touch -d`
+    Touch,
     NoChange,
 }
 
@@ -88,6 +90,7 @@ impl ChangeOperation {
             ChangeOperation::Delete => "delete",
             ChangeOperation::Move { .. } => "move",
             ChangeOperation::Rename { .. } => "rename",
+            ChangeOperation::Touch => "touch",
             ChangeOperation::NoChange => "no_change",
         }
     }
@@ -99,6 +102,7 @@ impl ChangeOperation {
             "delete" => Some(ChangeOperation::Delete),
             "move" => Some(ChangeOperation::Move ),
             "rename" => Some(ChangeOperation::Rename ),
+            "touch" => Some(ChangeOperation::Touch),
             "no_change" => Some(ChangeOperation::NoChange),
             _ => None,
         }
@@ -265,10 +269,10 @@ impl ProcessingItemRepository {
             r#"
             INSERT INTO processing_items (
                 drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                mime_type, download_url, is_deleted, parent_id, parent_path,
+                mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                 status, error_message, last_status_update, retry_count, priority,
                 change_type, change_operation, conflict_resolution, validation_errors, user_decision
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&item.drive_item.id)
@@ -285,6 +289,7 @@ impl ProcessingItemRepository {
                 .and_then(|f| f.mime_type.clone()),
         )
         .bind(&item.drive_item.download_url)
+        .bind(&item.drive_item.web_url)
         .bind(item.drive_item.deleted.is_some())
         .bind(parent_id)
         .bind(parent_path)
@@ -318,7 +323,7 @@ impl ProcessingItemRepository {
         let row = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items WHERE id = ?
@@ -341,7 +346,7 @@ impl ProcessingItemRepository {
         let row = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items WHERE drive_item_id = ?
@@ -364,7 +369,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items ORDER BY id ASC
@@ -390,7 +395,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items WHERE status = ? ORDER BY id ASC
@@ -502,29 +507,42 @@ impl ProcessingItemRepository {
         &self,
         change_type: &ChangeType,
     ) -> Result<Option<ProcessingItem>> {
-        let row = sqlx::query(
+        Ok(self
+            .get_next_unprocessed_items_by_change_type(change_type, 1)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Get the next `limit` unprocessed items in processing order
+    pub async fn get_next_unprocessed_items_by_change_type(
+        &self,
+        change_type: &ChangeType,
+        limit: usize,
+    ) -> Result<Vec<ProcessingItem>> {
+        let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
             WHERE change_type = ? AND status IN ('new', 'validated', 'error', 'conflicted'  )
             AND ( parent_path  NOT LIKE '/root/.%' OR (name ='root' and parent_path is null))
             and (change_type = 'remote' or last_status_update < datetime('now', '-5 seconds') ) -- this is to avoid processing the same item multiple times
-            ORDER BY id ASC LIMIT 1
+            ORDER BY id ASC LIMIT ?
             "#,
         )
         .bind(change_type.as_str())
-        .fetch_optional(&self.pool)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let processing_item = self.row_to_processing_item(row).await?;
-            Ok(Some(processing_item))
-        } else {
-            Ok(None)
+        let mut items = Vec::new();
+        for row in rows {
+            items.push(self.row_to_processing_item(row).await?);
         }
+        Ok(items)
     }
 
     /// Get unprocessed items by change type (Remote first, then Local)
@@ -535,7 +553,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
@@ -563,7 +581,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
@@ -784,7 +802,7 @@ impl ProcessingItemRepository {
             r#"
             UPDATE processing_items SET
                 drive_item_id = ?, name = ?, etag = ?, last_modified = ?, created_date = ?, size = ?, is_folder = ?,
-                mime_type = ?, download_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?,
+                mime_type = ?, download_url = ?, web_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?,
                 status = ?, error_message = ?, last_status_update = datetime('now'), retry_count = ?, priority = ?,
                 change_type = ?, change_operation = ?, conflict_resolution = ?, validation_errors = ?, user_decision = ?,
                 updated_at = CURRENT_TIMESTAMP
@@ -800,6 +818,7 @@ impl ProcessingItemRepository {
         .bind(item.drive_item.folder.is_some())
         .bind(item.drive_item.file.as_ref().and_then(|f| f.mime_type.clone()))
         .bind(&item.drive_item.download_url)
+        .bind(&item.drive_item.web_url)
         .bind(item.drive_item.deleted.is_some())
         .bind(parent_id)
         .bind(parent_path)
@@ -833,7 +852,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items WHERE parent_id = ?
@@ -864,6 +883,7 @@ impl ProcessingItemRepository {
         let is_folder: bool = row.try_get("is_folder")?;
         let mime_type: Option<String> = row.try_get("mime_type")?;
         let download_url: Option<String> = row.try_get("download_url")?;
+        let web_url: Option<String> = row.try_get("web_url")?;
         let is_deleted: bool = row.try_get("is_deleted")?;
         let parent_id: Option<String> = row.try_get("parent_id")?;
         let parent_path: Option<String> = row.try_get("parent_path")?;
@@ -923,6 +943,7 @@ impl ProcessingItemRepository {
             download_url,
             deleted,
             parent_reference,
+            web_url,
         };
 
         let status = ProcessingStatus::from_str(&status_str)
@@ -972,7 +993,7 @@ impl ProcessingItemRepository {
         let row = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
@@ -1003,7 +1024,7 @@ impl ProcessingItemRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
@@ -1034,7 +1055,7 @@ impl ProcessingItemRepository {
         let row = sqlx::query(
             r#"
             SELECT id, drive_item_id, name, etag, last_modified, created_date, size, is_folder,
-                   mime_type, download_url, is_deleted, parent_id, parent_path,
+                   mime_type, download_url, web_url, is_deleted, parent_id, parent_path,
                    status, error_message, last_status_update, retry_count, priority,
                    change_type, change_operation, conflict_resolution, validation_errors, user_decision
             FROM processing_items 
@@ -1076,7 +1097,7 @@ impl ProcessingItemRepository {
             r#"
             UPDATE processing_items SET
                 name = ?, etag = ?, last_modified = ?, created_date = ?, size = ?, is_folder = ?,
-                mime_type = ?, download_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?,
+                mime_type = ?, download_url = ?, web_url = ?, is_deleted = ?, parent_id = ?, parent_path = ?,
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
@@ -20,6 +20,16 @@ pub struct DownloadQueueItem {
     pub virtual_path: Option<String>,
 }
 
+/// Queued download that is due to start, with the name and size of its item
+#[derive(Debug, Clone)]
+pub struct PendingDownload {
+    pub queue_id: i64,
+    pub drive_item_id: String,
+    pub local_path: PathBuf,
+    pub name: Option<String>,
+    pub size: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum FileSource {
     Remote, // From OneDrive (DriveItems)
@@ -46,6 +56,14 @@ pub struct FuseMetadata {
     pub file_source: Option<FileSource>,
     pub sync_status: Option<String>,
     pub ctag: Option<String>,
+    /// Permission bits set via chmod (None = default permissions)
+    pub mode: Option<u32>,
+    /// Owner set via chown (None = default owner)
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Access and modification times set via utimens (RFC 3339)
+    pub atime: Option<String>,
+    pub mtime: Option<String>,
 }
 
 /// Complete item with both OneDrive and Fuse data
@@ -68,6 +86,11 @@ impl DriveItemWithFuse {
                 file_source: Some(FileSource::Remote),
                 sync_status: None,
                 ctag: None,
+                mode: None,
+                uid: None,
+                gid: None,
+                atime: None,
+                mtime: None,
             },
         }
     }
@@ -0,0 +1,183 @@
+//! UploadSessionRepository: Handles upload_sessions table operations
+use crate::onedrive_service::onedrive_client::parse_range_string;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::debug;
+use sqlx::{Pool, Row, Sqlite};
+
+/// Upload session of a large file, kept so the upload can continue after a restart
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadSession {
+    pub processing_item_id: i64,
+    pub drive_item_id: String,
+    pub upload_url: String,
+    /// `expirationDateTime` of the session as sent by Graph
+    pub expiration: String,
+    /// Size and modification time (ms since the epoch) of the local file when the
+    /// session was created; a changed file has to start over
+    pub file_size: u64,
+    pub file_modified: i64,
+    /// Byte ranges OneDrive has received, inclusive
+    pub committed_ranges: Vec<(u64, u64)>,
+}
+
+impl UploadSession {
+    /// Whether the session expired at `now`; an unreadable expiration counts as expired
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        is_expired(&self.expiration, now)
+    }
+
+    /// Bytes OneDrive has received
+    pub fn committed_bytes(&self) -> u64 {
+        self.committed_ranges
+            .iter()
+            .map(|(start, end)| end - start + 1)
+            .sum()
+    }
+}
+
+/// Whether an RFC 3339 expiration is not after `now`
+fn is_expired(expiration: &str, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(expiration)
+        .map(|expiration| expiration <= now)
+        .unwrap_or(true)
+}
+
+/// Format ranges like "0-327679,655360-983039"
+fn format_ranges(ranges: &[(u64, u64)]) -> String {
+    ranges
+        .iter()
+        .map(|(start, end)| format!("{}-{}", start, end))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Database operations for upload sessions of large files, keyed by processing item
+#[derive(Clone)]
+pub struct UploadSessionRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl UploadSessionRepository {
+    /// Create a new upload session repository
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Store the session of a processing item, replacing an older one
+    pub async fn store_session(&self, session: &UploadSession) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO upload_sessions
+                (processing_item_id, drive_item_id, upload_url, expiration, file_size, file_modified,
+                 committed_ranges, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(session.processing_item_id)
+        .bind(&session.drive_item_id)
+        .bind(&session.upload_url)
+        .bind(&session.expiration)
+        .bind(session.file_size as i64)
+        .bind(session.file_modified)
+        .bind(format_ranges(&session.committed_ranges))
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "📤 Stored upload session of processing item {} ({})",
+            session.processing_item_id, session.drive_item_id
+        );
+        Ok(())
+    }
+
+    /// Get the session of a processing item
+    pub async fn get_session(&self, processing_item_id: i64) -> Result<Option<UploadSession>> {
+        let row = sqlx::query(
+            r#"
+            SELECT processing_item_id, drive_item_id, upload_url, expiration, file_size, file_modified,
+                   committed_ranges
+            FROM upload_sessions WHERE processing_item_id = ?
+            "#,
+        )
+        .bind(processing_item_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let file_size = row.try_get::<i64, _>("file_size")?.max(0) as u64;
+        let committed_ranges: String = row.try_get("committed_ranges")?;
+        Ok(Some(UploadSession {
+            processing_item_id: row.try_get("processing_item_id")?,
+            drive_item_id: row.try_get("drive_item_id")?,
+            upload_url: row.try_get("upload_url")?,
+            expiration: row.try_get("expiration")?,
+            file_size,
+            file_modified: row.try_get("file_modified")?,
+            committed_ranges: committed_ranges
+                .split(',')
+                .filter_map(|range| parse_range_string(range, file_size))
+                .collect(),
+        }))
+    }
+
+    /// Record the byte ranges OneDrive has received
+    pub async fn set_committed_ranges(&self, processing_item_id: i64, ranges: &[(u64, u64)]) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE upload_sessions SET committed_ranges = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE processing_item_id = ?
+            "#,
+        )
+        .bind(format_ranges(ranges))
+        .bind(processing_item_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove the session of a processing item
+    pub async fn remove_session(&self, processing_item_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM upload_sessions WHERE processing_item_id = ?")
+            .bind(processing_item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove expired sessions and those of processing items that are gone.
+    /// Returns the number of removed sessions.
+    pub async fn remove_stale_sessions(&self, now: DateTime<Utc>) -> Result<u64> {
+        let orphaned = sqlx::query(
+            r#"
+            DELETE FROM upload_sessions
+            WHERE processing_item_id NOT IN (SELECT id FROM processing_items)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let rows = sqlx::query("SELECT processing_item_id, expiration FROM upload_sessions")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut expired = 0;
+        for row in rows {
+            let expiration: String = row.try_get("expiration")?;
+            if is_expired(&expiration, now) {
+                self.remove_session(row.try_get("processing_item_id")?).await?;
+                expired += 1;
+            }
+        }
+
+        if orphaned + expired > 0 {
+            debug!(
+                "📤 Removed {} expired and {} orphaned upload sessions",
+                expired, orphaned
+            );
+        }
+        Ok(orphaned + expired)
+    }
+}
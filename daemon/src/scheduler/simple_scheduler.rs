@@ -4,15 +4,60 @@
 //! that avoids memory leaks by not capturing heavy objects in closures.
 
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tokio::time::{interval, Duration};
 use tokio::task::JoinHandle;
-use log::{info, warn, error};
+use log::{debug, info, warn, error};
 use anyhow::Result;
 
 use crate::app_state::AppState;
+use crate::tasks::change_notifications::ChangeNotificationTransport;
 use crate::tasks::delta_update::SyncCycle;
+use crate::tasks::transfers::{TransferScheduler, DOWNLOAD_POLL_INTERVAL};
 
+/// How often NetworkManager and UPower are asked for the metered and battery state
+const NETWORK_CONDITIONS_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+
+/// Shortest accepted `sync_interval_seconds`
+const MIN_SYNC_INTERVAL_SECS: u64 = 5;
+
+/// Polling slows down to this multiple of the sync interval while nothing changes
+const MAX_IDLE_BACKOFF_FACTOR: u32 = 8;
+
+/// Longest time between polls while change notifications arrive
+const PUSH_FALLBACK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Delays between attempts to reconnect for change notifications
+const PUSH_RECONNECT_MIN_DELAY: Duration = Duration::from_secs(5);
+const PUSH_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// Polling interval that grows while the drive is idle
+#[derive(Debug, Default)]
+pub struct SyncBackoff {
+    current: Duration,
+}
+
+impl SyncBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time until the next poll after a cycle: back to `base` after changes, otherwise
+    /// doubled up to a limit that is higher while change notifications are connected
+    pub fn next(&mut self, base: Duration, had_changes: bool, push_connected: bool) -> Duration {
+        let max = if push_connected {
+            PUSH_FALLBACK_INTERVAL.max(base)
+        } else {
+            base * MAX_IDLE_BACKOFF_FACTOR
+        };
+        self.current = if had_changes {
+            base
+        } else {
+            (self.current.max(base) * 2).min(max)
+        };
+        self.current
+    }
+}
 
 /// Simple task manager that avoids memory leaks
 pub struct SimpleTaskManager {
@@ -26,57 +71,180 @@ impl SimpleTaskManager {
         }
     }
 
-    /// Start the sync task with overlap protection
+    /// Start the sync task with overlap protection. It polls every `sync_interval_seconds`,
+    /// slowing down while nothing changes, and runs right away when the sync trigger fires.
     pub async fn start_sync_task(&mut self, app_state: Arc<AppState>) -> Result<()> {
-        let sync_running = Arc::new(Mutex::new(false));
         let app_state_weak = Arc::downgrade(&app_state);
+        let sync_trigger = app_state.sync_trigger.clone();
 
         let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30));
-            
+            let mut backoff = SyncBackoff::new();
+            // The first cycle runs right away
+            let mut wait = Duration::ZERO;
+
             loop {
-                interval.tick().await;
-                
-                // Try to acquire lock - if already running, skip this cycle
-                if let Ok(_guard) = sync_running.try_lock() {
-                    // Get fresh app_state reference - if app is shutting down, break
-                    let Some(app_state) = app_state_weak.upgrade() else {
-                        info!("🛑 App state dropped, stopping sync task");
-                        break;
+                let triggered = sync_trigger.wait(wait).await;
+                if triggered {
+                    debug!("🔔 Sync triggered before the next poll");
+                }
+
+                // Get fresh app_state reference - if app is shutting down, break
+                let Some(app_state) = app_state_weak.upgrade() else {
+                    info!("🛑 App state dropped, stopping sync task");
+                    break;
+                };
+                let base = Duration::from_secs(
+                    app_state
+                        .config()
+                        .settings
+                        .read()
+                        .await
+                        .sync_config
+                        .sync_interval_seconds
+                        .max(MIN_SYNC_INTERVAL_SECS),
+                );
+
+                // A triggered cycle runs after one that is already running (e.g. started over D-Bus),
+                // a poll is skipped instead
+                let _guard = if triggered {
+                    app_state.sync_running.lock().await
+                } else {
+                    let Ok(guard) = app_state.sync_running.try_lock() else {
+                        warn!("⏭️ Sync task still running, skipping this cycle");
+                        wait = base;
+                        continue;
                     };
+                    guard
+                };
+
+                info!("🔄 Starting sync cycle");
+
+                // Create everything fresh - no permanent captures
+                let had_changes = match SyncCycle::new(app_state.clone()).run().await {
+                    Ok(had_changes) => {
+                        info!("✅ Sync cycle completed successfully");
+                        had_changes
+                    }
+                    Err(e) => {
+                        error!("❌ Sync cycle failed: {}", e);
+                        false
+                    }
+                };
+
+                wait = backoff.next(base, had_changes, app_state.sync_trigger().is_push_connected());
+                debug!("⏱️ Next sync poll in {}s", wait.as_secs());
+            }
+        });
+
+        self.shutdown_handles.push(handle);
+        info!("✅ Sync task started");
+        Ok(())
+    }
+
+    /// Start the change notification task: every change reported by the transport
+    /// triggers a sync cycle. Reconnects with a growing delay when the connection fails.
+    pub async fn start_change_notification_task(
+        &mut self,
+        app_state: Arc<AppState>,
+        transport: Arc<dyn ChangeNotificationTransport>,
+    ) -> Result<()> {
+        let app_state_weak = Arc::downgrade(&app_state);
+        let sync_trigger = app_state.sync_trigger.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut reconnect_delay = PUSH_RECONNECT_MIN_DELAY;
+
+            loop {
+                if app_state_weak.strong_count() == 0 {
+                    info!("🛑 App state dropped, stopping change notification task");
+                    break;
+                }
+
+                match transport.connect().await {
+                    Ok(mut stream) => {
+                        info!("🔔 Listening for change notifications");
+                        sync_trigger.set_push_connected(true);
+                        reconnect_delay = PUSH_RECONNECT_MIN_DELAY;
+                        // Catch up on changes made while not connected
+                        sync_trigger.trigger();
 
-                    let sync_running = sync_running.clone();
-                    
-                    // Spawn fresh task - no captured closures, clean scope
-                    tokio::spawn(async move {
-                        let _guard = sync_running.lock().await;
-                        
-                        info!("🔄 Starting sync cycle");
-                        
-                        // Create everything fresh - no permanent captures
-                        let sync_cycle = SyncCycle::new(app_state);
-                        let result = sync_cycle.run().await;
-                        
-                        match result {
-                            Ok(_) => info!("✅ Sync cycle completed successfully"),
-                            Err(e) => error!("❌ Sync cycle failed: {}", e),
+                        loop {
+                            match stream.next_change().await {
+                                Ok(()) => sync_trigger.trigger(),
+                                Err(e) => {
+                                    warn!("⚠️ Change notifications stopped: {}", e);
+                                    break;
+                                }
+                            }
                         }
-                        
-                        // Everything drops naturally here - sync_cycle, app_state clone, etc.
-                        // No permanent references held
-                    });
-                } else {
-                    warn!("⏭️ Sync task still running, skipping this cycle");
+                        sync_trigger.set_push_connected(false);
+                    }
+                    Err(e) => warn!("⚠️ Failed to connect for change notifications: {}", e),
                 }
+
+                // Polling covers the time until the connection is back
+                tokio::time::sleep(reconnect_delay).await;
+                reconnect_delay = (reconnect_delay * 2).min(PUSH_RECONNECT_MAX_DELAY);
             }
         });
 
         self.shutdown_handles.push(handle);
-        info!("✅ Sync task started");
+        info!("✅ Change notification task started");
+        Ok(())
+    }
+
+    /// Start the transfer task: downloads run in parallel, independent of the sync tick
+    pub async fn start_transfer_task(&mut self, app_state: Arc<AppState>) -> Result<()> {
+        let app_state_weak = Arc::downgrade(&app_state);
+        let transfers = app_state.transfers().clone();
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let Some(app_state) = app_state_weak.upgrade() else {
+                    info!("🛑 App state dropped, stopping transfer task");
+                    break;
+                };
+
+                if let Err(e) = TransferScheduler::new(app_state).start_downloads().await {
+                    error!("❌ Failed to start downloads: {}", e);
+                }
+
+                // Woken up when a transfer finishes or a sync cycle queued new downloads
+                transfers.wait_for_wakeup(DOWNLOAD_POLL_INTERVAL).await;
+            }
+        });
+
+        self.shutdown_handles.push(handle);
+        info!("✅ Transfer task started");
         Ok(())
     }
 
+    /// Start the task following the metered and battery state, which pauses or throttles transfers
+    pub async fn start_network_conditions_task(&mut self, app_state: Arc<AppState>) -> Result<()> {
+        let app_state_weak = Arc::downgrade(&app_state);
+
+        let handle = tokio::spawn(async move {
+            let mut interval = interval(NETWORK_CONDITIONS_POLL_INTERVAL);
+
+            loop {
+                interval.tick().await;
 
+                let Some(app_state) = app_state_weak.upgrade() else {
+                    info!("🛑 App state dropped, stopping network conditions task");
+                    break;
+                };
+
+                // Queued downloads may start right away once a pause ends
+                if app_state.connectivity().refresh_network_conditions().await {
+                    app_state.transfers().wake();
+                }
+            }
+        });
+
+        self.shutdown_handles.push(handle);
+        info!("✅ Network conditions task started");
+        Ok(())
+    }
 
     /// Gracefully shutdown all tasks
     pub async fn shutdown(self) {
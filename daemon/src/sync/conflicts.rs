@@ -1,5 +1,6 @@
 //! Defines the conflict types that can occur during synchronization.
 
+use crate::persistency::processing_item_repository::ChangeOperation;
 use thiserror::Error;
 
 /// Represents a conflict detected when a change from the remote server
@@ -60,4 +61,19 @@ pub enum LocalConflict {
 
     #[error("Local item was renamed or moved, but the original source item has been deleted from the server")]
     RenameOrMoveOfDeleted,
+
+    #[error("Local item was renamed or moved, but the remote item has been modified")]
+    RenameOrMoveOfModified,
+}
+
+impl LocalConflict {
+    /// The conflict of a local change OneDrive refused with 412: the remote item no
+    /// longer has the eTag the change was based on
+    pub fn on_precondition_failed(operation: &ChangeOperation) -> Self {
+        match operation {
+            ChangeOperation::Delete => Self::DeleteOnModified,
+            ChangeOperation::Move | ChangeOperation::Rename => Self::RenameOrMoveOfModified,
+            _ => Self::ModifyOnModified,
+        }
+    }
 }
@@ -1,7 +1,10 @@
 
+pub mod conflict_resolution;
 pub mod conflicts;
+pub mod selective_sync;
 pub mod sync_processor;
 pub mod sync_strategy;
+pub mod text_merge;
 
 pub use conflicts::*;
 pub use sync_processor::*;
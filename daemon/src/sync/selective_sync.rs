@@ -0,0 +1,209 @@
+//! Selective sync: which files are downloaded automatically
+//!
+//! `download_folders` selects whole folders, `selective_sync` refines that with
+//! gitignore-style include and exclude patterns on paths relative to the drive
+//! root. Excludes win over includes, pinned files are downloaded regardless.
+//! With `hide_excluded` excluded items are also left out of the mount.
+//!
+//! `local_ignore` uses the same patterns for the other direction: files created
+//! through the mount that match are never uploaded.
+
+use crate::onedrive_service::onedrive_models::DriveItem;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use log::warn;
+use onedrive_sync_lib::config::{LocalIgnore, SelectiveSync, Settings};
+use std::sync::{Arc, Mutex};
+
+/// Selective sync rules compiled for matching
+pub struct SyncRuleSet {
+    download_folders: Vec<String>,
+    include: GlobSet,
+    exclude: GlobSet,
+    hide_excluded: bool,
+    local_ignore: GlobSet,
+}
+
+impl SyncRuleSet {
+    /// Compile the rules, failing on the first invalid pattern
+    pub fn new(download_folders: &[String], selective_sync: &SelectiveSync) -> Result<Self> {
+        Ok(Self {
+            download_folders: download_folders
+                .iter()
+                .map(|folder| folder.trim_matches('/').to_string())
+                .collect(),
+            include: build_set(&selective_sync.include)?,
+            exclude: build_set(&selective_sync.exclude)?,
+            hide_excluded: selective_sync.hide_excluded,
+            local_ignore: GlobSet::empty(),
+        })
+    }
+
+    /// Add the local ignore patterns, failing on the first invalid one
+    pub fn with_local_ignore(mut self, local_ignore: &LocalIgnore) -> Result<Self> {
+        self.local_ignore = build_set(&local_ignore.effective_patterns())?;
+        Ok(self)
+    }
+
+    /// Compile the rules of the settings; invalid patterns are skipped
+    pub fn from_settings(settings: &Settings) -> Self {
+        let valid = |patterns: &[String]| -> Vec<String> {
+            patterns
+                .iter()
+                .filter(|pattern| match pattern_globs(pattern) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        warn!("⚠️ Ignoring selective sync pattern: {:#}", e);
+                        false
+                    }
+                })
+                .cloned()
+                .collect()
+        };
+        let selective_sync = SelectiveSync {
+            include: valid(&settings.selective_sync.include),
+            exclude: valid(&settings.selective_sync.exclude),
+            hide_excluded: settings.selective_sync.hide_excluded,
+        };
+        let local_ignore = LocalIgnore {
+            use_defaults: settings.local_ignore.use_defaults,
+            patterns: valid(&settings.local_ignore.patterns),
+        };
+        Self::new(&settings.download_folders, &selective_sync)
+            .and_then(|rules| rules.with_local_ignore(&local_ignore))
+            .expect("invalid patterns were filtered out")
+    }
+
+    /// Check if a file is downloaded automatically (path relative to the drive root)
+    pub fn is_included(&self, path: &str) -> bool {
+        let path = path.trim_matches('/');
+        if self.exclude.is_match(path) {
+            return false;
+        }
+        self.include.is_match(path)
+            || self
+                .download_folders
+                .iter()
+                .any(|folder| is_in_folder(path, folder))
+    }
+
+    /// Check if an item matches an exclude pattern
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.is_match(path.trim_matches('/'))
+    }
+
+    /// Check if an item is left out of the mount
+    pub fn is_hidden(&self, path: &str) -> bool {
+        self.hide_excluded && self.is_excluded(path)
+    }
+
+    /// Check if a path matches a local ignore pattern
+    pub fn is_locally_ignored(&self, path: &str) -> bool {
+        self.local_ignore.is_match(path.trim_matches('/'))
+    }
+}
+
+/// Rules compiled from the current settings, recompiled when the settings change
+#[derive(Default)]
+pub struct SyncRulesCache {
+    cached: Mutex<Option<CachedRules>>,
+}
+
+struct CachedRules {
+    download_folders: Vec<String>,
+    selective_sync: SelectiveSync,
+    local_ignore: LocalIgnore,
+    rules: Arc<SyncRuleSet>,
+}
+
+impl SyncRulesCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules for the given settings
+    pub fn get(&self, settings: &Settings) -> Arc<SyncRuleSet> {
+        let mut cached = self.cached.lock().unwrap();
+        if let Some(cached) = cached.as_ref() {
+            if cached.download_folders == settings.download_folders
+                && cached.selective_sync == settings.selective_sync
+                && cached.local_ignore == settings.local_ignore
+            {
+                return cached.rules.clone();
+            }
+        }
+        let rules = Arc::new(SyncRuleSet::from_settings(settings));
+        *cached = Some(CachedRules {
+            download_folders: settings.download_folders.clone(),
+            selective_sync: settings.selective_sync.clone(),
+            local_ignore: settings.local_ignore.clone(),
+            rules: rules.clone(),
+        });
+        rules
+    }
+}
+
+/// Path of an item relative to the drive root, e.g. `Documents/Work/report.pdf`
+pub fn item_path(item: &DriveItem) -> String {
+    let parent = item
+        .parent_reference
+        .as_ref()
+        .and_then(|parent| parent.path.as_deref())
+        .map(|path| path.strip_prefix("/drive/root:").unwrap_or(path))
+        .unwrap_or("")
+        .trim_matches('/');
+    let name = item.name.as_deref().unwrap_or("");
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+/// Check if a path lies in a folder (whole folder names only, `Photos` does not match `Photos2`)
+fn is_in_folder(path: &str, folder: &str) -> bool {
+    folder.is_empty()
+        || path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn build_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        for glob in pattern_globs(pattern)? {
+            builder.add(glob);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Globs for a gitignore-style pattern: without a `/` it matches a name at any depth,
+/// and a matching folder matches everything below it
+fn pattern_globs(pattern: &str) -> Result<Vec<Glob>> {
+    let trimmed = pattern.trim();
+    let anchored = trimmed.starts_with('/');
+    let trimmed = trimmed.trim_matches('/');
+    if trimmed.is_empty() {
+        anyhow::bail!("Empty pattern");
+    }
+    let base = if anchored || trimmed.contains('/') {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+    let mut globs = vec![base.clone()];
+    if !base.ends_with("/**") {
+        globs.push(format!("{}/**", base));
+    }
+    globs
+        .iter()
+        .map(|glob| {
+            GlobBuilder::new(glob)
+                .literal_separator(true)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("Invalid pattern {}", pattern))
+        })
+        .collect()
+}
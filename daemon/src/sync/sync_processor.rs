@@ -1,15 +1,33 @@
 use crate::app_state::AppState;
 use crate::file_manager::FileManager;
+use crate::fuse::attributes::AttributeManager;
+use crate::onedrive_service::http_client::{graph_error, GraphError};
+use crate::onedrive_service::onedrive_client::LARGE_FILE_THRESHOLD;
+use crate::onedrive_service::onedrive_models::{UploadProgress, UploadProgressFn, UploadResult};
 use crate::persistency::drive_item_with_fuse_repository::DriveItemWithFuseRepository;
 use crate::persistency::processing_item_repository::{
     ChangeOperation, ChangeType, ProcessingItem, ProcessingItemRepository, ProcessingStatus,
 };
+use crate::persistency::upload_session_repository::UploadSession;
+use crate::sync::conflicts::LocalConflict;
+use crate::sync::selective_sync;
 use crate::sync::sync_strategy::SyncStrategy;
+use crate::sync::text_merge;
+use crate::tasks::transfers::{self, BulkTransferMode, TransferDirection};
 use anyhow::{Context, Result};
+use futures_util::future::{self, join_all, FutureExt};
 use log::{debug, error, info, warn};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Where a file upload goes: a new file in a folder, or new content of an existing file
+/// that must still have the eTag `if_match`
+enum UploadTarget<'a> {
+    New { parent_id: &'a str, file_name: &'a str },
+    Existing { item_id: &'a str, if_match: Option<&'a str> },
+}
+
 pub struct SyncProcessor {
     strategy: SyncStrategy,
     app_state: Arc<AppState>,
@@ -29,10 +47,16 @@ impl SyncProcessor {
         }
     }
 
-    /// Process all items with priority: Remote first, then Local
-    pub async fn process_all_items(&self) -> Result<()> {
+    /// Process all items with priority: Remote first, then Local. Returns the number of
+    /// new local changes taken on; retried and held-back items are not counted.
+    pub async fn process_all_items(&self) -> Result<usize> {
         debug!("🏠️ Clean up processing items...");
         self.processing_repo.hause_keeping().await?;
+        self.app_state
+            .persistency()
+            .upload_session_repository()
+            .remove_stale_sessions(chrono::Utc::now())
+            .await?;
 
         // NEW: Squash local changes before processing
         debug!("🔀 Squashing local changes...");
@@ -46,35 +70,321 @@ impl SyncProcessor {
             .await?;
         for item in remote_items {
             if let Err(e) = self.process_single_item(&item).await {
-                error!("❌ Failed to process remote item: {}", e);
-                self.processing_repo
-                    .update_status_by_id(item.id.unwrap(), &ProcessingStatus::Error)
-                    .await?;
+                if !self.handle_item_error(&item, &e).await? {
+                    return Ok(0);
+                }
             }
         }
 
         // 2. Process Local changes after remote changes are handled
         debug!("🔄 Processing local changes...");
+        // Items whose upload is held back; their later moves, renames and deletes wait as well
+        let mut held = HashSet::new();
+        let mut new_local_changes = 0;
         loop {
-            // Always fetch the next unprocessed local item
-            if let Some(item) = self
+            // Always fetch the next unprocessed local items
+            let max_uploads = self
+                .app_state
+                .config()
+                .settings
+                .read()
+                .await
+                .transfer_config
+                .max_parallel_uploads;
+            let max_uploads = BulkTransferMode::current(&self.app_state)
+                .await
+                .max_parallel(max_uploads);
+            let items = self
                 .processing_repo
-                .get_next_unprocessed_item_by_change_type(&ChangeType::Local)
-                .await?
-            {
-                if let Err(e) = self.process_single_item(&item).await {
-                    error!("❌ Failed to process local item: {}", e);
-                    self.processing_repo
-                        .update_status_by_id(item.id.unwrap(), &ProcessingStatus::Error)
-                        .await?;
-                }
-            } else {
+                .get_next_unprocessed_items_by_change_type(&ChangeType::Local, max_uploads)
+                .await?;
+            if items.is_empty() {
                 // No more unprocessed items
                 break;
             }
+
+            let batch = Self::next_local_batch(&items);
+            let results = join_all(batch.iter().map(|item| self.process_local_transfer(item, &held))).await;
+            let mut keep_going = true;
+            for (item, result) in batch.iter().zip(results) {
+                let held_back = match result {
+                    Ok(held_back) => held_back,
+                    Err(e) => {
+                        keep_going &= self.handle_item_error(item, &e).await?;
+                        false
+                    }
+                };
+                if held_back {
+                    held.insert(item.drive_item.id.clone());
+                } else if item.status == ProcessingStatus::New {
+                    new_local_changes += 1;
+                }
+            }
+            if !keep_going {
+                break;
+            }
         }
 
-        Ok(())
+        Ok(new_local_changes)
+    }
+
+    /// Record why an item failed. Returns false if the rest of the cycle should wait:
+    /// OneDrive throttles us or the token was rejected.
+    async fn handle_item_error(&self, item: &ProcessingItem, error: &anyhow::Error) -> Result<bool> {
+        let db_id = item
+            .id
+            .ok_or_else(|| anyhow::anyhow!("ProcessingItem has no database ID"))?;
+        let name = item.drive_item.name.as_deref().unwrap_or("unnamed");
+        match graph_error(error) {
+            Some(GraphError::Throttled { retry_after, .. }) => {
+                warn!(
+                    "⏳ OneDrive is throttling requests, postponing {} (retry after {:?})",
+                    name, retry_after
+                );
+                // Touching the status delays the next attempt without counting as an error
+                self.processing_repo.update_status_by_id(db_id, &item.status).await?;
+                Ok(false)
+            }
+            Some(GraphError::Unauthorized(_)) => {
+                error!("🔒 OneDrive rejected the access token, postponing {}", name);
+                self.processing_repo.update_status_by_id(db_id, &item.status).await?;
+                Ok(false)
+            }
+            Some(GraphError::PreconditionFailed(_)) => {
+                // The remote change arrives with the next delta and pairs up with this item
+                let conflict = LocalConflict::on_precondition_failed(&item.change_operation);
+                warn!("⚠️ {} changed on OneDrive in the meantime: {}", name, conflict);
+                self.processing_repo
+                    .update_status_by_id(db_id, &ProcessingStatus::Conflicted)
+                    .await?;
+                self.processing_repo
+                    .update_validation_errors_by_id(db_id, &[conflict.to_string()])
+                    .await?;
+                Ok(true)
+            }
+            _ => {
+                error!("❌ Failed to process item {}: {:#}", name, error);
+                self.processing_repo
+                    .update_status_by_id(db_id, &ProcessingStatus::Error)
+                    .await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Local items to process together: the leading file uploads of different items run
+    /// side by side, anything else (folders, moves, deletes) runs on its own and in order
+    fn next_local_batch(items: &[ProcessingItem]) -> Vec<&ProcessingItem> {
+        let mut batch: Vec<&ProcessingItem> = Vec::new();
+        for item in items {
+            if !Self::is_file_upload(item) {
+                if batch.is_empty() {
+                    batch.push(item);
+                }
+                break;
+            }
+            if batch.iter().any(|b| b.drive_item.id == item.drive_item.id) {
+                break;
+            }
+            batch.push(item);
+        }
+        batch
+    }
+
+    /// Check if a local item uploads file content
+    fn is_file_upload(item: &ProcessingItem) -> bool {
+        item.drive_item.folder.is_none()
+            && matches!(item.change_operation, ChangeOperation::Create | ChangeOperation::Update)
+    }
+
+    /// Process a local item, listing file uploads as running transfers. Returns true if
+    /// the item was held back, also when an earlier upload of the same item in `held` was.
+    async fn process_local_transfer(&self, item: &ProcessingItem, held: &HashSet<String>) -> Result<bool> {
+        let reason = if held.contains(&item.drive_item.id) {
+            Some("an earlier upload of it is held back".to_string())
+        } else {
+            self.upload_hold_reason(item).await
+        };
+        if let Some(reason) = reason {
+            info!(
+                "⏸️ Holding back {} of {}: {}",
+                item.change_operation.as_str(),
+                item.drive_item.name.as_deref().unwrap_or("unnamed"),
+                reason
+            );
+            // Touching the status delays the next attempt, like a write-locked file
+            if let Some(db_id) = item.id {
+                self.processing_repo
+                    .update_status_by_id(db_id, &item.status)
+                    .await?;
+            }
+            return Ok(true);
+        }
+
+        let _guard = Self::is_file_upload(item).then(|| {
+            self.app_state.transfers().start(
+                TransferDirection::Upload,
+                &item.drive_item.id,
+                item.drive_item.name.as_deref().unwrap_or("unnamed"),
+                item.drive_item.size.unwrap_or(0),
+            )
+        });
+        self.process_single_item(item).await?;
+        Ok(false)
+    }
+
+    /// Report the upload progress of an item to its entry in the `TransferTracker`
+    fn upload_progress(&self, drive_item_id: &str) -> UploadProgressFn {
+        let tracker = self.app_state.transfers().clone();
+        let drive_item_id = drive_item_id.to_string();
+        Arc::new(move |progress: &UploadProgress| {
+            tracker.update_progress(
+                TransferDirection::Upload,
+                &drive_item_id,
+                progress.bytes_uploaded,
+                progress.total_bytes,
+            );
+            future::ready(()).boxed()
+        })
+    }
+
+    /// Report upload progress and record it in the stored upload session before the next
+    /// chunk is sent. A session receives its bytes in order, so OneDrive has one range from
+    /// the start of the file.
+    fn session_progress(&self, processing_item_id: i64, progress: UploadProgressFn) -> UploadProgressFn {
+        let sessions = self.app_state.persistency().upload_session_repository();
+        Arc::new(move |update: &UploadProgress| {
+            let reported = progress(update);
+            let sessions = sessions.clone();
+            let committed: Vec<(u64, u64)> = update
+                .bytes_uploaded
+                .checked_sub(1)
+                .map(|last| (0, last))
+                .into_iter()
+                .collect();
+            async move {
+                reported.await;
+                if let Err(e) = sessions.set_committed_ranges(processing_item_id, &committed).await {
+                    warn!("⚠️ Failed to record upload progress: {}", e);
+                }
+            }
+            .boxed()
+        })
+    }
+
+    /// Upload a file. Large files go through an upload session stored for the processing
+    /// item, so an upload interrupted by a restart continues where it stopped.
+    async fn upload_file(
+        &self,
+        item: &ProcessingItem,
+        local_path: &Path,
+        target: UploadTarget<'_>,
+    ) -> Result<UploadResult> {
+        let client = &self.app_state.onedrive_client;
+        let progress = self.upload_progress(&item.drive_item.id);
+        let metadata = tokio::fs::metadata(local_path)
+            .await
+            .with_context(|| format!("Failed to read metadata of {}", local_path.display()))?;
+        let processing_item_id = match item.id {
+            Some(id) if metadata.len() > LARGE_FILE_THRESHOLD => id,
+            _ => {
+                return match target {
+                    UploadTarget::New { parent_id, file_name } => {
+                        client.upload_file_smart(local_path, file_name, parent_id, Some(progress)).await
+                    }
+                    UploadTarget::Existing { item_id, if_match } => {
+                        client.update_file_smart(local_path, item_id, if_match, Some(progress)).await
+                    }
+                }
+            }
+        };
+
+        let name = item.drive_item.name.as_deref().unwrap_or("unnamed");
+        let file_modified = metadata
+            .modified()
+            .map(|time| chrono::DateTime::<chrono::Utc>::from(time).timestamp_millis())
+            .unwrap_or(0);
+        let sessions = self.app_state.persistency().upload_session_repository();
+        if let Some(session) = sessions.get_session(processing_item_id).await? {
+            if session.is_expired(chrono::Utc::now())
+                || session.file_size != metadata.len()
+                || session.file_modified != file_modified
+            {
+                info!("📤 Upload session of {} expired or the file changed, starting over", name);
+            } else {
+                info!(
+                    "⏯️ Resuming upload of {} after {} bytes",
+                    name,
+                    session.committed_bytes()
+                );
+                match client
+                    .resume_large_file_upload(
+                        &session.upload_url,
+                        local_path,
+                        None,
+                        Some(self.session_progress(processing_item_id, progress.clone())),
+                    )
+                    .await
+                {
+                    Ok(drive_item) if !drive_item.id.is_empty() => {
+                        sessions.remove_session(processing_item_id).await?;
+                        return Ok(drive_item.into());
+                    }
+                    Ok(_) => warn!("⚠️ Resumed upload of {} returned no item, starting over", name),
+                    Err(e) => warn!("⚠️ Failed to resume upload of {}, starting over: {}", name, e),
+                }
+            }
+            sessions.remove_session(processing_item_id).await?;
+        }
+
+        let session = match target {
+            UploadTarget::New { parent_id, file_name } => {
+                client.create_upload_session(parent_id, file_name).await?
+            }
+            UploadTarget::Existing { item_id, if_match } => {
+                client.create_update_upload_session(item_id, if_match).await?
+            }
+        };
+        sessions
+            .store_session(&UploadSession {
+                processing_item_id,
+                drive_item_id: item.drive_item.id.clone(),
+                upload_url: session.upload_url.clone(),
+                expiration: session.expiration_date_time,
+                file_size: metadata.len(),
+                file_modified,
+                committed_ranges: vec![],
+            })
+            .await?;
+        let drive_item = client
+            .upload_to_session(
+                &session.upload_url,
+                local_path,
+                None,
+                Some(self.session_progress(processing_item_id, progress)),
+            )
+            .await?;
+        sessions.remove_session(processing_item_id).await?;
+        Ok(drive_item.into())
+    }
+
+    /// Why a file upload can't run now: a closed transfer window, or a metered connection
+    /// or battery pausing bulk transfers. Folders, moves and deletes are never held back.
+    async fn upload_hold_reason(&self, item: &ProcessingItem) -> Option<String> {
+        if !Self::is_file_upload(item) {
+            return None;
+        }
+        let mode = BulkTransferMode::current(&self.app_state).await;
+        if mode.is_paused() {
+            return Some(mode.reason());
+        }
+        let settings = self.app_state.config().settings.read().await;
+        let in_window = transfers::transfer_window_open(
+            &settings.sync_config.transfer_windows,
+            TransferDirection::Upload,
+            item.drive_item.size.unwrap_or(0),
+        );
+        (!in_window).then(|| "outside its transfer window".to_string())
     }
 
     /// Squash local changes before processing to consolidate multiple changes into final state
@@ -259,6 +569,7 @@ impl SyncProcessor {
         let has_modifications = items.iter().any(|item| 
             matches!(item.change_operation, 
                 ChangeOperation::Update | 
+                ChangeOperation::Touch |
                 ChangeOperation::Rename { .. } | 
                 ChangeOperation::Move { .. }
             )
@@ -385,6 +696,19 @@ impl SyncProcessor {
             .id
             .ok_or_else(|| anyhow::anyhow!("ProcessingItem has no database ID"))?;
 
+        if self.is_write_locked(item).await? {
+            info!(
+                "🔒 Holding back {} change for {}: file is write-locked",
+                item.change_type.as_str(),
+                item.drive_item.name.as_deref().unwrap_or("unnamed")
+            );
+            // Touching the status delays the next attempt; the item stays pending until the lock is released
+            self.processing_repo
+                .update_status_by_id(db_id, &item.status)
+                .await?;
+            return Ok(());
+        }
+
         match item.change_type {
             ChangeType::Remote => {
                 let mut conflicts = self.strategy.detect_remote_conflicts(item).await?;
@@ -399,6 +723,8 @@ impl SyncProcessor {
                         .update_status_by_id(db_id, &ProcessingStatus::Validated)
                         .await?;
                     self.process_remote_item(item).await?;
+                } else if self.strategy.merge_content_conflict(item, &conflicts).await {
+                    info!("🧬 Content conflict of item {} resolved by merging", item.drive_item.id);
                 } else {
                     let error_strings: Vec<String> = conflicts.iter().map(|e| e.to_string()).collect();
                     warn!(
@@ -411,6 +737,9 @@ impl SyncProcessor {
                     self.processing_repo
                         .update_validation_errors_by_id(db_id, &error_strings)
                         .await?;
+                    if self.strategy.apply_conflict_strategy(item).await {
+                        info!("✅ Conflict of item {} resolved by strategy", item.drive_item.id);
+                    }
                 }
             }
             ChangeType::Local => {
@@ -446,6 +775,9 @@ impl SyncProcessor {
                     self.processing_repo
                         .update_validation_errors_by_id(db_id, &error_strings)
                         .await?;
+                    if self.strategy.apply_conflict_strategy(item).await {
+                        info!("✅ Conflict of item {} resolved by strategy", item.drive_item.id);
+                    }
                 }
             }
         }
@@ -453,6 +785,16 @@ impl SyncProcessor {
         Ok(())
     }
 
+    /// Check if the file of a processing item is write-locked on the mount
+    async fn is_write_locked(&self, item: &ProcessingItem) -> Result<bool> {
+        let ino = self
+            .drive_item_with_fuse_repo
+            .get_drive_item_with_fuse(&item.drive_item.id)
+            .await?
+            .and_then(|existing| existing.virtual_ino());
+        Ok(ino.is_some_and(|ino| self.app_state.file_locks().has_write_lock(ino)))
+    }
+
     /// Process a remote item (download, update database, etc.)
     async fn process_remote_item(&self, item: &ProcessingItem) -> Result<()> {
         let processing_repo = self.app_state.persistency().processing_item_repository();
@@ -462,6 +804,11 @@ impl SyncProcessor {
         processing_repo
             .update_status_by_id(db_id, &ProcessingStatus::Processing)
             .await?;
+        // Remember where the item was listed before, to invalidate the kernel cache afterwards
+        let before = self
+            .drive_item_with_fuse_repo
+            .get_drive_item_with_fuse(&item.drive_item.id)
+            .await?;
         match item.change_operation {
             ChangeOperation::Create => {
                 self.handle_remote_create(item).await?;
@@ -478,6 +825,12 @@ impl SyncProcessor {
             ChangeOperation::Rename { .. } => {
                 self.handle_remote_rename(item).await?;
             }
+            ChangeOperation::Touch => {
+                warn!(
+                    "⏭️ Ignoring remote touch of {}, OneDrive reports it as an update",
+                    item.drive_item.name.as_deref().unwrap_or("unnamed")
+                );
+            }
             ChangeOperation::NoChange => {
                 error!(
                     "⏭️ No change for item detected : {}",
@@ -485,12 +838,34 @@ impl SyncProcessor {
                 );
             }
         }
+        self.invalidate_kernel_cache(before.as_ref(), &item.drive_item.id)
+            .await?;
         processing_repo
             .update_status_by_id(db_id, &ProcessingStatus::Done)
             .await?;
         Ok(())
     }
 
+    /// Drop what the kernel cached for an item at its old and new location
+    async fn invalidate_kernel_cache(
+        &self,
+        before: Option<&crate::persistency::types::DriveItemWithFuse>,
+        drive_item_id: &str,
+    ) -> Result<()> {
+        let kernel_cache = self.app_state.kernel_cache();
+        if let Some(before) = before {
+            kernel_cache.invalidate_item(before);
+        }
+        if let Some(after) = self
+            .drive_item_with_fuse_repo
+            .get_drive_item_with_fuse(drive_item_id)
+            .await?
+        {
+            kernel_cache.invalidate_item(&after);
+        }
+        Ok(())
+    }
+
     /// Process a local item (upload to OneDrive, etc.)
     async fn process_local_item(&self, item: &ProcessingItem) -> Result<()> {
         let processing_repo = self.app_state.persistency().processing_item_repository();
@@ -543,6 +918,9 @@ impl SyncProcessor {
             ChangeOperation::Rename { .. } => {
                 self.handle_local_rename(item).await?;
             }
+            ChangeOperation::Touch => {
+                self.handle_local_touch(item).await?;
+            }
             ChangeOperation::NoChange => {
                 error!(
                     "⏭️ No change for item detecded from local: {}",
@@ -704,6 +1082,8 @@ impl SyncProcessor {
                 .await?;
         }
 
+        text_merge::forget_base(&self.app_state, &item.drive_item.id).await;
+
         // Remove item from drive_items_with_fuse table
         if let Err(e) = drive_item_with_fuse_repo
             .mark_as_deleted_by_onedrive_id(&item.drive_item.id)
@@ -772,11 +1152,12 @@ impl SyncProcessor {
             ));
         };
 
-        // Move the item on OneDrive
+        // Move the item on OneDrive, unless it changed there since the last sync
+        let etag = self.stored_etag(&item.drive_item.id).await?;
         match self
             .app_state
             .onedrive_client
-            .move_item(&item.drive_item.id, new_parent_id)
+            .move_item(&item.drive_item.id, new_parent_id, etag.as_deref())
             .await
         {
             Ok(moved_item) => {
@@ -980,141 +1361,144 @@ impl SyncProcessor {
                 ));
             };
 
-            // Read file data from local path
+            // Streamed from the local file, large files go through an upload session
             if local_path.exists() {
-                match std::fs::read(&local_path) {
-                    Ok(file_data) => {
-                        // Use the correct API endpoint with parent ID
+                match self
+                    .upload_file(
+                        item,
+                        &local_path,
+                        UploadTarget::New {
+                            parent_id: &parent_id,
+                            file_name,
+                        },
+                    )
+                    .await
+                {
+                    Ok(result) => {
+                        info!(
+                            "📤 Uploaded file to OneDrive: {} -> {}",
+                            file_name, result.onedrive_id
+                        );
+
+                        // Update all database references from temporary ID to real OneDrive ID
+                        let temporary_id = &item.drive_item.id;
+                        let real_onedrive_id = &result.onedrive_id;
+
+                        info!("🔄 Starting ID update process for new file: {} -> {}", temporary_id, real_onedrive_id);
+
+                        // Update download_queue FIRST (has FK constraint on drive_items_with_fuse.onedrive_id)
+                        info!("🔄 Updating download queue repository...");
+                        let download_queue_repo = self.app_state.persistency().download_queue_repository();
+                        download_queue_repo
+                            .update_onedrive_id(temporary_id, real_onedrive_id)
+                            .await?;
+                        info!("✅ Updated download queue repository");
+
+                        // Now safe to update DriveItemWithFuse (parent of FK)
+                        info!("🔄 Updating DriveItemWithFuse repository...");
+                        self.drive_item_with_fuse_repo
+                            .update_onedrive_id(temporary_id, real_onedrive_id)
+                            .await?;
+                        info!("✅ Updated DriveItemWithFuse repository");
+
+                        // Update ProcessingItems
+                        info!("🔄 Updating ProcessingItems repository...");
+                        self.processing_repo
+                            .update_onedrive_id(temporary_id, real_onedrive_id)
+                            .await?;
+                        info!("✅ Updated ProcessingItems repository");
+
+                        // Update parent IDs for any children that reference this temporary ID
+                        info!("🔄 Updating parent IDs in DriveItemWithFuse...");
+                        self.drive_item_with_fuse_repo
+                            .update_parent_id_for_children(temporary_id, real_onedrive_id)
+                            .await?;
+                        info!("✅ Updated parent IDs in DriveItemWithFuse");
+
+                        info!("🔄 Updating parent IDs in ProcessingItems...");
+                        self.processing_repo
+                            .update_parent_id_for_children(temporary_id, real_onedrive_id)
+                            .await?;
+                        info!("✅ Updated parent IDs in ProcessingItems");
+
+                        debug!(
+                            "🔄 Updated database references: {} -> {}",
+                            temporary_id, real_onedrive_id
+                        );
+
+                        // Get the full DriveItem from OneDrive to update with complete metadata
                         match self
                             .app_state
                             .onedrive_client
-                            .upload_new_file_to_parent(&file_data, file_name, &parent_id)
+                            .get_item_by_id(real_onedrive_id)
                             .await
                         {
-                            Ok(result) => {
-                                info!(
-                                    "📤 Uploaded file to OneDrive: {} -> {}",
-                                    file_name, result.onedrive_id
-                                );
-
-                                // Update all database references from temporary ID to real OneDrive ID
-                                let temporary_id = &item.drive_item.id;
-                                let real_onedrive_id = &result.onedrive_id;
-
-                                info!("🔄 Starting ID update process for new file: {} -> {}", temporary_id, real_onedrive_id);
-
-                                // Update download_queue FIRST (has FK constraint on drive_items_with_fuse.onedrive_id)
-                                info!("🔄 Updating download queue repository...");
-                                let download_queue_repo = self.app_state.persistency().download_queue_repository();
-                                download_queue_repo
-                                    .update_onedrive_id(temporary_id, real_onedrive_id)
-                                    .await?;
-                                info!("✅ Updated download queue repository");
-
-                                // Now safe to update DriveItemWithFuse (parent of FK)
-                                info!("🔄 Updating DriveItemWithFuse repository...");
-                                self.drive_item_with_fuse_repo
-                                    .update_onedrive_id(temporary_id, real_onedrive_id)
-                                    .await?;
-                                info!("✅ Updated DriveItemWithFuse repository");
-
-                                // Update ProcessingItems
-                                info!("🔄 Updating ProcessingItems repository...");
-                                self.processing_repo
-                                    .update_onedrive_id(temporary_id, real_onedrive_id)
-                                    .await?;
-                                info!("✅ Updated ProcessingItems repository");
+                            Ok(full_drive_item) => {
+                                text_merge::remember_base_from_file(
+                                    &self.app_state,
+                                    real_onedrive_id,
+                                    result.ctag.as_deref(),
+                                    &local_path,
+                                )
+                                .await;
+
+                                // Move file from upload folder to download folder
+                                if let Err(e) = self
+                                    .move_file_to_its_new_name(
+                                        &local_path,
+                                        real_onedrive_id,
+                                    )
+                                    .await
+                                {
+                                    warn!(
+                                        "⚠️ Failed to move file to download folder: {}",
+                                        e
+                                    );
+                                }
 
-                                // Update parent IDs for any children that reference this temporary ID
-                                info!("🔄 Updating parent IDs in DriveItemWithFuse...");
-                                self.drive_item_with_fuse_repo
-                                    .update_parent_id_for_children(temporary_id, real_onedrive_id)
+                                // Setup FUSE metadata for the uploaded file with real OneDrive data
+                                let local_downloads_path = self
+                                    .app_state
+                                    .config()
+                                    .project_dirs
+                                    .data_dir()
+                                    .join("downloads");
+                                let _inode = self
+                                    .setup_fuse_metadata(
+                                        &full_drive_item,
+                                        &self.drive_item_with_fuse_repo,
+                                        &local_downloads_path,
+                                    )
                                     .await?;
-                                info!("✅ Updated parent IDs in DriveItemWithFuse");
 
-                                info!("🔄 Updating parent IDs in ProcessingItems...");
+                                // Update the processing item with the real OneDrive data
+                                let mut updated_processing_item = item.clone();
+                                updated_processing_item.drive_item = full_drive_item;
                                 self.processing_repo
-                                    .update_parent_id_for_children(temporary_id, real_onedrive_id)
+                                    .update_processing_item(&updated_processing_item)
                                     .await?;
-                                info!("✅ Updated parent IDs in ProcessingItems");
 
-                                debug!(
-                                    "🔄 Updated database references: {} -> {}",
-                                    temporary_id, real_onedrive_id
-                                );
-
-                                // Get the full DriveItem from OneDrive to update with complete metadata
-                                match self
-                                    .app_state
-                                    .onedrive_client
-                                    .get_item_by_id(real_onedrive_id)
-                                    .await
-                                {
-                                    Ok(full_drive_item) => {
-                                        // Move file from upload folder to download folder
-                                        if let Err(e) = self
-                                            .move_file_to_its_new_name(
-                                                &local_path,
-                                                real_onedrive_id,
-                                            )
-                                            .await
-                                        {
-                                            warn!(
-                                                "⚠️ Failed to move file to download folder: {}",
-                                                e
-                                            );
-                                        }
-
-                                        // Setup FUSE metadata for the uploaded file with real OneDrive data
-                                        let local_downloads_path = self
-                                            .app_state
-                                            .config()
-                                            .project_dirs
-                                            .data_dir()
-                                            .join("downloads");
-                                        let _inode = self
-                                            .setup_fuse_metadata(
-                                                &full_drive_item,
-                                                &self.drive_item_with_fuse_repo,
-                                                &local_downloads_path,
-                                            )
-                                            .await?;
-
-                                        // Update the processing item with the real OneDrive data
-                                        let mut updated_processing_item = item.clone();
-                                        updated_processing_item.drive_item = full_drive_item;
-                                        self.processing_repo
-                                            .update_processing_item(&updated_processing_item)
-                                            .await?;
-
-                                        // NEW: Store ctag from upload result
-                                        if let Some(ctag) = &result.ctag {
-                                            self.drive_item_with_fuse_repo
-                                                .update_ctag(real_onedrive_id, ctag)
-                                                .await?;
-                                            debug!("✅ Stored ctag for uploaded file: {} -> {}", file_name, ctag);
-                                        }
-
-                                        debug!("✅ Updated processing item with real OneDrive data for file: {}", file_name);
-                                    }
-                                    Err(e) => {
-                                        warn!(
-                                            "⚠️ Failed to get full DriveItem for uploaded file: {}",
-                                            e
-                                        );
-                                        // Continue anyway since we have the basic info
-                                    }
+                                // NEW: Store ctag from upload result
+                                if let Some(ctag) = &result.ctag {
+                                    self.drive_item_with_fuse_repo
+                                        .update_ctag(real_onedrive_id, ctag)
+                                        .await?;
+                                    debug!("✅ Stored ctag for uploaded file: {} -> {}", file_name, ctag);
                                 }
+                                debug!("✅ Updated processing item with real OneDrive data for file: {}", file_name);
                             }
                             Err(e) => {
-                                error!("❌ Failed to upload file to OneDrive: {}", e);
-                                return Err(e);
+                                warn!(
+                                    "⚠️ Failed to get full DriveItem for uploaded file: {}",
+                                    e
+                                );
+                                // Continue anyway since we have the basic info
                             }
                         }
                     }
                     Err(e) => {
-                        error!("❌ Failed to read local file for upload: {}", e);
-                        return Err(anyhow::anyhow!("Failed to read local file: {}", e));
+                        error!("❌ Failed to upload file to OneDrive: {}", e);
+                        return Err(e);
                     }
                 }
             } else {
@@ -1147,6 +1531,10 @@ impl SyncProcessor {
             .get_local_dir()
             .join(fs.virtual_ino().unwrap().to_string());
         if !path.exists() {
+            // A file that is not downloaded can still have its modification time changed
+            if item.drive_item.folder.is_none() && fs.fuse_metadata.mtime.is_some() {
+                return self.handle_local_touch(item).await;
+            }
             return Err(anyhow::anyhow!(
                 "Local file does not exist: {}",
                 path.display()
@@ -1177,11 +1565,15 @@ impl SyncProcessor {
             // For files, read the file and update on OneDrive
 
             if path.exists() {
-                let file_data = std::fs::read(&path).context("Failed to read local file")?;
                 let result = self
-                    .app_state
-                    .onedrive_client
-                    .upload_updated_file(&file_data, &item.drive_item.id)
+                    .upload_file(
+                        item,
+                        &path,
+                        UploadTarget::Existing {
+                            item_id: &item.drive_item.id,
+                            if_match: fs.etag(),
+                        },
+                    )
                     .await
                     .context(format!("Failed to update file on OneDrive , {}  / {} " , item.drive_item.id , item.drive_item.name.as_deref().unwrap_or("unnamed")))?;
                 info!(
@@ -1202,6 +1594,11 @@ impl SyncProcessor {
                 fs.drive_item.set_size(result.size.clone().unwrap());
                 fs.drive_item.set_ctag(result.ctag.clone().unwrap());
 
+                // Keep a modification time set via touch/utimens across machines
+                if fs.fuse_metadata.mtime.is_some() {
+                    self.push_last_modified(&mut fs, &path).await;
+                }
+
                 info!("✅ Updated local fs object with new ID: {}", new_id);
 
                 // Update all database references with the new OneDrive ID
@@ -1214,6 +1611,8 @@ impl SyncProcessor {
                     .await
                     .context("Failed to store modifiedFUSE item")?;
                 info!("✅ Successfully stored updated FUSE item");
+                text_merge::remember_base_from_file(&self.app_state, &new_id, result.ctag.as_deref(), &path)
+                    .await;
             } else {
                 return Err(anyhow::anyhow!(
                     "Local file does not exist: {}",
@@ -1225,6 +1624,83 @@ impl SyncProcessor {
         Ok(())
     }
 
+    /// Upload a modification time set via touch/utimens without uploading the content
+    async fn handle_local_touch(&self, item: &ProcessingItem) -> Result<()> {
+        let mut fs = self
+            .drive_item_with_fuse_repo
+            .get_drive_item_with_fuse(&item.drive_item.id)
+            .await
+            .context("Failed to obtain FUSE item")?
+            .ok_or_else(|| anyhow::anyhow!("FUSE item not found: {}", item.drive_item.id))?;
+        let path = self
+            .app_state
+            .file_manager()
+            .get_local_dir()
+            .join(fs.virtual_ino().unwrap_or(0).to_string());
+        self.push_last_modified(&mut fs, &path).await;
+        self.drive_item_with_fuse_repo
+            .store_drive_item_with_fuse(&fs)
+            .await
+            .context("Failed to store modified FUSE item")?;
+        Ok(())
+    }
+
+    /// Upload the local modification time as `fileSystemInfo.lastModifiedDateTime`.
+    /// Uses the local file's mtime if it is downloaded, otherwise the stored mtime,
+    /// which is dropped once uploaded.
+    async fn push_last_modified(&self, fs: &mut crate::persistency::types::DriveItemWithFuse, path: &std::path::Path) {
+        let last_modified = match std::fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(time) => Some(AttributeManager::format_time(time)),
+            Err(_) => fs.fuse_metadata.mtime.clone(),
+        };
+        let Some(last_modified) = last_modified else {
+            return;
+        };
+
+        match self
+            .app_state
+            .onedrive()
+            .update_last_modified(&fs.drive_item.id, &last_modified)
+            .await
+        {
+            Ok(updated) => {
+                // Updating fileSystemInfo changes the eTag
+                if let Some(etag) = updated.etag {
+                    fs.drive_item.set_etag(etag);
+                }
+                fs.drive_item
+                    .set_last_modified(updated.last_modified.unwrap_or(last_modified));
+                if let Some(ino) = fs.virtual_ino() {
+                    if let Err(e) = self.drive_item_with_fuse_repo.clear_mtime_override(ino).await {
+                        warn!("⚠️ Failed to clear uploaded modification time: {}", e);
+                    }
+                }
+                fs.fuse_metadata.mtime = None;
+                debug!(
+                    "🕒 Uploaded modification time for {}",
+                    fs.drive_item.name.as_deref().unwrap_or("unnamed")
+                );
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️ Failed to upload modification time for {}: {}",
+                    fs.drive_item.name.as_deref().unwrap_or("unnamed"),
+                    e
+                );
+            }
+        }
+    }
+
+    /// eTag of an item as of the last sync, sent as `If-Match` so a change made on
+    /// OneDrive in the meantime is not overwritten
+    async fn stored_etag(&self, drive_item_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .drive_item_with_fuse_repo
+            .get_drive_item_with_fuse(drive_item_id)
+            .await?
+            .and_then(|existing| existing.etag().map(str::to_string)))
+    }
+
     async fn handle_local_delete(&self, item: &ProcessingItem) -> Result<()> {
         debug!(
             "🗑️ Processing local delete: {}",
@@ -1236,12 +1712,15 @@ impl SyncProcessor {
             .persistency()
             .drive_item_with_fuse_repository();
 
-        // Get the virtual path for the item to delete
-        let virtual_path = if let Some(existing_item) = drive_item_with_fuse_repo
+        // Get the virtual path and last synced eTag of the item to delete
+        let (virtual_path, etag) = if let Some(existing_item) = drive_item_with_fuse_repo
             .get_drive_item_with_fuse(&item.drive_item.id)
             .await?
         {
-            existing_item.virtual_path().unwrap_or_default().to_string()
+            (
+                existing_item.virtual_path().unwrap_or_default().to_string(),
+                existing_item.etag().map(str::to_string),
+            )
         } else {
             return Err(anyhow::anyhow!(
                 "Item not found in FUSE database for deletion"
@@ -1252,7 +1731,7 @@ impl SyncProcessor {
         match self
             .app_state
             .onedrive_client
-            .delete_item(&virtual_path)
+            .delete_item(&virtual_path, etag.as_deref())
             .await
         {
             Ok(result) => {
@@ -1260,27 +1739,31 @@ impl SyncProcessor {
                     "🗑️ Deleted item from OneDrive: {} -> {}",
                     virtual_path, result.item_path
                 );
-
-                // Mark as deleted in FUSE database
-                let local_downloads_path = self
-                    .app_state
-                    .config()
-                    .project_dirs
-                    .data_dir()
-                    .join("downloads");
-                let _inode = self
-                    .setup_fuse_metadata(
-                        &item.drive_item,
-                        &drive_item_with_fuse_repo,
-                        &local_downloads_path,
-                    )
-                    .await?;
+            }
+            Err(e) if matches!(graph_error(&e), Some(GraphError::NotFound(_))) => {
+                info!("🗑️ Item was already deleted on OneDrive: {}", virtual_path);
             }
             Err(e) => {
                 error!("❌ Failed to delete item from OneDrive: {}", e);
                 return Err(e);
             }
         }
+        text_merge::forget_base(&self.app_state, &item.drive_item.id).await;
+
+        // Mark as deleted in FUSE database
+        let local_downloads_path = self
+            .app_state
+            .config()
+            .project_dirs
+            .data_dir()
+            .join("downloads");
+        let _inode = self
+            .setup_fuse_metadata(
+                &item.drive_item,
+                &drive_item_with_fuse_repo,
+                &local_downloads_path,
+            )
+            .await?;
 
         Ok(())
     }
@@ -1306,11 +1789,12 @@ impl SyncProcessor {
             ));
         };
 
-        // Move the item on OneDrive
+        // Move the item on OneDrive, unless it changed there since the last sync
+        let etag = self.stored_etag(&item.drive_item.id).await?;
         match self
             .app_state
             .onedrive_client
-            .move_item(&item.drive_item.id, new_parent_id)
+            .move_item(&item.drive_item.id, new_parent_id, etag.as_deref())
             .await
         {
             Ok(moved_item) => {
@@ -1374,11 +1858,12 @@ impl SyncProcessor {
             return Err(anyhow::anyhow!("No name specified for rename operation"));
         };
 
-        // Rename the item on OneDrive
+        // Rename the item on OneDrive, unless it changed there since the last sync
+        let etag = self.stored_etag(&item.drive_item.id).await?;
         match self
             .app_state
             .onedrive_client
-            .rename_item(&item.drive_item.id, new_name)
+            .rename_item(&item.drive_item.id, new_name, etag.as_deref())
             .await
         {
             Ok(renamed_item) => {
@@ -1473,6 +1958,19 @@ impl SyncProcessor {
             .store_drive_item_with_fuse(&item_with_fuse)
             .await?;
 
+        // Times set locally are outdated once the file changed remotely
+        if let Some(existing) = &existing_item {
+            let parse = |s: Option<&String>| {
+                s.and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            };
+            if (existing.fuse_metadata.mtime.is_some() || existing.fuse_metadata.atime.is_some())
+                && parse(existing.drive_item.last_modified.as_ref())
+                    != parse(item.last_modified.as_ref())
+            {
+                drive_item_with_fuse_repo.clear_time_overrides(inode).await?;
+            }
+        }
+
         Ok(inode)
     }
 
@@ -1486,23 +1984,14 @@ impl SyncProcessor {
     ///
     /// # Logic
     /// 1. Skip folders (download on demand)
-    /// 2. If no download folders configured, download everything
-    /// 3. Check if item's parent path matches any configured download folder
-    /// 4. Path matching strips "/drive/root:/" prefix and uses exact folder matching
+    /// 2. Download files pinned via the `user.onedrive.pin` xattr
+    /// 3. If no download folders configured, download nothing
+    /// 4. Check if item's parent path matches any configured download folder
+    /// 5. Path matching strips "/drive/root:/" prefix and uses exact folder matching
     async fn should_download(
         &self,
         item: &crate::onedrive_service::onedrive_models::DriveItem,
     ) -> bool {
-        // Get configured download folders from settings
-        let download_folders = self
-            .app_state
-            .config()
-            .settings
-            .read()
-            .await
-            .download_folders
-            .clone();
-
         // Skip folders - they are downloaded on demand when accessed
         if item.folder.is_some() {
             debug!(
@@ -1512,54 +2001,31 @@ impl SyncProcessor {
             return false;
         }
 
-        // If no download folders specified, download all files
-        if download_folders.is_empty() {
-            debug!("📥 No download folders configured, do not download anything!");
-            return false;
-        }
-
-        // Check if item's parent path matches any configured download folder
-        if let Some(parent_ref) = &item.parent_reference {
-            if let Some(parent_path) = &parent_ref.path {
-                // Strip "/drive/root:/" prefix to get the virtual path
-                // Example: "/drive/root:/Test/Downloads" -> "/Test/Downloads"
-                const DRIVE_ROOT_PREFIX: &str = "/drive/root:/";
-                let virtual_path = parent_path
-                    .strip_prefix(DRIVE_ROOT_PREFIX)
-                    .unwrap_or(parent_path);
-
-                // Check if any download folder matches as a prefix (exact folder matching)
-                for folder in &download_folders {
-                    if virtual_path.starts_with(folder) {
-                        debug!(
-                            "📥 File matches download folder '{}': {} (path: {})",
-                            folder,
-                            item.name.as_deref().unwrap_or("unnamed"),
-                            virtual_path
-                        );
-                        return true;
-                    }
-                }
-
-                debug!(
-                    "⏭️ File does not match any download folder: {} (path: {})",
-                    item.name.as_deref().unwrap_or("unnamed"),
-                    virtual_path
-                );
-            } else {
-                debug!(
-                    "⚠️ No parent path available for item: {}",
-                    item.name.as_deref().unwrap_or("unnamed")
-                );
-            }
-        } else {
+        // Files pinned via the user.onedrive.pin xattr are always kept downloaded
+        if self
+            .app_state
+            .persistency()
+            .pinned_item_repository()
+            .is_pinned_by_onedrive_id(&item.id)
+            .await
+            .unwrap_or(false)
+        {
             debug!(
-                "⚠️ No parent reference available for item: {}",
+                "📌 File is pinned for download: {}",
                 item.name.as_deref().unwrap_or("unnamed")
             );
+            return true;
         }
 
-        false
+        // Download folders and include patterns, minus the exclude patterns
+        let path = selective_sync::item_path(item);
+        if self.app_state.sync_rules().await.is_included(&path) {
+            debug!("📥 File matches the selective sync rules: {}", path);
+            true
+        } else {
+            debug!("⏭️ File does not match the selective sync rules: {}", path);
+            false
+        }
     }
 
     fn etag_changed(
@@ -1836,6 +2302,7 @@ impl SyncProcessor {
                     path: parent_path.clone(),
                 }
             }),
+            web_url: None,
         };
 
         let mut item_with_fuse = self.drive_item_with_fuse_repo
@@ -1,6 +1,7 @@
 use crate::app_state::AppState;
 use crate::persistency::drive_item_with_fuse_repository::DriveItemWithFuseRepository;
 use crate::persistency::processing_item_repository::{ChangeOperation, ChangeType, ProcessingItem, ProcessingStatus};
+use crate::sync::conflict_resolution::ConflictResolver;
 use crate::sync::conflicts::{LocalConflict, RemoteConflict};
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
@@ -9,12 +10,14 @@ use std::sync::Arc;
 pub struct SyncStrategy {
     app_state: Arc<AppState>,
     drive_item_with_fuse_repo: DriveItemWithFuseRepository,
+    resolver: ConflictResolver,
 }
 
 impl SyncStrategy {
     pub fn new(app_state: Arc<AppState>) -> Self {
         let drive_item_with_fuse_repo = app_state.persistency().drive_item_with_fuse_repository();
         Self {
+            resolver: ConflictResolver::new(app_state.clone()),
             app_state,
             drive_item_with_fuse_repo,
         }
@@ -294,6 +297,39 @@ impl SyncStrategy {
         Ok(())
     }
 
+    /// Merge a remote update into a local one when the only conflict is their content.
+    /// Returns false if the item has to be marked as conflicted.
+    pub async fn merge_content_conflict(&self, item: &ProcessingItem, conflicts: &[RemoteConflict]) -> bool {
+        let content_only = conflicts.iter().all(|conflict| {
+            matches!(conflict, RemoteConflict::ContentConflict(..) | RemoteConflict::ModifyOnModify(..))
+        });
+        if !content_only {
+            return false;
+        }
+        match self.resolver.merge_content(item).await {
+            Ok(merged) => merged,
+            Err(e) => {
+                warn!("⚠️ Failed to merge changes of {}: {}", item.drive_item.id, e);
+                false
+            }
+        }
+    }
+
+    /// Resolve a conflicted item (local or remote) with the configured conflict resolution
+    /// strategy. Returns false if it has to wait for the user.
+    pub async fn apply_conflict_strategy(&self, item: &ProcessingItem) -> bool {
+        match self.resolver.apply_strategy(item).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                warn!(
+                    "⚠️ Failed to resolve conflict of {} automatically: {}",
+                    item.drive_item.id, e
+                );
+                false
+            }
+        }
+    }
+
     /// Restore a parent item from OneDrive and mark it as not deleted in local database
     async fn restore_parent_from_onedrive(&self, parent_id: &str) -> Result<()> {
         // 1. Fetch parent DriveItem from OneDrive by ID
@@ -0,0 +1,79 @@
+//! Three-way merge of text files changed on both sides
+//!
+//! Whenever a text file is synced (downloaded or uploaded) its content is kept
+//! as merge base, together with the ctag of that version. When a remote update
+//! then meets a local one, local and remote content are merged against the
+//! base. Only files whose changes overlap are left as conflicts.
+
+use crate::app_state::AppState;
+use log::{debug, warn};
+use std::path::Path;
+
+/// Files larger than this are not kept as merge base and never merged
+pub const MAX_MERGE_SIZE: u64 = 1024 * 1024;
+
+/// Content as text if the file is small enough and not binary
+pub fn as_text(bytes: &[u8]) -> Option<&str> {
+    if bytes.len() as u64 > MAX_MERGE_SIZE || bytes.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+/// Merge local and remote changes against their common base.
+/// Returns None if the changes overlap.
+pub fn merge(base: &str, local: &str, remote: &str) -> Option<String> {
+    diffy::merge(base, local, remote).ok()
+}
+
+/// Unified diff from the local to the remote version of a file
+pub fn unified_diff(name: &str, local: &str, remote: &str) -> String {
+    diffy::DiffOptions::new()
+        .set_original_filename(format!("{} (local)", name))
+        .set_modified_filename(format!("{} (remote)", name))
+        .create_patch(local, remote)
+        .to_string()
+}
+
+/// Keep the synced content of a file as base for later merges.
+/// Binary and large files drop an older base instead.
+pub async fn remember_base(app_state: &AppState, drive_item_id: &str, ctag: Option<&str>, content: &[u8]) {
+    let repo = app_state.persistency().merge_base_repository();
+    let result = match (ctag, as_text(content)) {
+        (Some(ctag), Some(text)) => repo.store_base(drive_item_id, ctag, text).await,
+        _ => {
+            debug!("🧬 No merge base for {}: not a synced text file", drive_item_id);
+            repo.remove_base(drive_item_id).await
+        }
+    };
+    if let Err(e) = result {
+        warn!("⚠️ Failed to update merge base of {}: {}", drive_item_id, e);
+    }
+}
+
+/// Drop the merge base of a file that was deleted
+pub async fn forget_base(app_state: &AppState, drive_item_id: &str) {
+    let repo = app_state.persistency().merge_base_repository();
+    if let Err(e) = repo.remove_base(drive_item_id).await {
+        warn!("⚠️ Failed to remove merge base of {}: {}", drive_item_id, e);
+    }
+}
+
+/// Content of a file is small enough to be merged or diffed
+pub fn fits_merge(size: Option<u64>) -> bool {
+    size.is_none_or(|size| size <= MAX_MERGE_SIZE)
+}
+
+/// Keep the synced content of a file on disk as merge base, without reading files
+/// too large to merge
+pub async fn remember_base_from_file(app_state: &AppState, drive_item_id: &str, ctag: Option<&str>, path: &Path) {
+    let content = match tokio::fs::metadata(path).await {
+        Ok(metadata) if metadata.len() <= MAX_MERGE_SIZE => tokio::fs::read(path).await.ok(),
+        _ => None,
+    };
+    match content {
+        Some(content) => remember_base(app_state, drive_item_id, ctag, &content).await,
+        // Too large or unreadable, drop an older base
+        None => remember_base(app_state, drive_item_id, None, &[]).await,
+    }
+}
@@ -0,0 +1,259 @@
+//! Push notifications for changes in the drive
+//!
+//! Graph hands out a Socket.IO endpoint for the drive
+//! (`/me/drive/root/subscriptions/socketIo`) that emits a `notification` event
+//! whenever something changes. The change notification task listens to it and
+//! pulls the sync task forward through the `SyncTrigger`; the notification only
+//! says that something changed, the delta query still finds out what. Polling
+//! stays as the fallback when the connection is down.
+//!
+//! The transport sits behind `ChangeNotificationTransport` so tests can drive
+//! the task without Graph.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use log::debug;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::onedrive_service::onedrive_client::OneDriveClientTrait;
+
+/// A local change is uploaded at most this long after it was queued; changes that come
+/// in meanwhile go with the same cycle
+pub const LOCAL_CHANGE_DELAY: Duration = Duration::from_secs(2);
+
+/// Wakes the sync task before its next poll
+#[derive(Default)]
+pub struct SyncTrigger {
+    wakeup: Notify,
+    local_change: Notify,
+    push_connected: AtomicBool,
+}
+
+impl SyncTrigger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a sync cycle now, or right after the running one
+    pub fn trigger(&self) {
+        self.wakeup.notify_one();
+    }
+
+    /// Run a sync cycle `LOCAL_CHANGE_DELAY` after a local change was queued
+    pub fn local_change(&self) {
+        self.local_change.notify_one();
+    }
+
+    /// Wait until `trigger` is called, `LOCAL_CHANGE_DELAY` after `local_change` is called,
+    /// or until the timeout passes. Returns true if triggered.
+    pub async fn wait(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut local_deadline = None;
+        loop {
+            tokio::select! {
+                _ = self.wakeup.notified() => return true,
+                _ = self.local_change.notified(), if local_deadline.is_none() => {
+                    local_deadline = Some(tokio::time::Instant::now() + LOCAL_CHANGE_DELAY);
+                }
+                _ = tokio::time::sleep_until(local_deadline.unwrap_or(deadline).min(deadline)) => {
+                    return local_deadline.is_some_and(|local| local <= deadline);
+                }
+            }
+        }
+    }
+
+    /// Record whether change notifications are being received
+    pub fn set_push_connected(&self, connected: bool) {
+        self.push_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn is_push_connected(&self) -> bool {
+        self.push_connected.load(Ordering::Relaxed)
+    }
+}
+
+/// Opens connections that report changes in the drive
+#[async_trait]
+pub trait ChangeNotificationTransport: Send + Sync {
+    async fn connect(&self) -> Result<Box<dyn ChangeNotificationStream>>;
+}
+
+/// An open change notification connection
+#[async_trait]
+pub trait ChangeNotificationStream: Send {
+    /// Wait for the next change; an error means the connection is gone
+    async fn next_change(&mut self) -> Result<()>;
+}
+
+/// Change notifications from the Graph Socket.IO endpoint
+pub struct SocketIoTransport {
+    onedrive_client: Arc<dyn OneDriveClientTrait>,
+}
+
+impl SocketIoTransport {
+    pub fn new(onedrive_client: Arc<dyn OneDriveClientTrait>) -> Self {
+        Self { onedrive_client }
+    }
+}
+
+#[async_trait]
+impl ChangeNotificationTransport for SocketIoTransport {
+    async fn connect(&self) -> Result<Box<dyn ChangeNotificationStream>> {
+        // The endpoint expires, every connection asks for a fresh one
+        let notification_url = self.onedrive_client.get_socket_io_endpoint().await?;
+        Ok(Box::new(SocketIoStream::connect(&notification_url).await?))
+    }
+}
+
+/// Split a Socket.IO notification URL into the Engine.IO websocket URL and the namespace,
+/// e.g. `https://host/ns?token=x` into `wss://host/socket.io/?EIO=4&transport=websocket&token=x`
+/// and `/ns`
+pub fn socket_io_url(notification_url: &str) -> Result<(String, String)> {
+    let mut url = url::Url::parse(notification_url)
+        .with_context(|| format!("Invalid Socket.IO URL: {}", notification_url))?;
+    let scheme = match url.scheme() {
+        "https" | "wss" => "wss",
+        "http" | "ws" => "ws",
+        other => bail!("Unsupported Socket.IO URL scheme: {}", other),
+    };
+    let namespace = url.path().trim_end_matches('/').to_string();
+    let query = match url.query() {
+        Some(query) if !query.is_empty() => format!("EIO=4&transport=websocket&{}", query),
+        _ => "EIO=4&transport=websocket".to_string(),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| anyhow!("Failed to set scheme of {}", notification_url))?;
+    url.set_path("/socket.io/");
+    url.set_query(Some(&query));
+    Ok((url.to_string(), namespace))
+}
+
+/// Engine.IO defaults until the open packet tells the server's ping timing
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_millis(25_000);
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_millis(20_000);
+
+/// A Socket.IO client connection, just enough to receive events
+pub struct SocketIoStream {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    namespace: String,
+    /// The server pings within this time; a silent connection is considered dead
+    read_timeout: Duration,
+}
+
+impl SocketIoStream {
+    /// Connect and join the namespace of the notification URL
+    pub async fn connect(notification_url: &str) -> Result<Self> {
+        let (ws_url, namespace) = socket_io_url(notification_url)?;
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url.as_str())
+            .await
+            .context("Failed to connect to Socket.IO endpoint")?;
+        let mut stream = Self {
+            socket,
+            namespace,
+            read_timeout: DEFAULT_PING_INTERVAL + DEFAULT_PING_TIMEOUT,
+        };
+
+        // Engine.IO open packet: 0{"sid":...,"pingInterval":...}
+        let open = stream.next_packet().await?;
+        let Some(handshake) = open.strip_prefix('0') else {
+            bail!("Unexpected Engine.IO open packet: {}", open);
+        };
+        if let Ok(handshake) = serde_json::from_str::<serde_json::Value>(handshake) {
+            let millis = |key: &str, default: Duration| {
+                handshake
+                    .get(key)
+                    .and_then(|value| value.as_u64())
+                    .map_or(default, Duration::from_millis)
+            };
+            stream.read_timeout =
+                millis("pingInterval", DEFAULT_PING_INTERVAL) + millis("pingTimeout", DEFAULT_PING_TIMEOUT);
+        }
+
+        // Socket.IO connect to the namespace
+        let connect = match stream.namespace.as_str() {
+            "" => "40".to_string(),
+            namespace => format!("40{},", namespace),
+        };
+        stream.send(connect).await?;
+        loop {
+            let packet = stream.next_packet().await?;
+            if packet.starts_with("40") {
+                break;
+            }
+            if let Some(error) = packet.strip_prefix("44") {
+                bail!("Socket.IO connection refused: {}", error);
+            }
+        }
+        debug!("🔔 Connected to Socket.IO namespace {}", stream.namespace);
+        Ok(stream)
+    }
+
+    async fn send(&mut self, packet: String) -> Result<()> {
+        self.socket
+            .send(Message::Text(packet))
+            .await
+            .context("Failed to send Socket.IO packet")
+    }
+
+    /// The next Engine.IO packet, answering pings on the way. Fails if the server
+    /// stays silent for longer than its ping interval and timeout.
+    async fn next_packet(&mut self) -> Result<String> {
+        loop {
+            let message = tokio::time::timeout(self.read_timeout, self.socket.next())
+                .await
+                .map_err(|_| anyhow!("No ping from the Socket.IO server in {:?}", self.read_timeout))?
+                .ok_or_else(|| anyhow!("Socket.IO connection closed"))?
+                .context("Failed to read from Socket.IO connection")?;
+            match message {
+                Message::Text(text) => {
+                    let packet = text.to_string();
+                    match packet.as_str() {
+                        // Ping, answered with a pong to keep the connection
+                        "2" => self.send("3".to_string()).await?,
+                        "1" => bail!("Socket.IO connection closed by the server"),
+                        _ => return Ok(packet),
+                    }
+                }
+                Message::Close(_) => bail!("Socket.IO connection closed"),
+                // Websocket pings are answered by tungstenite
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ChangeNotificationStream for SocketIoStream {
+    async fn next_change(&mut self) -> Result<()> {
+        loop {
+            let packet = self.next_packet().await?;
+            if packet.starts_with("41") {
+                bail!("Socket.IO namespace disconnected");
+            }
+            let Some(event) = packet.strip_prefix("42") else {
+                continue;
+            };
+            // Strip the namespace ("/ns,") and an ack id before the event array
+            let event = match event.strip_prefix('/') {
+                Some(rest) => rest.split_once(',').map_or("", |(_, payload)| payload),
+                None => event,
+            };
+            let event = event.trim_start_matches(|c: char| c.is_ascii_digit());
+            let name = serde_json::from_str::<serde_json::Value>(event)
+                .ok()
+                .and_then(|value| value.get(0).and_then(|name| name.as_str()).map(str::to_string));
+            if name.as_deref() == Some("notification") {
+                debug!("🔔 Change notification received");
+                return Ok(());
+            }
+        }
+    }
+}
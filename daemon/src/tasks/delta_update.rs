@@ -6,18 +6,17 @@ use log::{debug, error, info, warn};
 
 use crate::{
     app_state::AppState,
+    file_manager::FileManager,
+    onedrive_service::http_client::{graph_error, GraphError},
     onedrive_service::onedrive_models::DriveItem,
     persistency::{
-        download_queue_repository::DownloadQueueRepository,
         drive_item_with_fuse_repository::DriveItemWithFuseRepository,
         processing_item_repository::{ProcessingItem, ProcessingItemRepository},
         sync_state_repository::SyncStateRepository,
+        types::DriveItemWithFuse,
     },
-    
 };
 
-use onedrive_sync_lib::notifications::{NotificationSender, NotificationUrgency};
-
 /// Default sync interval in seconds
 const DEFAULT_SYNC_INTERVAL_SECS: u64 = 30;
 
@@ -42,6 +41,17 @@ pub enum ChangeType {
     NoChange,
 }
 
+/// What `SyncCycle::sync_subtree` did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubtreeSync {
+    /// Items whose metadata was fetched again
+    pub refreshed: u32,
+    /// Items that changed on OneDrive
+    pub changed: u32,
+    /// Files queued for download again
+    pub queued_downloads: u32,
+}
+
 /// OneDrive delta synchronization cycle
 pub struct SyncCycle {
     app_state: Arc<AppState>,
@@ -128,7 +138,7 @@ impl SyncCycle {
                     }
                 }
 
-                Err(e) if e.to_string().contains("410") => {
+                Err(e) if matches!(graph_error(&e), Some(GraphError::Gone(_))) => {
                     // Token expired, restart delta sync
                     warn!("🔄 Delta token expired, restarting sync");
                     current_token = None;
@@ -177,151 +187,37 @@ impl SyncCycle {
         }
     }
 
-    /// Process download queue
-    async fn process_download_queue(&self) -> Result<()> {
-        let download_queue_repo = self.app_state.persistency().download_queue_repository();
-        let pending_downloads = download_queue_repo.get_pending_downloads().await?;
-
-        info!(
-            "📋 Processing {} pending downloads",
-            pending_downloads.len()
-        );
-
-        for (queue_id, drive_item_id, local_path) in pending_downloads {
-            match self.download_file(&drive_item_id, &local_path).await {
-                Ok(_) => {
-                    download_queue_repo
-                        .mark_download_completed(queue_id)
-                        .await?;
-                    info!("✅ Download completed: {}", drive_item_id);
-
-                    let drive_item_with_fuse_repo = self.app_state.persistency().drive_item_with_fuse_repository();
-                    let name = drive_item_with_fuse_repo
-                        .get_drive_item_with_fuse(&drive_item_id)
-                        .await?
-                        .unwrap()
-                        .drive_item
-                        .name
-                        .unwrap_or("unnamed".to_string());
-
-                    let notification_sender = NotificationSender::new().await;
-                    if let Ok(sender) = notification_sender {
-                        let filename = name;
-                        let _ = sender
-                            .send_notification(
-                                "Open OneDrive",
-                                0,
-                                "open-onedrive",
-                                "Open OneDrive",
-                                &format!("File {} finished downloading", filename),
-                                vec![],
-                                vec![("urgency", &NotificationUrgency::Normal.to_u8().to_string())],
-                                5000,
-                            )
-                            .await;
-                    }
-                    // Get the inode for this file
-                    let drive_item_with_fuse_repo = self.app_state.persistency().drive_item_with_fuse_repository();
-                    if let Ok(Some(item)) = drive_item_with_fuse_repo
-                        .get_drive_item_with_fuse(&drive_item_id)
-                        .await
-                    {
-                        if let Some(ino) = item.virtual_ino() {
-                            // Move the file to the local folder using inode
-                            self.app_state
-                                .file_manager
-                                .move_downloaded_file_to_local_folder(ino)
-                                .await?;
-                        }
-                    }
-                }
-                Err(e) => {
-                    download_queue_repo
-                        .mark_download_failed(queue_id, 0)
-                        .await?;
-                    error!("❌ Download failed for {}: {}", drive_item_id, e);
-                    // Skip and retry next cycle as per your strategy
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Download a single file
-    async fn download_file(&self, drive_item_id: &str, local_path: &std::path::Path) -> Result<()> {
-        // Fetch full DriveItem by ID to get download URL
-        let full_item = self
+    /// Fetch the drive quota from OneDrive and cache it for statfs and the UI
+    async fn refresh_drive_quota(&self) -> Result<()> {
+        let quota = self
             .app_state
             .onedrive_client
-            .get_item_by_id(drive_item_id)
+            .get_drive_quota()
             .await
-            .context("Failed to get item by ID")?;
+            .context("Failed to get drive quota")?;
 
-        if let Some(download_url) = full_item.download_url {
-            // Download file using OneDrive API
-            let filename = full_item.name.as_deref().unwrap_or("unnamed");
-            let download_result = self
-                .app_state
-                .onedrive_client
-                .download_file(&download_url, drive_item_id, filename)
-                .await
-                .context("Failed to download file")?;
-
-            // Get the length before moving the data
-            let data_len = download_result.file_data.len();
-
-            // Get the inode for this file to determine local path
-            let drive_item_with_fuse_repo = self.app_state.persistency().drive_item_with_fuse_repository();
-            let actual_local_path = if let Ok(Some(item)) = drive_item_with_fuse_repo
-                .get_drive_item_with_fuse(drive_item_id)
-                .await
-            {
-                if let Some(ino) = item.virtual_ino() {
-                    self.app_state
-                        .config()
-                        .project_dirs
-                        .data_dir()
-                        .join("downloads")
-                        .join(ino.to_string())
-                } else {
-                    local_path.to_path_buf()
-                }
-            } else {
-                local_path.to_path_buf()
-            };
-
-            // Write downloaded data to local file
-            std::fs::write(&actual_local_path, download_result.file_data).with_context(|| {
-                format!(
-                    "Failed to write file {}: {}",
-                    actual_local_path.display(),
-                    drive_item_id
-                )
-            })?;
+        self.app_state
+            .persistency()
+            .drive_quota_repository()
+            .store_quota(&quota)
+            .await
+            .context("Failed to store drive quota")?;
 
-            debug!(
-                "📥 Downloaded file: {} -> {} ({} bytes)",
-                drive_item_id,
-                local_path.display(),
-                data_len
-            );
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!(
-                "No download URL available for {}",
-                drive_item_id
-            ))
-        }
+        debug!(
+            "💽 Drive quota refreshed: {} of {} bytes used",
+            quota.used, quota.total
+        );
+        Ok(())
     }
 
-    /// Run the complete sync cycle
-    pub async fn run(&self) -> Result<()> {
+    /// Run the complete sync cycle. Returns whether there were remote or local changes,
+    /// so polling can slow down while the drive is idle.
+    pub async fn run(&self) -> Result<bool> {
         // Check if sync is paused
         let settings = self.app_state.config().settings.read().await;
         if settings.sync_paused {
             info!("⏸️ Sync is paused, skipping sync cycle");
-            return Ok(());
+            return Ok(false);
         }
         drop(settings); // Release the read lock
 
@@ -332,7 +228,7 @@ impl SyncCycle {
         info!("📊 Retrieved {} delta items", items.len());
 
         // Create ProcessingItems for remote changes (skip items with no actual changes)
-
+        let mut had_changes = false;
         for item in &items {
             let change_operation = self.detect_change_operation(item);
 
@@ -354,17 +250,115 @@ impl SyncCycle {
                 .store_processing_item(&processing_item)
                 .await?;
             info!("✅ Processing item stored: {}", _id);
+            had_changes = true;
         }
 
         // Process all items using the new two-way sync system
         let sync_processor =
             crate::sync::sync_processor::SyncProcessor::new(self.app_state.clone());
-        sync_processor.process_all_items().await?;
-        self.process_download_queue().await?;
+        // New local changes count as activity too, items retried from earlier cycles don't
+        had_changes |= sync_processor.process_all_items().await? > 0;
+        // Downloads queued by this cycle start right away
+        self.app_state.transfers().wake();
         //self.process_upload_queue().await?;
 
+        // Quota is informational only - a failure must not fail the cycle
+        if let Err(e) = self.refresh_drive_quota().await {
+            warn!("⚠️ Failed to refresh drive quota: {}", e);
+        }
+
         info!("✅ Two-way sync cycle completed");
-        Ok(())
+        Ok(had_changes)
+    }
+
+    /// Run a sync cycle in the background right away. Returns false if a cycle is already
+    /// running; the request is coalesced with it.
+    pub fn start_now(app_state: Arc<AppState>) -> bool {
+        let Ok(guard) = app_state.sync_running.clone().try_lock_owned() else {
+            debug!("⏭️ Sync cycle already running, not starting another one");
+            return false;
+        };
+        tokio::spawn(async move {
+            let _guard = guard;
+            info!("🔄 Starting requested sync cycle");
+            match SyncCycle::new(app_state).run().await {
+                Ok(_) => info!("✅ Requested sync cycle completed"),
+                Err(e) => error!("❌ Requested sync cycle failed: {}", e),
+            }
+        });
+        true
+    }
+
+    /// Fetch the metadata of an item and everything known below it again, without waiting
+    /// for the delta query. Changes are stored as remote processing items and applied by a
+    /// sync cycle that is triggered right away. With `redownload`, the files are queued for
+    /// download again, except those with local changes waiting for upload.
+    pub async fn sync_subtree(&self, root: &DriveItemWithFuse, redownload: bool) -> Result<SubtreeSync> {
+        let mut result = SubtreeSync::default();
+        let download_queue_repo = self.app_state.persistency().download_queue_repository();
+        let mut pending = vec![root.clone()];
+
+        while let Some(item) = pending.pop() {
+            // Not on OneDrive yet, nothing to refresh
+            if !item.is_synced() {
+                continue;
+            }
+
+            match self.app_state.onedrive().get_item_by_id(item.id()).await {
+                Ok(fresh) => {
+                    result.refreshed += 1;
+                    let change_operation = self.detect_change_operation(&fresh);
+                    if change_operation
+                        != crate::persistency::processing_item_repository::ChangeOperation::NoChange
+                    {
+                        let processing_item = ProcessingItem::new_remote(fresh, change_operation);
+                        self.processing_repo.store_processing_item(&processing_item).await?;
+                        result.changed += 1;
+                    }
+                }
+                Err(e) => warn!("⚠️ Failed to refresh {}: {}", item.id(), e),
+            }
+
+            if item.is_folder() {
+                if let Some(ino) = item.virtual_ino() {
+                    pending.extend(self.drive_item_with_fuse_repo.get_children_by_parent_ino(ino).await?);
+                }
+                continue;
+            }
+
+            if redownload {
+                let has_local_changes = self
+                    .processing_repo
+                    .get_pending_processing_item_by_drive_item_id_and_change_type(
+                        item.id(),
+                        &crate::persistency::processing_item_repository::ChangeType::Local,
+                    )
+                    .await?
+                    .is_some();
+                if has_local_changes {
+                    debug!("⏭️ Not downloading {} again: it has local changes", item.id());
+                    continue;
+                }
+                let local_path = self.app_state.file_manager().get_download_dir().join(item.id());
+                download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+                result.queued_downloads += 1;
+            }
+        }
+
+        info!(
+            "🔄 Refreshed {} items below {}: {} changed, {} queued for download",
+            result.refreshed,
+            root.name().unwrap_or("root"),
+            result.changed,
+            result.queued_downloads
+        );
+        if result.changed > 0 {
+            self.app_state.sync_trigger().trigger();
+        }
+        if result.queued_downloads > 0 {
+            self.app_state.transfers().wake();
+        }
+        Ok(result)
     }
 
     /// Detect change operation based on OneDrive delta response and existing DB state
@@ -1,2 +1,4 @@
+pub mod change_notifications;
 pub mod delta_update;
 pub mod status_broadcast;
+pub mod transfers;
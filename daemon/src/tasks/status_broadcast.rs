@@ -15,9 +15,9 @@ impl StatusBroadcastTask {
 
     async fn compute_status(&self) -> DaemonStatus {
         use onedrive_sync_lib::dbus::types::SyncStatus;
-        let is_authenticated = self.app_state.auth().get_valid_token().await.is_ok();
+        let is_authenticated = self.app_state.is_authenticated().await;
         let is_connected = matches!(
-            self.app_state.connectivity().check_connectivity().await,
+            self.app_state.connectivity_status().await,
             crate::connectivity::ConnectivityStatus::Online
         );
         let sync_status = SyncStatus::Running; //TODO Restore it 
@@ -40,7 +40,31 @@ impl StatusBroadcastTask {
             .lines()
             .any(|line| line.split_whitespace().nth(1) == Some(p.to_str().unwrap_or_default()));
 
-        DaemonStatus { is_authenticated, is_connected, sync_status, has_conflicts, is_mounted }
+        let quota = self
+            .app_state
+            .persistency()
+            .drive_quota_repository()
+            .get_quota()
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+
+        let transfer_pause_reason = crate::tasks::transfers::BulkTransferMode::current(&self.app_state)
+            .await
+            .reason();
+
+        DaemonStatus {
+            is_authenticated,
+            is_connected,
+            sync_status,
+            has_conflicts,
+            is_mounted,
+            quota_total: quota.total,
+            quota_used: quota.used,
+            quota_remaining: quota.remaining,
+            transfer_pause_reason,
+        }
     }
 
     pub async fn run(self) {
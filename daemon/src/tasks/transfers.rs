@@ -0,0 +1,656 @@
+//! Parallel downloads and uploads with progress
+//!
+//! Downloads run in their own task instead of at the end of the 30s sync
+//! cycle: `TransferScheduler` keeps up to `max_parallel_downloads` items of the
+//! download queue in flight, highest priority first. Uploads stay in the sync
+//! processor, which runs consecutive file uploads side by side (up to
+//! `max_parallel_uploads`). Every running transfer is listed in the
+//! `TransferTracker` with its progress.
+//!
+//! Transfer windows from `SyncConfig` hold back the transfers they apply to
+//! while they are closed, e.g. large uploads before 19:00. On a metered
+//! connection or on battery, `TransferPolicy` pauses or throttles them.
+
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveTime;
+use log::{debug, error, info};
+use onedrive_sync_lib::config::{ConstrainedAction, TransferPolicy, TransferWindow, TransferWindowDirection};
+use onedrive_sync_lib::notifications::{NotificationSender, NotificationUrgency};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+
+use crate::app_state::AppState;
+use crate::connectivity::NetworkConditions;
+use crate::persistency::types::PendingDownload;
+use crate::sync::text_merge;
+
+/// How often the download queue is checked when nothing wakes the scheduler up
+pub const DOWNLOAD_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Received bytes of a download are recorded in the queue every this many bytes
+const DOWNLOAD_CHECKPOINT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Interrupted downloads are resumed this many times before they count as failed
+const MAX_DOWNLOAD_RETRIES: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Download,
+    Upload,
+}
+
+impl TransferDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferDirection::Download => "Download",
+            TransferDirection::Upload => "Upload",
+        }
+    }
+}
+
+/// Parse a window time like "19:00"
+pub fn parse_window_time(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .with_context(|| format!("Invalid time '{}', expected HH:MM", time))
+}
+
+/// Check if a transfer may run at `now`: every window that applies to its direction
+/// and size has to be open. Windows with an invalid time are ignored.
+pub fn is_in_transfer_window(
+    windows: &[TransferWindow],
+    direction: TransferDirection,
+    size: u64,
+    now: NaiveTime,
+) -> bool {
+    windows
+        .iter()
+        .filter(|window| applies_to(window, direction) && size >= window.min_file_size)
+        .all(|window| window_open_at(window, now))
+}
+
+/// Smallest file size the windows hold back at `now`, `None` if they hold back nothing
+pub fn held_back_size(windows: &[TransferWindow], direction: TransferDirection, now: NaiveTime) -> Option<u64> {
+    windows
+        .iter()
+        .filter(|window| applies_to(window, direction) && !window_open_at(window, now))
+        .map(|window| window.min_file_size)
+        .min()
+}
+
+fn applies_to(window: &TransferWindow, direction: TransferDirection) -> bool {
+    match window.direction {
+        TransferWindowDirection::Both => true,
+        TransferWindowDirection::Upload => direction == TransferDirection::Upload,
+        TransferWindowDirection::Download => direction == TransferDirection::Download,
+    }
+}
+
+fn window_open_at(window: &TransferWindow, now: NaiveTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_window_time(&window.start), parse_window_time(&window.end)) else {
+        return true;
+    };
+    if start <= end {
+        // Equal times leave the window open all day
+        start == end || (start <= now && now < end)
+    } else {
+        // Runs past midnight
+        now >= start || now < end
+    }
+}
+
+/// Check if a transfer may run now, in local time
+pub fn transfer_window_open(windows: &[TransferWindow], direction: TransferDirection, size: u64) -> bool {
+    is_in_transfer_window(windows, direction, size, chrono::Local::now().time())
+}
+
+/// How background transfers may run under the current network conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkTransferMode {
+    Normal,
+    /// One download and one upload at a time
+    Throttled(&'static str),
+    /// No background transfers
+    Paused(&'static str),
+}
+
+impl BulkTransferMode {
+    /// The mode for some conditions; a pause wins over a throttle
+    pub fn for_conditions(conditions: NetworkConditions, policy: &TransferPolicy) -> Self {
+        let mut mode = BulkTransferMode::Normal;
+        for (active, action, reason) in [
+            (conditions.metered, policy.on_metered, "metered connection"),
+            (conditions.on_battery, policy.on_battery, "on battery"),
+        ] {
+            if !active {
+                continue;
+            }
+            match action {
+                ConstrainedAction::Pause => return BulkTransferMode::Paused(reason),
+                ConstrainedAction::Throttle if mode == BulkTransferMode::Normal => {
+                    mode = BulkTransferMode::Throttled(reason)
+                }
+                _ => {}
+            }
+        }
+        mode
+    }
+
+    /// The mode for the last known conditions and the current settings
+    pub async fn current(app_state: &AppState) -> Self {
+        let conditions = app_state.connectivity().network_conditions();
+        Self::for_conditions(conditions, &app_state.config().settings.read().await.transfer_policy)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self, BulkTransferMode::Paused(_))
+    }
+
+    /// Limit a number of parallel transfers
+    pub fn max_parallel(&self, configured: usize) -> usize {
+        match self {
+            BulkTransferMode::Normal => configured.max(1),
+            BulkTransferMode::Throttled(_) | BulkTransferMode::Paused(_) => 1,
+        }
+    }
+
+    /// Why transfers are held back, empty for `Normal`
+    pub fn reason(&self) -> String {
+        match self {
+            BulkTransferMode::Normal => String::new(),
+            BulkTransferMode::Throttled(reason) => format!("Throttled: {}", reason),
+            BulkTransferMode::Paused(reason) => format!("Paused: {}", reason),
+        }
+    }
+}
+
+/// A running transfer
+#[derive(Debug, Clone)]
+pub struct TransferProgress {
+    pub id: u64,
+    pub direction: TransferDirection,
+    pub drive_item_id: String,
+    pub name: String,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Downloads and uploads in flight
+#[derive(Default)]
+pub struct TransferTracker {
+    next_id: AtomicU64,
+    transfers: Mutex<BTreeMap<u64, TransferProgress>>,
+    wakeup: Notify,
+    downloads: Mutex<JoinSet<()>>,
+}
+
+impl TransferTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a transfer; it is listed until the guard is dropped
+    pub fn start(
+        self: &Arc<Self>,
+        direction: TransferDirection,
+        drive_item_id: &str,
+        name: &str,
+        bytes_total: u64,
+    ) -> TransferGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.transfers.lock().unwrap().insert(
+            id,
+            TransferProgress {
+                id,
+                direction,
+                drive_item_id: drive_item_id.to_string(),
+                name: name.to_string(),
+                bytes_done: 0,
+                bytes_total,
+            },
+        );
+        TransferGuard {
+            tracker: self.clone(),
+            id,
+        }
+    }
+
+    /// Check if an item is being transferred in a direction
+    pub fn is_active(&self, direction: TransferDirection, drive_item_id: &str) -> bool {
+        self.transfers
+            .lock()
+            .unwrap()
+            .values()
+            .any(|t| t.direction == direction && t.drive_item_id == drive_item_id)
+    }
+
+    /// Number of running transfers in a direction
+    pub fn count(&self, direction: TransferDirection) -> usize {
+        self.transfers
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|t| t.direction == direction)
+            .count()
+    }
+
+    /// Running transfers, oldest first
+    pub fn list(&self) -> Vec<TransferProgress> {
+        self.transfers.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Record the progress of a running transfer by item, for code that has no guard at hand
+    pub fn update_progress(&self, direction: TransferDirection, drive_item_id: &str, bytes_done: u64, bytes_total: u64) {
+        let mut transfers = self.transfers.lock().unwrap();
+        if let Some(transfer) = transfers
+            .values_mut()
+            .find(|t| t.direction == direction && t.drive_item_id == drive_item_id)
+        {
+            transfer.bytes_done = bytes_done;
+            transfer.bytes_total = bytes_total;
+        }
+    }
+
+    /// Let the download scheduler look at the queue right away
+    pub fn wake(&self) {
+        self.wakeup.notify_one();
+    }
+
+    /// Run a download task; it is cancelled by `cancel_downloads`
+    fn spawn_download<F>(&self, download: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let mut downloads = self.downloads.lock().unwrap();
+        // Forget downloads that already finished
+        while downloads.try_join_next().is_some() {}
+        downloads.spawn(download);
+    }
+
+    /// Cancel running downloads and wait until they stopped. They keep their .part
+    /// file and queue entry, so they resume on the next start.
+    pub async fn cancel_downloads(&self) {
+        let mut downloads = std::mem::take(&mut *self.downloads.lock().unwrap());
+        if !downloads.is_empty() {
+            info!("🛑 Cancelling {} running downloads", downloads.len());
+        }
+        downloads.abort_all();
+        while downloads.join_next().await.is_some() {}
+    }
+
+    /// Wait until `wake` is called or the timeout passes
+    pub async fn wait_for_wakeup(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.wakeup.notified()).await;
+    }
+}
+
+/// Keeps a transfer listed in the `TransferTracker` while it runs
+pub struct TransferGuard {
+    tracker: Arc<TransferTracker>,
+    id: u64,
+}
+
+impl TransferGuard {
+    /// Record how many bytes have been transferred
+    pub fn set_progress(&self, bytes_done: u64) {
+        if let Some(transfer) = self.tracker.transfers.lock().unwrap().get_mut(&self.id) {
+            transfer.bytes_done = bytes_done;
+        }
+    }
+}
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        self.tracker.transfers.lock().unwrap().remove(&self.id);
+        // A slot is free for the next queued download
+        self.tracker.wake();
+    }
+}
+
+/// Starts downloads from the download queue
+pub struct TransferScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl TransferScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    /// Start queued downloads, highest priority first, until `max_parallel_downloads`
+    /// are running. Returns the ids of the started items.
+    pub async fn start_downloads(&self) -> Result<Vec<String>> {
+        let settings = self.app_state.config().settings.read().await;
+        if settings.sync_paused {
+            return Ok(Vec::new());
+        }
+        let max_downloads = settings.transfer_config.max_parallel_downloads;
+        let windows = settings.sync_config.transfer_windows.clone();
+        drop(settings);
+
+        let mode = BulkTransferMode::current(&self.app_state).await;
+        if mode.is_paused() {
+            debug!("⏸️ Not starting downloads: {}", mode.reason());
+            return Ok(Vec::new());
+        }
+        let max_downloads = mode.max_parallel(max_downloads);
+
+        let tracker = self.app_state.transfers();
+        let free = max_downloads.saturating_sub(tracker.count(TransferDirection::Download));
+        if free == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Files the transfer windows hold back stay queued until their window opens
+        let max_size = held_back_size(&windows, TransferDirection::Download, chrono::Local::now().time());
+        // Running downloads are still pending in the queue, so skip past them
+        let limit = free + tracker.count(TransferDirection::Download);
+        let pending: Vec<_> = self
+            .app_state
+            .persistency()
+            .download_queue_repository()
+            .get_next_pending_downloads(limit, max_size)
+            .await?
+            .into_iter()
+            .filter(|download| !tracker.is_active(TransferDirection::Download, &download.drive_item_id))
+            .take(free)
+            .collect();
+
+        let mut started = Vec::new();
+        for PendingDownload { queue_id, drive_item_id, local_path, name, size } in pending {
+            let name = name.unwrap_or_else(|| "unnamed".to_string());
+            let guard = tracker.start(TransferDirection::Download, &drive_item_id, &name, size);
+            debug!("📥 Starting download of {} ({} bytes)", name, size);
+
+            let app_state = self.app_state.clone();
+            let id = drive_item_id.clone();
+            tracker.spawn_download(async move {
+                let download_queue_repo = app_state.persistency().download_queue_repository();
+                let result = match download_file(&app_state, queue_id, &id, &local_path, &guard).await {
+                    Ok(()) => download_queue_repo.mark_download_completed(queue_id).await,
+                    Err(e) => {
+                        error!("❌ Download failed for {}: {:#}", id, e);
+                        // Interrupted downloads keep their .part file and resume later
+                        match download_queue_repo.retry_download(queue_id, MAX_DOWNLOAD_RETRIES).await {
+                            Ok(true) => Ok(()),
+                            Ok(false) => {
+                                if let Ok((path, _)) = download_path(&app_state, &id, &local_path).await {
+                                    let _ = tokio::fs::remove_file(part_path(&path)).await;
+                                }
+                                download_queue_repo.mark_download_failed(queue_id, 0).await
+                            }
+                            Err(e) => Err(e),
+                        }
+                    }
+                };
+                if let Err(e) = result {
+                    error!("❌ Failed to update download queue for {}: {}", id, e);
+                }
+            });
+            started.push(drive_item_id);
+        }
+
+        if !started.is_empty() {
+            info!("📋 Started {} downloads", started.len());
+        }
+        Ok(started)
+    }
+}
+
+/// Download a file into the local folder, resuming an interrupted download
+async fn download_file(
+    app_state: &AppState,
+    queue_id: i64,
+    drive_item_id: &str,
+    local_path: &Path,
+    guard: &TransferGuard,
+) -> Result<()> {
+    // Fetch full DriveItem by ID to get download URL
+    let full_item = app_state
+        .onedrive_client
+        .get_item_by_id(drive_item_id)
+        .await
+        .context("Failed to get item by ID")?;
+
+    let download_url = full_item
+        .download_url
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No download URL available for {}", drive_item_id))?;
+    let filename = full_item.name.as_deref().unwrap_or("unnamed");
+
+    let (actual_local_path, ino) = download_path(app_state, drive_item_id, local_path).await?;
+    let part_path = part_path(&actual_local_path);
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+
+    // Resume after what an earlier attempt left in the .part file
+    let (recorded, etag) = download_queue_repo.get_download_offset(queue_id).await?;
+    let on_disk = tokio::fs::metadata(&part_path)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    // Without an ETag a changed file could not be told apart, the download starts over
+    let offset = if etag.is_some() { recorded.min(on_disk) } else { 0 };
+
+    let mut stream = app_state
+        .onedrive_client
+        .open_download(download_url, drive_item_id, offset, etag.as_deref())
+        .await
+        .context("Failed to download file")?;
+    if stream.offset != offset {
+        if stream.offset != 0 {
+            bail!(
+                "Download of {} resumed at {} instead of {}",
+                drive_item_id,
+                stream.offset,
+                offset
+            );
+        }
+        info!("🔄 {} changed since its download was interrupted, starting over", filename);
+    } else if offset > 0 {
+        info!("⏯️ Resuming download of {} at {} bytes", filename, offset);
+    }
+
+    let mut part = PartFile::open(&part_path, stream.offset).await?;
+    download_queue_repo
+        .set_download_offset(queue_id, stream.offset, stream.etag.as_deref())
+        .await?;
+
+    let mut checkpoint = part.received();
+    guard.set_progress(checkpoint);
+    loop {
+        let chunk = match stream.body.next_chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                // Keep what arrived for the next attempt
+                part.sync().await?;
+                download_queue_repo
+                    .set_download_offset(queue_id, part.received(), stream.etag.as_deref())
+                    .await?;
+                return Err(e.context(format!("Download interrupted after {} bytes", part.received())));
+            }
+        };
+        part.write(&chunk).await?;
+        guard.set_progress(part.received());
+
+        if part.received() - checkpoint >= DOWNLOAD_CHECKPOINT_BYTES {
+            // Only record bytes that are on disk, a crash must not leave a gap
+            part.sync().await?;
+            download_queue_repo
+                .set_download_offset(queue_id, part.received(), stream.etag.as_deref())
+                .await?;
+            checkpoint = part.received();
+        }
+    }
+    let received = part.received();
+    if let Err(e) = part.finish(&actual_local_path, stream.total_size).await {
+        download_queue_repo
+            .set_download_offset(queue_id, received, stream.etag.as_deref())
+            .await?;
+        return Err(e.context(format!("Download of {} incomplete", drive_item_id)));
+    }
+
+    debug!(
+        "📥 Downloaded file: {} -> {} ({} bytes)",
+        drive_item_id,
+        local_path.display(),
+        received
+    );
+
+    // The downloaded version is the base for merging later changes
+    text_merge::remember_base_from_file(
+        app_state,
+        drive_item_id,
+        full_item.ctag.as_deref(),
+        &actual_local_path,
+    )
+    .await;
+
+    // Move the file to the local folder using inode
+    if let Some(ino) = ino {
+        app_state
+            .file_manager
+            .move_downloaded_file_to_local_folder(ino)
+            .await?;
+    }
+    info!("✅ Download completed: {}", drive_item_id);
+
+    if let Ok(sender) = NotificationSender::new().await {
+        let _ = sender
+            .send_notification(
+                "Open OneDrive",
+                0,
+                "open-onedrive",
+                "Open OneDrive",
+                &format!("File {} finished downloading", filename),
+                vec![],
+                vec![("urgency", &NotificationUrgency::Normal.to_u8().to_string())],
+                5000,
+            )
+            .await;
+    }
+    Ok(())
+}
+
+/// Where a download is written: `downloads/<ino>`, or the queued path for items without an inode
+async fn download_path(
+    app_state: &AppState,
+    drive_item_id: &str,
+    local_path: &Path,
+) -> Result<(PathBuf, Option<u64>)> {
+    let drive_item_with_fuse_repo = app_state.persistency().drive_item_with_fuse_repository();
+    let ino = drive_item_with_fuse_repo
+        .get_drive_item_with_fuse(drive_item_id)
+        .await?
+        .and_then(|item| item.virtual_ino());
+    let path = match ino {
+        Some(ino) => app_state
+            .config()
+            .project_dirs
+            .data_dir()
+            .join("downloads")
+            .join(ino.to_string()),
+        None => local_path.to_path_buf(),
+    };
+    Ok((path, ino))
+}
+
+/// Download a whole file into `target` through its .part file, without resuming.
+/// Returns the number of bytes received.
+pub async fn download_to_file(
+    app_state: &AppState,
+    download_url: &str,
+    drive_item_id: &str,
+    target: &Path,
+) -> Result<u64> {
+    let mut stream = app_state
+        .onedrive_client
+        .open_download(download_url, drive_item_id, 0, None)
+        .await
+        .context("Failed to download file")?;
+    let mut part = PartFile::open(&part_path(target), 0).await?;
+    while let Some(chunk) = stream.body.next_chunk().await? {
+        part.write(&chunk).await?;
+    }
+    let received = part.received();
+    part.finish(target, stream.total_size).await?;
+    Ok(received)
+}
+
+/// A download being written to its .part file
+pub struct PartFile {
+    file: tokio::fs::File,
+    path: PathBuf,
+    received: u64,
+}
+
+impl PartFile {
+    /// Open the .part file for a body starting at `offset`; anything after it is cut off
+    pub async fn open(path: &Path, offset: u64) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .await
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.set_len(offset).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            received: offset,
+        })
+    }
+
+    /// Bytes of the file written so far, including those before the start offset
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file
+            .write_all(chunk)
+            .await
+            .with_context(|| format!("Failed to write file {}", self.path.display()))?;
+        self.received += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Make the written bytes survive a crash
+    pub async fn sync(&mut self) -> Result<()> {
+        Ok(self.file.sync_data().await?)
+    }
+
+    /// Move the file to `target` once it is complete. Fails, keeping the .part file,
+    /// if fewer than `total_size` bytes arrived.
+    pub async fn finish(self, target: &Path, total_size: Option<u64>) -> Result<()> {
+        self.file.sync_all().await?;
+        if let Some(total_size) = total_size {
+            if self.received != total_size {
+                bail!("Download ended after {} of {} bytes", self.received, total_size);
+            }
+        }
+        drop(self.file);
+        tokio::fs::rename(&self.path, target)
+            .await
+            .with_context(|| format!("Failed to move {} into place", self.path.display()))
+    }
+}
+
+/// The file a download is streamed into until it is complete
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
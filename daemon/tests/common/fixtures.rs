@@ -30,6 +30,7 @@ pub fn create_test_file_item(id: &str, name: &str, parent_id: Option<String>) ->
             id: pid,
             path: Some("/root".to_string()),
         }),
+        web_url: None,
     }
 }
 
@@ -51,6 +52,7 @@ pub fn create_test_folder_item(id: &str, name: &str, parent_id: Option<String>)
             id: pid,
             path: Some("/root".to_string()),
         }),
+        web_url: None,
     }
 }
 
@@ -0,0 +1,278 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_daemon::persistency::processing_item_repository::ChangeOperation;
+use onedrive_sync_daemon::scheduler::simple_scheduler::{SimpleTaskManager, SyncBackoff};
+use onedrive_sync_daemon::sync::SyncProcessor;
+use onedrive_sync_daemon::tasks::change_notifications::{
+    socket_io_url, ChangeNotificationStream, ChangeNotificationTransport, SocketIoTransport, LOCAL_CHANGE_DELAY,
+};
+use serial_test::serial;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::common::fixtures::create_test_local_processing_item;
+use crate::common::mock_onedrive_client::MockOneDriveClient;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_sync_backoff_while_idle() {
+    println!("\n🧪 Running test: Polling slows down while nothing changes");
+    let base = Duration::from_secs(30);
+    let mut backoff = SyncBackoff::new();
+
+    assert_eq!(backoff.next(base, false, false), Duration::from_secs(60));
+    assert_eq!(backoff.next(base, false, false), Duration::from_secs(120));
+    assert_eq!(backoff.next(base, false, false), Duration::from_secs(240));
+    // Capped at 8 times the interval
+    assert_eq!(backoff.next(base, false, false), Duration::from_secs(240));
+    // A change brings the interval back
+    assert_eq!(backoff.next(base, true, false), base);
+
+    // Change notifications let idle polling slow down further
+    for _ in 0..10 {
+        backoff.next(base, false, true);
+    }
+    assert_eq!(backoff.next(base, false, true), Duration::from_secs(15 * 60));
+}
+
+#[test]
+fn test_socket_io_url() -> Result<()> {
+    println!("\n🧪 Running test: Socket.IO notification URL to websocket URL");
+    let (url, namespace) = socket_io_url("https://f3hb0mpua.svc.ms/zbaehwg/callback?snthgk=1ff3-2345672zz831837523")?;
+    assert_eq!(
+        url,
+        "wss://f3hb0mpua.svc.ms/socket.io/?EIO=4&transport=websocket&snthgk=1ff3-2345672zz831837523"
+    );
+    assert_eq!(namespace, "/zbaehwg/callback");
+
+    let (url, namespace) = socket_io_url("http://127.0.0.1:8080/")?;
+    assert_eq!(url, "ws://127.0.0.1:8080/socket.io/?EIO=4&transport=websocket");
+    assert_eq!(namespace, "");
+
+    assert!(socket_io_url("ftp://example.com/ns").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_socket_io_transport_with_local_server() -> Result<()> {
+    println!("\n🧪 Running test: Socket.IO transport receives notifications from a local server");
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+
+    let server = tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await?;
+        let mut uri = String::new();
+        // The handshake callback's error type is tungstenite's
+        #[allow(clippy::result_large_err)]
+        let mut socket = tokio_tungstenite::accept_hdr_async(tcp, |request: &Request, response: Response| {
+            uri = request.uri().to_string();
+            Ok(response)
+        })
+        .await?;
+
+        socket
+            .send(Message::Text(r#"0{"sid":"abc","pingInterval":25000,"pingTimeout":20000}"#.into()))
+            .await?;
+        let connect = socket.next().await.unwrap()?;
+        assert_eq!(connect.to_text()?, "40/drive/callback,");
+        socket.send(Message::Text(r#"40/drive/callback,{"sid":"def"}"#.into())).await?;
+
+        // Pings are answered, other events are skipped
+        socket.send(Message::Text("2".into())).await?;
+        let pong = socket.next().await.unwrap()?;
+        assert_eq!(pong.to_text()?, "3");
+        socket.send(Message::Text(r#"42/drive/callback,["other","{}"]"#.into())).await?;
+        socket
+            .send(Message::Text(r#"42/drive/callback,["notification","{\"clientState\":null}"]"#.into()))
+            .await?;
+        socket.close(None).await?;
+        anyhow::Ok(uri)
+    });
+
+    let mock_client = MockOneDriveClient::new();
+    mock_client.set_expected_socket_io_endpoint(format!("http://127.0.0.1:{}/drive/callback?token=abc", port));
+    let transport = SocketIoTransport::new(Arc::new(mock_client));
+
+    let mut stream = transport.connect().await?;
+    stream.next_change().await?;
+    // The server closed the connection
+    assert!(stream.next_change().await.is_err());
+
+    let uri = server.await??;
+    assert_eq!(uri, "/socket.io/?EIO=4&transport=websocket&token=abc");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_socket_io_stream_fails_when_server_stops_pinging() -> Result<()> {
+    println!("\n🧪 Running test: A Socket.IO connection without pings counts as lost");
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+
+    let server = tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await?;
+        let mut socket = tokio_tungstenite::accept_async(tcp).await?;
+        socket
+            .send(Message::Text(r#"0{"sid":"abc","pingInterval":200,"pingTimeout":100}"#.into()))
+            .await?;
+        socket.next().await.unwrap()?;
+        socket.send(Message::Text("40".into())).await?;
+        // Stays connected without sending anything
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        anyhow::Ok(())
+    });
+
+    let mock_client = MockOneDriveClient::new();
+    mock_client.set_expected_socket_io_endpoint(format!("http://127.0.0.1:{}/", port));
+    let transport = SocketIoTransport::new(Arc::new(mock_client));
+
+    let mut stream = transport.connect().await?;
+    let result = tokio::time::timeout(Duration::from_secs(2), stream.next_change()).await?;
+    assert!(result.is_err());
+    server.abort();
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_only_new_local_changes_count_as_activity() -> Result<()> {
+    println!("\n🧪 Running test: Local changes retried from earlier cycles do not keep polling fast");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Q4 report (ino 8) changed locally, its upload keeps failing
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("8"), b"changed report")?;
+    let id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            report.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+    mock_client.make_operation_fail("upload_file");
+    assert_eq!(sync_processor.process_all_items().await?, 1);
+
+    // The next attempt is a retry
+    sqlx::query("UPDATE processing_items SET last_status_update = datetime('now', '-1 minute') WHERE id = ?")
+        .bind(id)
+        .execute(app_state.persistency().pool())
+        .await?;
+    assert_eq!(sync_processor.process_all_items().await?, 0);
+    assert_eq!(mock_client.if_match_tags().len(), 2);
+
+    mock_client.clear_operation_failures();
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_local_changes_trigger_sync_shortly() -> Result<()> {
+    println!("\n🧪 Running test: Changes queued through the mount pull the next sync cycle forward");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let fuse = OneDriveFuse::new(
+        app_state.persistency().pool().clone(),
+        app_state.persistency().download_queue_repository(),
+        app_state.file_manager.clone(),
+        app_state.clone(),
+    )
+    .await?;
+
+    // Q1 report (ino 5) changed twice through the mount
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let queued = tokio::time::Instant::now();
+    assert!(fuse.create_processing_item(&report, ChangeOperation::Update)?.is_some());
+    assert!(fuse.create_processing_item(&report, ChangeOperation::Update)?.is_some());
+
+    // One cycle runs shortly after, not at the next poll
+    assert!(app_state.sync_trigger().wait(Duration::from_secs(60)).await);
+    let waited = queued.elapsed();
+    assert!(waited >= LOCAL_CHANGE_DELAY && waited < LOCAL_CHANGE_DELAY * 3, "waited {:?}", waited);
+
+    // A poll that comes first is not held back
+    assert!(fuse.create_processing_item(&report, ChangeOperation::Update)?.is_some());
+    assert!(!app_state.sync_trigger().wait(Duration::from_millis(100)).await);
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+/// Transport reporting a change for every message sent on the channel
+struct FakeTransport {
+    changes: Arc<Mutex<mpsc::UnboundedReceiver<()>>>,
+}
+
+struct FakeStream {
+    changes: Arc<Mutex<mpsc::UnboundedReceiver<()>>>,
+}
+
+#[async_trait]
+impl ChangeNotificationTransport for FakeTransport {
+    async fn connect(&self) -> Result<Box<dyn ChangeNotificationStream>> {
+        Ok(Box::new(FakeStream {
+            changes: self.changes.clone(),
+        }))
+    }
+}
+
+#[async_trait]
+impl ChangeNotificationStream for FakeStream {
+    async fn next_change(&mut self) -> Result<()> {
+        self.changes
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Fake transport closed"))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_change_notifications_trigger_sync() -> Result<()> {
+    println!("\n🧪 Running test: Change notifications trigger a sync cycle");
+    let (app_state, _repo, _drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let (changes_tx, changes_rx) = mpsc::unbounded_channel();
+    let transport = Arc::new(FakeTransport {
+        changes: Arc::new(Mutex::new(changes_rx)),
+    });
+
+    let mut task_manager = SimpleTaskManager::new();
+    task_manager
+        .start_change_notification_task(app_state.clone(), transport)
+        .await?;
+
+    // Connecting triggers a catch-up sync
+    assert!(app_state.sync_trigger().wait(Duration::from_secs(5)).await);
+    assert!(app_state.sync_trigger().is_push_connected());
+    assert!(!app_state.sync_trigger().wait(Duration::from_millis(100)).await);
+
+    changes_tx.send(())?;
+    assert!(app_state.sync_trigger().wait(Duration::from_secs(5)).await);
+
+    // A lost connection falls back to polling
+    drop(changes_tx);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while app_state.sync_trigger().is_push_connected() {
+        assert!(tokio::time::Instant::now() < deadline, "push mode did not stop");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    task_manager.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::persistency::processing_item_repository::{ChangeOperation, ProcessingStatus};
+use onedrive_sync_daemon::sync::{LocalConflict, SyncProcessor};
+use serial_test::serial;
+
+use crate::common::fixtures::{create_test_local_processing_item, create_test_remote_processing_item};
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_local_changes_send_stored_etag() -> Result<()> {
+    println!("\n🧪 Running test: Local changes are sent with the eTag of the last sync");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Q4 report (ino 8) changed locally
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    let stored_etag = report.etag().map(str::to_string);
+    assert!(stored_etag.is_some());
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("8"), b"changed report")?;
+    repo.store_processing_item(&create_test_local_processing_item(
+        report.drive_item().clone(),
+        ChangeOperation::Update,
+    ))
+    .await?;
+    sync_processor.process_all_items().await?;
+    assert_eq!(
+        mock_client.if_match_tags(),
+        vec![("update_file_smart".to_string(), stored_etag)]
+    );
+
+    // Q3 report (ino 7) deleted locally
+    let deleted = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    repo.store_processing_item(&create_test_local_processing_item(
+        deleted.drive_item().clone(),
+        ChangeOperation::Delete,
+    ))
+    .await?;
+    sync_processor.process_all_items().await?;
+    assert_eq!(
+        mock_client.if_match_tags().last(),
+        Some(&("delete_item".to_string(), deleted.etag().map(str::to_string)))
+    );
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_remote_move_sends_stored_etag() -> Result<()> {
+    println!("\n🧪 Running test: A remote move is sent with the eTag of the last sync");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Q1 report (ino 5) moved on OneDrive
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let mut moved = report.drive_item().clone();
+    moved.etag = Some("remote_etag".to_string());
+    repo.store_processing_item(&create_test_remote_processing_item(moved, ChangeOperation::Move))
+        .await?;
+    sync_processor.process_all_items().await?;
+
+    assert_eq!(
+        mock_client.if_match_tags(),
+        vec![("move_item".to_string(), report.etag().map(str::to_string))]
+    );
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_rejected_precondition_becomes_local_conflict() -> Result<()> {
+    println!("\n🧪 Running test: A change refused with 412 lands in the conflict queue");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Q2 report (ino 6) renamed locally while it changed on OneDrive
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(6)
+        .await?
+        .unwrap();
+    let mut renamed = report.drive_item().clone();
+    renamed.name = Some("Q2_Report_final.pdf".to_string());
+    let item_id = repo
+        .store_processing_item(&create_test_local_processing_item(renamed, ChangeOperation::Rename))
+        .await?;
+    mock_client.make_operation_fail_with_status("rename_item", reqwest::StatusCode::PRECONDITION_FAILED);
+    sync_processor.process_all_items().await?;
+
+    assert_eq!(
+        mock_client.if_match_tags(),
+        vec![("rename_item".to_string(), report.etag().map(str::to_string))]
+    );
+    let item = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    assert_eq!(item.status, ProcessingStatus::Conflicted);
+    assert_eq!(
+        item.validation_errors,
+        vec![LocalConflict::RenameOrMoveOfModified.to_string()]
+    );
+
+    mock_client.clear_operation_failures();
+    repo.clear_all_items().await?;
+    Ok(())
+}
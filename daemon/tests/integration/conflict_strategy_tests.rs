@@ -0,0 +1,353 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::onedrive_service::onedrive_models::ParentReference;
+use onedrive_sync_daemon::persistency::processing_item_repository::{
+    ChangeOperation, ChangeType, ProcessingStatus,
+};
+use onedrive_sync_daemon::sync::conflict_resolution::{conflict_name, ConflictResolver};
+use onedrive_sync_daemon::sync::SyncProcessor;
+use onedrive_sync_lib::config::{ConflictResolutionStrategy, Settings};
+use onedrive_sync_lib::dbus::types::UserChoice;
+use serial_test::serial;
+
+use crate::common::fixtures::{
+    create_test_local_processing_item, create_test_remote_processing_item,
+};
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_conflict_strategy_follows_folder_overrides() {
+    println!("\n🧪 Running test: Conflict strategy follows folder overrides");
+    let mut settings = Settings {
+        conflict_resolution_strategy: ConflictResolutionStrategy::NewestWins,
+        ..Default::default()
+    };
+    settings
+        .conflict_resolution_overrides
+        .insert("Documents".to_string(), ConflictResolutionStrategy::KeepBoth);
+    settings
+        .conflict_resolution_overrides
+        .insert("Documents/Work".to_string(), ConflictResolutionStrategy::RemoteWins);
+
+    assert_eq!(settings.conflict_strategy_for("/Documents"), ConflictResolutionStrategy::KeepBoth);
+    assert_eq!(settings.conflict_strategy_for("/Documents/Personal"), ConflictResolutionStrategy::KeepBoth);
+    assert_eq!(settings.conflict_strategy_for("/Documents/Work/Reports"), ConflictResolutionStrategy::RemoteWins);
+    // Folder names are case-insensitive on OneDrive
+    assert_eq!(settings.conflict_strategy_for("/documents/WORK"), ConflictResolutionStrategy::RemoteWins);
+    assert_eq!(settings.conflict_strategy_for("/DOCUMENTS/Personal"), ConflictResolutionStrategy::KeepBoth);
+    // Only whole folder names match
+    assert_eq!(settings.conflict_strategy_for("/DocumentsOld"), ConflictResolutionStrategy::NewestWins);
+    assert_eq!(settings.conflict_strategy_for("/Pictures"), ConflictResolutionStrategy::NewestWins);
+
+    assert_eq!(conflict_name("Q1_Report.pdf", "laptop 2024-05-01"), "Q1_Report (conflict laptop 2024-05-01).pdf");
+    assert_eq!(conflict_name("archive.tar.gz", "pc 2024-05-01"), "archive.tar (conflict pc 2024-05-01).gz");
+    assert_eq!(conflict_name(".bashrc", "pc 2024-05-01"), ".bashrc (conflict pc 2024-05-01)");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_keep_both_strategy_saves_local_version_as_copy() -> Result<()> {
+    println!("\n🧪 Running test: KeepBoth saves the local version as a conflict copy");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    app_state.config().settings.write().await.conflict_resolution_strategy =
+        ConflictResolutionStrategy::KeepBoth;
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("5"), "local content")?;
+
+    // Both sides changed the file: the remote update conflicts with the pending local one
+    let mut local_modified = original.drive_item().clone();
+    local_modified.etag = Some("local-etag".to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_modified, ChangeOperation::Update))
+        .await?;
+    let mut remote_modified = original.drive_item().clone();
+    remote_modified.etag = Some("remote-etag".to_string());
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(remote_modified, ChangeOperation::Update))
+        .await?;
+
+    let local_item = repo.get_processing_item_by_id(local_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&local_item)
+        .await?;
+
+    // The local change is replaced by the upload of a conflict copy, the remote one goes through
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::Cancelled);
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::New);
+
+    let copy_create = repo
+        .get_all_processing_items()
+        .await?
+        .into_iter()
+        .find(|item| item.change_type == ChangeType::Local && item.change_operation == ChangeOperation::Create)
+        .expect("conflict copy is queued for upload");
+    let copy_name = copy_create.drive_item.name.clone().unwrap();
+    assert!(copy_name.starts_with("Q1_Report (conflict "), "{}", copy_name);
+    assert!(copy_name.ends_with(").pdf"), "{}", copy_name);
+
+    let copy = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse(&copy_create.drive_item.id)
+        .await?
+        .unwrap();
+    assert_eq!(copy.parent_ino(), original.parent_ino());
+    let copy_ino = copy.virtual_ino().unwrap();
+    assert_eq!(std::fs::read_to_string(local_dir.join(copy_ino.to_string()))?, "local content");
+    assert!(!local_dir.join("5").exists());
+
+    app_state.config().settings.write().await.conflict_resolution_strategy =
+        ConflictResolutionStrategy::Manual;
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+/// Store a local and a remote update of the Q1 report (ino 5) modified at the given times,
+/// process the local one and return the statuses of (local, remote) afterwards
+async fn resolve_update_conflict(
+    strategy: ConflictResolutionStrategy,
+    local_modified_at: &str,
+    remote_modified_at: &str,
+) -> Result<(ProcessingStatus, ProcessingStatus)> {
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    app_state.config().settings.write().await.conflict_resolution_strategy = strategy;
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("5"), "local content")?;
+
+    let mut local_modified = original.drive_item().clone();
+    local_modified.etag = Some("local-etag".to_string());
+    local_modified.last_modified = Some(local_modified_at.to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_modified, ChangeOperation::Update))
+        .await?;
+    let mut remote_modified = original.drive_item().clone();
+    remote_modified.etag = Some("remote-etag".to_string());
+    remote_modified.last_modified = Some(remote_modified_at.to_string());
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(remote_modified, ChangeOperation::Update))
+        .await?;
+
+    let local_item = repo.get_processing_item_by_id(local_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&local_item)
+        .await?;
+
+    let statuses = (
+        repo.get_processing_item_by_id(local_id).await?.unwrap().status,
+        repo.get_processing_item_by_id(remote_id).await?.unwrap().status,
+    );
+    app_state.config().settings.write().await.conflict_resolution_strategy =
+        ConflictResolutionStrategy::Manual;
+    repo.clear_all_items().await?;
+    Ok(statuses)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_newest_wins_strategy_keeps_latest_change() -> Result<()> {
+    println!("\n🧪 Running test: NewestWins keeps the side that changed last");
+
+    let (local, remote) = resolve_update_conflict(
+        ConflictResolutionStrategy::NewestWins,
+        "2024-05-02T10:00:00Z",
+        "2024-05-01T10:00:00Z",
+    )
+    .await?;
+    assert_eq!(local, ProcessingStatus::New);
+    assert_eq!(remote, ProcessingStatus::Cancelled);
+
+    let (local, remote) = resolve_update_conflict(
+        ConflictResolutionStrategy::NewestWins,
+        "2024-05-01T10:00:00Z",
+        "2024-05-02T10:00:00Z",
+    )
+    .await?;
+    assert_eq!(local, ProcessingStatus::Cancelled);
+    assert_eq!(remote, ProcessingStatus::New);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_local_wins_strategy_keeps_local_change() -> Result<()> {
+    println!("\n🧪 Running test: LocalWins keeps the local change even when the remote one is newer");
+
+    let (local, remote) = resolve_update_conflict(
+        ConflictResolutionStrategy::LocalWins,
+        "2024-05-01T10:00:00Z",
+        "2024-05-02T10:00:00Z",
+    )
+    .await?;
+    assert_eq!(local, ProcessingStatus::New);
+    assert_eq!(remote, ProcessingStatus::Cancelled);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_folder_override_resolves_delete_conflict() -> Result<()> {
+    println!("\n🧪 Running test: Folder override resolves a delete conflict");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    app_state
+        .config()
+        .settings
+        .write()
+        .await
+        .conflict_resolution_overrides
+        .insert("Documents/Work".to_string(), ConflictResolutionStrategy::RemoteWins);
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let mut drive_item = original.drive_item().clone();
+    drive_item.parent_reference = Some(ParentReference {
+        id: drive_item.parent_reference.unwrap().id,
+        path: Some("/drive/root:/Documents/Work/Reports".to_string()),
+    });
+
+    // Remote deletion of a file that was modified locally
+    let mut local_modified = drive_item.clone();
+    local_modified.etag = Some("local-etag".to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_modified, ChangeOperation::Update))
+        .await?;
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(drive_item, ChangeOperation::Delete))
+        .await?;
+
+    let remote_item = repo.get_processing_item_by_id(remote_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&remote_item)
+        .await?;
+
+    // The folder override keeps the remote deletion instead of waiting for the user
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::Cancelled);
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::New);
+
+    app_state.config().settings.write().await.conflict_resolution_overrides.clear();
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_user_choice_keep_both_shows_both_files() -> Result<()> {
+    println!("\n🧪 Running test: Resolving with KeepBoth keeps both files on the mount");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(6)
+        .await?
+        .unwrap();
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("6"), "local edit")?;
+
+    let mut local_modified = original.drive_item().clone();
+    local_modified.etag = Some("local-etag".to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_modified, ChangeOperation::Update))
+        .await?;
+    let mut remote_modified = original.drive_item().clone();
+    remote_modified.etag = Some("remote-etag".to_string());
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(remote_modified, ChangeOperation::Update))
+        .await?;
+    repo.update_status_by_id(remote_id, &ProcessingStatus::Conflicted).await?;
+
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+    dbus_service
+        .resolve_conflict_for_test(remote_id, UserChoice::KeepBoth)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus resolve_conflict failed: {}", e))?;
+
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::Cancelled);
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::New);
+
+    // Both files are listed in the folder: the original and the local conflict copy
+    let parent_ino = original.parent_ino().unwrap();
+    let children: Vec<String> = drive_items_with_fuse_repo
+        .get_children_by_parent_ino(parent_ino)
+        .await?
+        .iter()
+        .filter_map(|child| child.name().map(str::to_string))
+        .collect();
+    assert!(children.contains(&"Q2_Report.pdf".to_string()));
+    let copy_name = children
+        .iter()
+        .find(|name| name.starts_with("Q2_Report (conflict "))
+        .expect("conflict copy is listed");
+    let copy = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(parent_ino, copy_name)
+        .await?
+        .unwrap();
+    assert!(copy.id().starts_with("local_"));
+    let copy_ino = copy.virtual_ino().unwrap();
+    assert_eq!(std::fs::read_to_string(local_dir.join(copy_ino.to_string()))?, "local edit");
+
+    // The remote version is fetched for the original name
+    let downloads = app_state
+        .persistency()
+        .download_queue_repository()
+        .get_pending_downloads()
+        .await?;
+    assert!(downloads.iter().any(|(_, id, _)| id == original.id()));
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_keep_both_without_local_file_fails() -> Result<()> {
+    println!("\n🧪 Running test: KeepBoth without the local version queues no empty copy");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    let _ = std::fs::remove_file(app_state.file_manager().get_local_dir().join("7"));
+
+    let mut local_modified = original.drive_item().clone();
+    local_modified.etag = Some("local-etag".to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_modified, ChangeOperation::Update))
+        .await?;
+    let mut remote_modified = original.drive_item().clone();
+    remote_modified.etag = Some("remote-etag".to_string());
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(remote_modified, ChangeOperation::Update))
+        .await?;
+    repo.update_status_by_id(remote_id, &ProcessingStatus::Conflicted).await?;
+
+    let result = ConflictResolver::new(app_state.clone())
+        .resolve(remote_id, UserChoice::KeepBoth)
+        .await;
+    assert!(result.is_err());
+
+    // Nothing changed: no copy, both changes still waiting
+    let children = drive_items_with_fuse_repo
+        .get_children_by_parent_ino(original.parent_ino().unwrap())
+        .await?;
+    assert!(!children
+        .iter()
+        .any(|child| child.name().is_some_and(|name| name.contains("(conflict "))));
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::New);
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::Conflicted);
+    assert_eq!(repo.get_all_processing_items().await?.len(), 2);
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
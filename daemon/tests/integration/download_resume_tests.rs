@@ -0,0 +1,173 @@
+use anyhow::Result;
+use onedrive_sync_daemon::app_state::AppState;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::onedrive_service::onedrive_models::DownloadResult;
+use onedrive_sync_daemon::tasks::transfers::{download_to_file, part_path, TransferScheduler};
+use serial_test::serial;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+const CONTENT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn download_result(id: &str, etag: &str) -> DownloadResult {
+    DownloadResult {
+        file_data: CONTENT.to_vec(),
+        file_name: "Q1_Report.pdf".to_string(),
+        onedrive_id: id.to_string(),
+        etag: Some(etag.to_string()),
+        mime_type: Some("application/pdf".to_string()),
+        size: Some(CONTENT.len() as u64),
+        last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+    }
+}
+
+/// Start queued downloads and wait until none is running
+async fn run_downloads(app_state: &Arc<AppState>) -> Result<()> {
+    let scheduler = TransferScheduler::new(app_state.clone());
+    scheduler.start_downloads().await?;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while !app_state.transfers().list().is_empty() {
+        assert!(tokio::time::Instant::now() < deadline, "download did not finish");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_interrupted_download_resumes_from_part_file() -> Result<()> {
+    println!("\n🧪 Running test: Interrupted download resumes from its .part file");
+    let (app_state, _repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Q1 report (ino 5)
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    mock_client.set_expected_download_result(item.id().to_string(), download_result(item.id(), "v1"));
+    mock_client.interrupt_next_download_after(10);
+    let local_path = app_state.file_manager().get_download_dir().join(item.id());
+    download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+
+    run_downloads(&app_state).await?;
+
+    // The received part is kept and the download queued again
+    let part = part_path(&app_state.file_manager().get_download_dir().join("5"));
+    assert_eq!(std::fs::read(&part)?, &CONTENT[..10]);
+    let queued = download_queue_repo.get_all_items().await?;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].status, "pending");
+    assert_eq!(queued[0].retry_count, 1);
+    assert_eq!(
+        download_queue_repo.get_download_offset(queued[0].id).await?,
+        (10, Some("v1".to_string()))
+    );
+    // Retries wait before they start again
+    assert!(download_queue_repo.get_pending_downloads().await?.is_empty());
+
+    sqlx::query("UPDATE download_queue SET updated_at = datetime('now', '-1 hour')")
+        .execute(app_state.persistency().pool())
+        .await?;
+    run_downloads(&app_state).await?;
+
+    assert_eq!(mock_client.download_offsets(), vec![0, 10]);
+    assert_eq!(std::fs::read(app_state.file_manager().get_local_dir().join("5"))?, CONTENT);
+    assert!(!part.exists());
+    assert_eq!(download_queue_repo.get_all_items().await?[0].status, "completed");
+
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_changed_file_download_starts_over() -> Result<()> {
+    println!("\n🧪 Running test: Download of a file changed since the interruption starts over");
+    let (app_state, _repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Q2 report (ino 6), left half downloaded in an older version, e.g. before a restart
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(6)
+        .await?
+        .unwrap();
+    mock_client.set_expected_download_result(item.id().to_string(), download_result(item.id(), "v2"));
+    let local_path = app_state.file_manager().get_download_dir().join(item.id());
+    download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+    let queue_id = download_queue_repo.get_all_items().await?[0].id;
+    download_queue_repo.set_download_offset(queue_id, 8, Some("v1")).await?;
+    let part = part_path(&app_state.file_manager().get_download_dir().join("6"));
+    std::fs::write(&part, b"XXXXXXXX")?;
+
+    run_downloads(&app_state).await?;
+
+    assert_eq!(mock_client.download_offsets(), vec![8]);
+    assert_eq!(std::fs::read(app_state.file_manager().get_local_dir().join("6"))?, CONTENT);
+    assert!(!part.exists());
+    assert_eq!(download_queue_repo.get_all_items().await?[0].status, "completed");
+
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_download_without_etag_starts_over() -> Result<()> {
+    println!("\n🧪 Running test: A download without a recorded eTag is not resumed");
+    let (app_state, _repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Q3 report (ino 7) with received bytes but no eTag to check them against
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    mock_client.set_expected_download_result(item.id().to_string(), download_result(item.id(), "v1"));
+    let local_path = app_state.file_manager().get_download_dir().join(item.id());
+    download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+    let queue_id = download_queue_repo.get_all_items().await?[0].id;
+    download_queue_repo.set_download_offset(queue_id, 8, None).await?;
+    let part = part_path(&app_state.file_manager().get_download_dir().join("7"));
+    std::fs::create_dir_all(app_state.file_manager().get_download_dir())?;
+    std::fs::write(&part, [b'X'; 64])?;
+
+    run_downloads(&app_state).await?;
+
+    // The longer stale .part file was cut, not patched
+    assert_eq!(mock_client.download_offsets(), vec![0]);
+    assert_eq!(std::fs::read(app_state.file_manager().get_local_dir().join("7"))?, CONTENT);
+    assert!(!part.exists());
+
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_download_to_file_streams_through_part_file() -> Result<()> {
+    println!("\n🧪 Running test: A whole file download is streamed through a .part file");
+    let (app_state, _repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+
+    // Q4 report (ino 8)
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    mock_client.set_expected_download_result(item.id().to_string(), download_result(item.id(), "v1"));
+    let target = app_state.file_manager().get_local_dir().join("8");
+    std::fs::create_dir_all(app_state.file_manager().get_local_dir())?;
+    std::fs::write(part_path(&target), b"left over from a crash, longer than the file")?;
+
+    let received = download_to_file(&app_state, "https://example.com/q4", item.id(), &target).await?;
+
+    assert_eq!(received, CONTENT.len() as u64);
+    assert_eq!(std::fs::read(&target)?, CONTENT);
+    assert!(!part_path(&target).exists());
+    Ok(())
+}
@@ -56,6 +56,7 @@ async fn test_injectable_drive_item() -> Result<()> {
         deleted: None,
         parent_reference: None,
         ctag: None,
+        web_url: None,
     };
     
     mock_client.set_expected_drive_item("test_file_123".to_string(), custom_item.clone());
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::file_handles::FileHandleManager;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_daemon::persistency::processing_item_repository::{
+    ChangeOperation, ProcessingStatus,
+};
+use serial_test::serial;
+use std::time::Duration;
+
+use crate::common::fixtures::create_test_local_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_upload_is_needed_on_final_release_of_dirty_handle() -> Result<()> {
+    println!("\n🧪 Running test: Upload is needed on final release of a dirty handle");
+    let handles = FileHandleManager::new();
+
+    let reader = handles.register_file(5, tempfile::tempfile()?);
+    let writer = handles.register_file(5, tempfile::tempfile()?);
+    let other = handles.register_file(6, tempfile::tempfile()?);
+    handles.mark_dirty(writer);
+
+    // The writer closes first: the reader still has the file open
+    let closed = handles.close_file(writer).unwrap();
+    assert_eq!(closed.ino, 5);
+    assert!(!closed.upload_needed);
+
+    // Clean handles of other files don't queue anything
+    assert!(!handles.close_file(other).unwrap().upload_needed);
+
+    // The last handle carries the pending change
+    assert!(handles.close_file(reader).unwrap().upload_needed);
+    assert!(handles.close_file(reader).is_none());
+
+    // fsync takes the dirty state, so the release doesn't upload again
+    let fh = handles.register_file(7, tempfile::tempfile()?);
+    handles.mark_dirty(fh);
+    assert!(handles.take_dirty(7));
+    assert!(!handles.take_dirty(7));
+    assert!(!handles.close_file(fh).unwrap().upload_needed);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_wait_for_upload_follows_processing_status() -> Result<()> {
+    println!("\n🧪 Running test: Waiting for an upload follows the processing status");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let processing_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            item.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+
+    let waiter = tokio::spawn(OneDriveFuse::wait_for_upload(
+        app_state.clone(),
+        5,
+        processing_id,
+        Duration::from_secs(10),
+    ));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!waiter.is_finished());
+
+    repo.update_status_by_id(processing_id, &ProcessingStatus::Done)
+        .await?;
+    waiter.await??;
+
+    // A failed upload is reported
+    let processing_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            item.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+    repo.update_status_by_id(processing_id, &ProcessingStatus::Error)
+        .await?;
+    assert!(
+        OneDriveFuse::wait_for_upload(app_state.clone(), 5, processing_id, Duration::from_secs(10))
+            .await
+            .is_err()
+    );
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
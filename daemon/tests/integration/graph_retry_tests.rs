@@ -0,0 +1,175 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::onedrive_service::http_client::{graph_error, GraphError, HttpClient, RetryPolicy};
+use onedrive_sync_daemon::persistency::processing_item_repository::{ChangeOperation, ProcessingStatus};
+use onedrive_sync_daemon::sync::{LocalConflict, SyncProcessor};
+use serial_test::serial;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::common::fixtures::create_test_local_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+/// Serve the given status lines and extra headers in order, repeating the last one.
+/// Request bodies are not read, so they should not contain line breaks.
+/// Returns the server URL and the number of requests received.
+async fn serve_responses(responses: Vec<(&'static str, &'static str)>) -> Result<(String, Arc<AtomicUsize>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/me/drive", listener.local_addr()?);
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let counter = counter.clone();
+            let responses = responses.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                loop {
+                    // Requests without body: read up to the empty line
+                    let mut line = String::new();
+                    loop {
+                        line.clear();
+                        if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let index = counter.fetch_add(1, Ordering::SeqCst).min(responses.len() - 1);
+                    let (status, headers) = responses[index];
+                    let body = r#"{"error":{"code":"mock","message":"mock message"}}"#;
+                    let body = if status.starts_with("200") { r#"{"ok":true}"# } else { body };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        status,
+                        headers,
+                        body.len(),
+                        body
+                    );
+                    if stream.get_mut().write_all(response.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    Ok((url, requests))
+}
+
+fn test_client() -> HttpClient {
+    HttpClient::with_retry_policy(RetryPolicy {
+        max_retries: 2,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        max_retry_after: Duration::from_millis(50),
+    })
+}
+
+#[tokio::test]
+async fn test_requests_retry_throttling_and_server_errors() -> Result<()> {
+    println!("\n🧪 Running test: Throttled and failed Graph requests are retried");
+    let (url, requests) = serve_responses(vec![
+        ("429 Too Many Requests", "Retry-After: 0\r\n"),
+        ("503 Service Unavailable", ""),
+        ("200 OK", ""),
+    ])
+    .await?;
+    let value: serde_json::Value = test_client().get(&url, "Bearer token").await?;
+    assert_eq!(value["ok"], true);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // Throttled beyond the retries
+    let (url, requests) = serve_responses(vec![("429 Too Many Requests", "Retry-After: 0\r\n")]).await?;
+    let error = test_client()
+        .get::<serde_json::Value>(&url, "Bearer token")
+        .await
+        .unwrap_err();
+    assert!(matches!(graph_error(&error), Some(GraphError::Throttled { .. })));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // Other errors are not retried and keep the Graph message
+    let (url, requests) = serve_responses(vec![("412 Precondition Failed", "")]).await?;
+    let error = test_client()
+        .get::<serde_json::Value>(&url, "Bearer token")
+        .await
+        .unwrap_err();
+    match graph_error(&error) {
+        Some(GraphError::PreconditionFailed(message)) => assert_eq!(message, "mock message"),
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_post_requests_retry_only_when_turned_away() -> Result<()> {
+    println!("\n🧪 Running test: POST requests are retried when throttled but not on server errors");
+    let body = serde_json::json!({});
+    let (url, requests) = serve_responses(vec![
+        ("429 Too Many Requests", "Retry-After: 0\r\n"),
+        ("200 OK", ""),
+    ])
+    .await?;
+    let value: serde_json::Value = test_client().post(&url, &body, "Bearer token").await?;
+    assert_eq!(value["ok"], true);
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // The server may have applied it before failing
+    let (url, requests) = serve_responses(vec![("502 Bad Gateway", ""), ("200 OK", "")]).await?;
+    let error = test_client()
+        .post::<serde_json::Value, _>(&url, &body, "Bearer token")
+        .await
+        .unwrap_err();
+    assert!(matches!(graph_error(&error), Some(GraphError::Status { .. })));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_sync_processor_branches_on_graph_errors() -> Result<()> {
+    println!("\n🧪 Running test: Throttled uploads wait, rejected preconditions become conflicts");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+
+    // Q4 report (ino 8) changed locally
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    mock_client.set_expected_drive_item(original.id().to_string(), original.drive_item().clone());
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("8"), b"changed report")?;
+    let item_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            original.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Throttling postpones the item without marking it failed
+    mock_client.make_operation_fail_with_status("upload_file", reqwest::StatusCode::TOO_MANY_REQUESTS);
+    sync_processor.process_all_items().await?;
+    assert_eq!(mock_client.get_call_count("upload_file"), 1);
+    assert_eq!(repo.get_processing_item_by_id(item_id).await?.unwrap().status, ProcessingStatus::New);
+
+    // A changed item on OneDrive makes it a conflict
+    mock_client.clear_operation_failures();
+    mock_client.make_operation_fail_with_status("upload_file", reqwest::StatusCode::PRECONDITION_FAILED);
+    sqlx::query("UPDATE processing_items SET last_status_update = datetime('now', '-1 minute')")
+        .execute(app_state.persistency().pool())
+        .await?;
+    sync_processor.process_all_items().await?;
+    let item = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    assert_eq!(item.status, ProcessingStatus::Conflicted);
+    assert_eq!(item.validation_errors, vec![LocalConflict::ModifyOnModified.to_string()]);
+
+    mock_client.clear_operation_failures();
+    repo.clear_all_items().await?;
+    Ok(())
+}
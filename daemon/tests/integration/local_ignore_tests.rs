@@ -0,0 +1,110 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_daemon::persistency::processing_item_repository::ChangeOperation;
+use onedrive_sync_daemon::sync::selective_sync::SyncRuleSet;
+use onedrive_sync_lib::config::{LocalIgnore, SelectiveSync, Settings};
+use serial_test::serial;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_local_ignore_defaults_match_temp_files() {
+    println!("\n🧪 Running test: Local ignore defaults match editor temp files");
+    let rules = SyncRuleSet::from_settings(&Settings::default());
+    for path in [
+        "Documents/.notes.txt.swp",
+        "Documents/.~lock.budget.ods#",
+        "Documents/~$report.docx",
+        "Downloads/movie.mkv.part",
+        "Documents/.goutputstream-4XK2Q1",
+        "Documents/notes.txt~",
+        "Documents/4913",
+    ] {
+        assert!(rules.is_locally_ignored(path), "{} should be ignored", path);
+    }
+    for path in ["Documents/notes.txt", "Documents/swp.txt", "Documents/report.part.docx"] {
+        assert!(!rules.is_locally_ignored(path), "{} should not be ignored", path);
+    }
+
+    // Own patterns, with or without the defaults
+    let settings = Settings {
+        local_ignore: LocalIgnore {
+            use_defaults: false,
+            patterns: vec!["*.bak".to_string(), "Scratch/**".to_string()],
+        },
+        ..Default::default()
+    };
+    let rules = SyncRuleSet::from_settings(&settings);
+    assert!(rules.is_locally_ignored("Documents/old.bak"));
+    assert!(rules.is_locally_ignored("Scratch/draft.txt"));
+    assert!(!rules.is_locally_ignored("Documents/.notes.txt.swp"));
+
+    // Rules built for selective sync only ignore nothing
+    let rules = SyncRuleSet::new(&[], &SelectiveSync::default()).unwrap();
+    assert!(!rules.is_locally_ignored("Documents/.notes.txt.swp"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_ignored_files_are_not_queued_for_upload() -> Result<()> {
+    println!("\n🧪 Running test: Ignored files created through the mount are not uploaded");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let fuse = OneDriveFuse::new(
+        app_state.persistency().pool().clone(),
+        app_state.persistency().download_queue_repository(),
+        app_state.file_manager.clone(),
+        app_state.clone(),
+    )
+    .await?;
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+
+    // A swap file created next to Q1_Report.pdf (in Reports, ino 4)
+    let ino = fuse
+        .database()
+        .apply_local_change_to_db_repository("create", 4, ".Q1_Report.pdf.swp", false)
+        .await?;
+    let swap_item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(ino)
+        .await?
+        .unwrap();
+    assert!(fuse.is_local_only(&swap_item));
+    for operation in [ChangeOperation::Create, ChangeOperation::Update, ChangeOperation::Delete] {
+        assert_eq!(fuse.create_processing_item(&swap_item, operation)?, None);
+    }
+    assert_eq!(fuse.queue_upload(ino)?, None);
+    assert!(repo.get_all_processing_items().await?.is_empty());
+
+    let ignored = dbus_service
+        .get_ignored_items_for_test()
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus get_ignored_items failed: {}", e))?;
+    assert_eq!(ignored.len(), 1);
+    assert_eq!(ignored[0].name, ".Q1_Report.pdf.swp");
+    assert_eq!(ignored[0].ino, ino);
+
+    // Items already on OneDrive keep syncing even with an ignored name
+    let mut remote_item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    remote_item.drive_item_mut().set_name("Q1_Report.pdf.part".to_string());
+    assert!(!fuse.is_local_only(&remote_item));
+    assert!(fuse.create_processing_item(&remote_item, ChangeOperation::Update)?.is_some());
+
+    // Renamed to a regular name, the file is uploaded
+    fuse.rename_item_in_db(&swap_item, 4, "Q1_Notes.txt")?;
+    let renamed = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(ino)
+        .await?
+        .unwrap();
+    assert!(!fuse.is_local_only(&renamed));
+    assert!(fuse.create_processing_item(&renamed, ChangeOperation::Create)?.is_some());
+    assert!(dbus_service
+        .get_ignored_items_for_test()
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus get_ignored_items failed: {}", e))?
+        .is_empty());
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
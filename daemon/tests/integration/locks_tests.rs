@@ -0,0 +1,134 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::locks::FileLockManager;
+use onedrive_sync_daemon::persistency::processing_item_repository::{
+    ChangeOperation, ProcessingStatus,
+};
+use onedrive_sync_daemon::sync::SyncProcessor;
+use serial_test::serial;
+
+use crate::common::fixtures::create_test_remote_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_lock_table_conflicts_and_release() {
+    println!("\n🧪 Running test: Lock table conflicts and release");
+    let locks = FileLockManager::new();
+
+    // Shared read locks from two owners
+    assert!(locks.set_lock(5, 1, 100, 0, 99, libc::F_RDLCK).is_ok());
+    assert!(locks.set_lock(5, 2, 200, 50, 149, libc::F_RDLCK).is_ok());
+    assert!(!locks.has_write_lock(5));
+
+    // A write lock over a read-locked range of another owner conflicts
+    let conflict = locks.set_lock(5, 2, 200, 0, 10, libc::F_WRLCK).unwrap_err();
+    assert_eq!(conflict.owner, 1);
+    assert_eq!(locks.test_lock(5, 2, 0, 10, libc::F_WRLCK).map(|l| l.pid), Some(100));
+
+    // Unlocking the middle of a range keeps both ends
+    locks.set_lock(5, 1, 100, 20, 79, libc::F_UNLCK).unwrap();
+    assert!(locks.test_lock(5, 2, 20, 49, libc::F_WRLCK).is_none());
+    assert!(locks.test_lock(5, 2, 0, 19, libc::F_WRLCK).is_some());
+    assert!(locks.test_lock(5, 3, 80, 80, libc::F_WRLCK).is_some());
+
+    // Whole-file lock as sent for flock()
+    locks.release_owner(5, 1);
+    locks.release_owner(5, 2);
+    assert!(locks.set_lock(5, 3, 300, 0, i64::MAX as u64, libc::F_WRLCK).is_ok());
+    assert!(locks.has_write_lock(5));
+    assert!(locks.set_lock(5, 4, 400, 0, 0, libc::F_RDLCK).is_err());
+    locks.release_owner(5, 3);
+    assert!(!locks.has_write_lock(5));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_blocking_lock_waits_for_release() {
+    println!("\n🧪 Running test: Blocking lock waits for release");
+    let locks = std::sync::Arc::new(FileLockManager::new());
+    locks.set_lock(7, 1, 100, 0, 0, libc::F_WRLCK).unwrap();
+
+    let waiter = {
+        let locks = locks.clone();
+        tokio::spawn(async move { locks.wait_for_lock(7, 2, 200, 0, 0, libc::F_WRLCK).await })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!waiter.is_finished());
+
+    locks.release_owner(7, 1);
+    assert!(waiter.await.unwrap());
+    assert_eq!(locks.test_lock(7, 1, 0, 0, libc::F_RDLCK).map(|l| l.owner), Some(2));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_blocking_lock_wakes_on_split_and_downgrade() {
+    println!("\n🧪 Running test: Blocking lock wakes up when the holder splits or downgrades its lock");
+    let locks = std::sync::Arc::new(FileLockManager::new());
+    locks.set_lock(7, 1, 100, 0, 99, libc::F_WRLCK).unwrap();
+
+    // Turning the end of the write lock into a read lock lets a reader in there
+    let reader = {
+        let locks = locks.clone();
+        tokio::spawn(async move { locks.wait_for_lock(7, 2, 200, 60, 69, libc::F_RDLCK).await })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!reader.is_finished());
+    locks.set_lock(7, 1, 100, 50, 99, libc::F_RDLCK).unwrap();
+    assert!(reader.await.unwrap());
+    assert_eq!(locks.test_lock(7, 2, 0, 0, libc::F_RDLCK).map(|l| l.typ), Some(libc::F_WRLCK));
+
+    // Downgrading the rest does the same for its start
+    let reader = {
+        let locks = locks.clone();
+        tokio::spawn(async move { locks.wait_for_lock(7, 3, 300, 0, 9, libc::F_RDLCK).await })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!reader.is_finished());
+    locks.set_lock(7, 1, 100, 0, 49, libc::F_RDLCK).unwrap();
+    assert!(reader.await.unwrap());
+    assert!(!locks.has_write_lock(7));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_remote_update_is_held_back_while_write_locked() -> Result<()> {
+    println!("\n🧪 Running test: Remote update is held back while the file is write-locked");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let old_etag = item.drive_item().etag.clone();
+    app_state
+        .file_locks()
+        .set_lock(5, 42, 4242, 0, i64::MAX as u64, libc::F_WRLCK)
+        .unwrap();
+
+    let mut di = item.drive_item().clone();
+    di.etag = Some("locked-etag-2".to_string());
+    let item_id = repo
+        .store_processing_item(&create_test_remote_processing_item(di, ChangeOperation::Update))
+        .await?;
+    let item_to_process = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    let processor = SyncProcessor::new(app_state.clone());
+    processor.process_single_item(&item_to_process).await?;
+
+    let pending = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    assert_eq!(pending.status, ProcessingStatus::New);
+    let unchanged = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert_eq!(unchanged.drive_item().etag, old_etag);
+
+    // Once the lock is gone the update goes through
+    app_state.file_locks().release_owner(5, 42);
+    processor.process_single_item(&pending).await?;
+    let updated = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert_eq!(updated.drive_item().etag.as_deref(), Some("locked-etag-2"));
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
@@ -21,3 +21,25 @@ async fn test_app_state_with_mock() -> Result<()> {
     
     Ok(())
 }
+
+#[tokio::test]
+async fn test_drive_quota_cached_from_mock() -> Result<()> {
+    let mut env = TEST_ENV.lock().await;
+    let app_state = env.get_app_state_with_mock().await?;
+    drop(env);
+
+    // Fetch quota from the mock client and cache it like the sync cycle does
+    let quota = app_state.onedrive().get_drive_quota().await?;
+    assert!(quota.used <= quota.total);
+
+    let quota_repo = app_state.persistency().drive_quota_repository();
+    quota_repo.store_quota(&quota).await?;
+    assert_eq!(quota_repo.get_quota().await?, Some(quota));
+
+    quota_repo.clear_quota().await?;
+    assert_eq!(quota_repo.get_quota().await?, None);
+
+    println!("✅ Drive quota cache test completed successfully");
+
+    Ok(())
+}
@@ -3,3 +3,26 @@ mod onedrive_mock_tests;
 mod mock_appstate_tests;
 mod enhanced_mock_tests;
 mod fuse_test;
+mod xattr_tests;
+mod posix_attributes_tests;
+mod symlink_tests;
+mod locks_tests;
+mod file_handle_tests;
+mod placeholder_tests;
+mod mount_config_tests;
+mod run_mode_tests;
+mod conflict_strategy_tests;
+mod text_merge_tests;
+mod selective_sync_tests;
+mod local_ignore_tests;
+mod transfer_tests;
+mod transfer_limits_tests;
+mod network_conditions_tests;
+mod change_notification_tests;
+mod sync_now_tests;
+mod upload_streaming_tests;
+mod download_resume_tests;
+mod upload_session_tests;
+mod graph_retry_tests;
+mod conditional_request_tests;
+mod streaming_tests;
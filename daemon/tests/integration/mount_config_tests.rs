@@ -0,0 +1,73 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_lib::config::{MountConfig, PlaceholderMode, Settings};
+use serial_test::serial;
+use std::time::Duration;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_mount_config_defaults_for_existing_settings() {
+    println!("\n🧪 Running test: Mount config defaults for settings written by older versions");
+    let json = r#"{
+        "download_folders": ["Documents"],
+        "sync_config": { "sync_interval_seconds": 30, "max_retry_count": 3, "enable_notifications": true },
+        "conflict_resolution_strategy": "Manual",
+        "sync_paused": false
+    }"#;
+    let settings: Settings = serde_json::from_str(json).unwrap();
+    assert_eq!(settings.download_folders, vec!["Documents".to_string()]);
+    assert_eq!(settings.mount_config.placeholder_mode, PlaceholderMode::Suffix);
+    assert_eq!(settings.mount_config.entry_ttl_seconds, 1);
+    assert_eq!(settings.mount_config.attr_ttl_seconds, 1);
+    assert!(settings.mount_config.keep_cache);
+    assert!(!settings.mount_config.writeback_cache);
+    assert!(!settings.mount_config.fsync_waits_for_upload);
+
+    // Only the given mount options change
+    let json = r#"{
+        "download_folders": [],
+        "sync_config": { "sync_interval_seconds": 30, "max_retry_count": 3, "enable_notifications": true },
+        "conflict_resolution_strategy": "Manual",
+        "sync_paused": false,
+        "mount_config": { "attr_ttl_seconds": 60, "writeback_cache": true }
+    }"#;
+    let settings: Settings = serde_json::from_str(json).unwrap();
+    assert_eq!(settings.mount_config.attr_ttl_seconds, 60);
+    assert_eq!(settings.mount_config.entry_ttl_seconds, 1);
+    assert!(settings.mount_config.writeback_cache);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_mount_follows_changed_mount_config() -> Result<()> {
+    println!("\n🧪 Running test: The mount uses mount config changes made after it was created");
+    let (app_state, _repo, _drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    app_state.config().settings.write().await.mount_config = MountConfig::default();
+
+    let fuse = OneDriveFuse::new(
+        app_state.persistency().pool().clone(),
+        app_state.persistency().download_queue_repository(),
+        app_state.file_manager.clone(),
+        app_state.clone(),
+    )
+    .await?;
+    assert_eq!(fuse.entry_ttl(), Duration::from_secs(1));
+    assert_eq!(fuse.attr_ttl(), Duration::from_secs(1));
+    assert!(fuse.mount_config().keep_cache);
+
+    {
+        let mut settings = app_state.config().settings.write().await;
+        settings.mount_config.entry_ttl_seconds = 30;
+        settings.mount_config.attr_ttl_seconds = 60;
+        settings.mount_config.keep_cache = false;
+        settings.mount_config.placeholder_mode = PlaceholderMode::Xattr;
+    }
+    assert_eq!(fuse.entry_ttl(), Duration::from_secs(30));
+    assert_eq!(fuse.attr_ttl(), Duration::from_secs(60));
+    assert!(!fuse.mount_config().keep_cache);
+    assert_eq!(fuse.placeholder_mode(), PlaceholderMode::Xattr);
+
+    app_state.config().settings.write().await.mount_config = MountConfig::default();
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use onedrive_sync_daemon::connectivity::NetworkConditions;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::persistency::processing_item_repository::{ChangeOperation, ProcessingStatus};
+use onedrive_sync_daemon::sync::SyncProcessor;
+use onedrive_sync_daemon::tasks::transfers::{BulkTransferMode, TransferScheduler};
+use onedrive_sync_lib::config::{ConstrainedAction, TransferPolicy};
+use serial_test::serial;
+
+use crate::common::fixtures::create_test_local_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_bulk_transfer_mode_for_conditions() {
+    println!("\n🧪 Running test: Metered connection and battery pause or throttle transfers");
+    let policy = TransferPolicy::default();
+    let metered = NetworkConditions { metered: true, on_battery: false };
+    let on_battery = NetworkConditions { metered: false, on_battery: true };
+    let both = NetworkConditions { metered: true, on_battery: true };
+
+    assert_eq!(
+        BulkTransferMode::for_conditions(NetworkConditions::default(), &policy),
+        BulkTransferMode::Normal
+    );
+    assert_eq!(
+        BulkTransferMode::for_conditions(metered, &policy).reason(),
+        "Paused: metered connection"
+    );
+    let mode = BulkTransferMode::for_conditions(on_battery, &policy);
+    assert_eq!(mode.reason(), "Throttled: on battery");
+    assert_eq!(mode.max_parallel(4), 1);
+    assert!(!mode.is_paused());
+    // A pause wins over a throttle
+    assert!(BulkTransferMode::for_conditions(both, &policy).is_paused());
+
+    let policy = TransferPolicy {
+        on_metered: ConstrainedAction::Ignore,
+        on_battery: ConstrainedAction::Pause,
+    };
+    assert_eq!(BulkTransferMode::for_conditions(metered, &policy), BulkTransferMode::Normal);
+    assert_eq!(BulkTransferMode::for_conditions(both, &policy).reason(), "Paused: on battery");
+    assert_eq!(BulkTransferMode::Normal.max_parallel(4), 4);
+    assert_eq!(BulkTransferMode::Normal.reason(), "");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_downloads_wait_while_metered() -> Result<()> {
+    println!("\n🧪 Running test: Queued downloads wait while the connection is metered");
+    let (app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Q1 and Q2 reports (ino 5 and 6)
+    for ino in 5..=6 {
+        let item = drive_items_with_fuse_repo
+            .get_drive_item_with_fuse_by_virtual_ino(ino)
+            .await?
+            .unwrap();
+        let local_path = app_state.file_manager().get_download_dir().join(item.id());
+        download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+    }
+
+    let scheduler = TransferScheduler::new(app_state.clone());
+    assert!(app_state.connectivity().set_network_conditions(NetworkConditions {
+        metered: true,
+        on_battery: false,
+    }));
+    assert!(scheduler.start_downloads().await?.is_empty());
+    assert_eq!(download_queue_repo.get_pending_downloads().await?.len(), 2);
+
+    // On battery only one download runs at a time
+    app_state.connectivity().set_network_conditions(NetworkConditions {
+        metered: false,
+        on_battery: true,
+    });
+    assert_eq!(scheduler.start_downloads().await?.len(), 1);
+
+    app_state.connectivity().set_network_conditions(NetworkConditions::default());
+    // Let the started download finish before cleaning up
+    while !app_state.transfers().list().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_held_upload_holds_later_changes_of_item() -> Result<()> {
+    println!("\n🧪 Running test: Changes after a held-back upload of the same item wait as well");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+    app_state.connectivity().set_network_conditions(NetworkConditions {
+        metered: true,
+        on_battery: false,
+    });
+
+    // Q4 report (ino 8) changed and then renamed locally
+    let report = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("8"), b"changed report")?;
+    let update_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            report.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+    let mut renamed = report.drive_item().clone();
+    renamed.name = Some("Q4_Report_final.pdf".to_string());
+    let rename_id = repo
+        .store_processing_item(&create_test_local_processing_item(renamed, ChangeOperation::Rename))
+        .await?;
+    sync_processor.process_all_items().await?;
+
+    // Neither change reached OneDrive
+    assert!(mock_client.if_match_tags().is_empty());
+    for id in [update_id, rename_id] {
+        let item = repo.get_processing_item_by_id(id).await?.unwrap();
+        assert_eq!(item.status, ProcessingStatus::New);
+    }
+
+    app_state.connectivity().set_network_conditions(NetworkConditions::default());
+    repo.clear_all_items().await?;
+    Ok(())
+}
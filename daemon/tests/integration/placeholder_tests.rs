@@ -0,0 +1,50 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::filesystem::PLACEHOLDER_SUFFIX;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_lib::config::PlaceholderMode;
+use serial_test::serial;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_placeholder_names_follow_mode() -> Result<()> {
+    println!("\n🧪 Running test: Placeholder names follow the configured mode");
+    let (app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let fuse = OneDriveFuse::new(
+        app_state.persistency().pool().clone(),
+        app_state.persistency().download_queue_repository(),
+        app_state.file_manager.clone(),
+        app_state.clone(),
+    )
+    .await?;
+
+    // Item 5 is a file that is not downloaded in the test tree
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let name = item.name().unwrap().to_string();
+    let parent = item.parent_ino().unwrap();
+    let suffixed = format!("{}{}", name, PLACEHOLDER_SUFFIX);
+
+    // Suffix mode: listed with the suffix, both names resolve
+    assert_eq!(fuse.placeholder_mode(), PlaceholderMode::Suffix);
+    assert_eq!(fuse.display_name(&item, fuse.placeholder_mode()), suffixed);
+    assert_eq!(fuse.lookup_name(&suffixed), name);
+    assert_eq!(fuse.find_child(parent, &suffixed)?.and_then(|i| i.virtual_ino()), Some(5));
+    assert_eq!(fuse.find_child(parent, &name)?.and_then(|i| i.virtual_ino()), Some(5));
+
+    // Real-name modes: listed and resolved by the real name only
+    for mode in [PlaceholderMode::HydrateOnOpen, PlaceholderMode::Xattr] {
+        app_state.config().settings.write().await.mount_config.placeholder_mode = mode;
+        assert_eq!(fuse.display_name(&item, fuse.placeholder_mode()), name);
+        assert_eq!(fuse.lookup_name(&suffixed), suffixed);
+        assert_eq!(fuse.find_child(parent, &name)?.and_then(|i| i.virtual_ino()), Some(5));
+        assert!(fuse.find_child(parent, &suffixed)?.is_none());
+    }
+
+    app_state.config().settings.write().await.mount_config.placeholder_mode = PlaceholderMode::Suffix;
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::fuse::attributes::AttributeManager;
+use onedrive_sync_daemon::persistency::processing_item_repository::ChangeOperation;
+use onedrive_sync_daemon::sync::SyncProcessor;
+use serial_test::serial;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::common::fixtures::{
+    create_test_local_processing_item, create_test_remote_processing_item,
+};
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_posix_attributes_are_persisted_and_honored() -> Result<()> {
+    println!("\n🧪 Running test: POSIX attributes are persisted and honored");
+    let (_app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let mtime = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let mtime_str = AttributeManager::format_time(mtime);
+    drive_items_with_fuse_repo
+        .update_posix_attributes(5, Some(0o755), Some(1234), None, None, Some(&mtime_str))
+        .await?;
+
+    // A later partial update keeps the values that were not given
+    drive_items_with_fuse_repo
+        .update_posix_attributes(5, None, None, Some(4321), None, None)
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let attr = AttributeManager::item_to_file_attr(&item);
+    assert_eq!(attr.perm, 0o755);
+    assert_eq!(attr.uid, 1234);
+    assert_eq!(attr.gid, 4321);
+    assert_eq!(attr.mtime, mtime);
+
+    // Storing the item again (e.g. from a sync) must not drop the attributes
+    drive_items_with_fuse_repo
+        .store_drive_item_with_fuse(&item)
+        .await?;
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert_eq!(item.fuse_metadata().mode, Some(0o755));
+    assert_eq!(item.fuse_metadata().mtime.as_deref(), Some(mtime_str.as_str()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_remote_change_clears_local_mtime() -> Result<()> {
+    println!("\n🧪 Running test: Remote change clears local mtime");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let mtime_str = AttributeManager::format_time(UNIX_EPOCH + Duration::from_secs(1_600_000_000));
+    drive_items_with_fuse_repo
+        .update_posix_attributes(5, Some(0o600), None, None, None, Some(&mtime_str))
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let mut di = item.drive_item().clone();
+    di.etag = Some("remote-etag-2".to_string());
+    di.last_modified = Some("2031-01-01T00:00:00Z".to_string());
+    let item_id = repo
+        .store_processing_item(&create_test_remote_processing_item(di, ChangeOperation::Update))
+        .await?;
+    let item_to_process = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&item_to_process)
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert_eq!(item.fuse_metadata().mtime, None);
+    assert_eq!(item.fuse_metadata().mode, Some(0o600));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_local_mtime_is_uploaded_for_non_downloaded_file() -> Result<()> {
+    println!("\n🧪 Running test: Local mtime is uploaded for a file that is not downloaded");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+
+    let mtime_str = AttributeManager::format_time(UNIX_EPOCH + Duration::from_secs(1_600_000_000));
+    drive_items_with_fuse_repo
+        .update_posix_attributes(5, None, None, None, None, Some(&mtime_str))
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let item_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            item.drive_item().clone(),
+            ChangeOperation::Touch,
+        ))
+        .await?;
+    let item_to_process = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&item_to_process)
+        .await?;
+
+    assert_eq!(mock_client.get_call_count("update_last_modified"), 1);
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    // Sub-second precision is kept and the uploaded override is dropped
+    assert_eq!(item.drive_item().last_modified.as_deref(), Some(mtime_str.as_str()));
+    assert_eq!(item.fuse_metadata().mtime, None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_touch_of_downloaded_file_does_not_upload_content() -> Result<()> {
+    println!("\n🧪 Running test: Touching a downloaded file only uploads its modification time");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // Q1 report (ino 5) is downloaded and touched
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("5"), b"report")?;
+    let mtime = UNIX_EPOCH + Duration::from_millis(1_600_000_000_250);
+    std::fs::File::options()
+        .write(true)
+        .open(local_dir.join("5"))?
+        .set_modified(mtime)?;
+    let mtime_str = AttributeManager::format_time(mtime);
+    drive_items_with_fuse_repo
+        .update_posix_attributes(5, None, None, None, None, Some(&mtime_str))
+        .await?;
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    repo.store_processing_item(&create_test_local_processing_item(
+        item.drive_item().clone(),
+        ChangeOperation::Touch,
+    ))
+    .await?;
+    sync_processor.process_all_items().await?;
+
+    assert_eq!(mock_client.get_call_count("upload_file"), 0);
+    assert_eq!(mock_client.get_call_count("update_last_modified"), 1);
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert_eq!(item.drive_item().last_modified.as_deref(), Some(mtime_str.as_str()));
+    assert_eq!(item.fuse_metadata().mtime, None);
+
+    // A later content change does not upload the time again
+    std::fs::write(local_dir.join("5"), b"changed report")?;
+    repo.store_processing_item(&create_test_local_processing_item(
+        item.drive_item().clone(),
+        ChangeOperation::Update,
+    ))
+    .await?;
+    sync_processor.process_all_items().await?;
+    assert_eq!(mock_client.get_call_count("upload_file"), 1);
+    assert_eq!(mock_client.get_call_count("update_last_modified"), 1);
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
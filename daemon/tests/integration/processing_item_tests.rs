@@ -822,6 +822,7 @@ async fn test_mock_api_response_showcase() -> Result<()> {
         deleted: None,
         parent_reference: None,
         ctag: None,
+        web_url: None,
     };
     mock_client.set_expected_drive_item("showcase_file_id".to_string(), custom_drive_item);
     
@@ -1724,6 +1725,7 @@ async fn test_conflict_resolution_integration_end_to_end() -> Result<()> {
         deleted: None,
         parent_reference: original_item.drive_item().parent_reference.clone(),
         ctag: None,
+        web_url: None,
     };
     mock_client.set_expected_drive_item("e2e_resolved_file_id".to_string(), expected_drive_item);
 
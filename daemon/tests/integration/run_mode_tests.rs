@@ -0,0 +1,71 @@
+use anyhow::Result;
+use onedrive_sync_daemon::connectivity::ConnectivityStatus;
+use onedrive_sync_daemon::fuse::OneDriveFuse;
+use onedrive_sync_daemon::persistency::processing_item_repository::ChangeOperation;
+use serial_test::serial;
+use std::sync::Arc;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_read_only_mode_queues_nothing() -> Result<()> {
+    println!("\n🧪 Running test: Read-only mode queues nothing for upload");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let mut read_only_state = (*app_state).clone();
+    read_only_state.run_mode.read_only = true;
+    let read_only_state = Arc::new(read_only_state);
+    let fuse = OneDriveFuse::new(
+        read_only_state.persistency().pool().clone(),
+        read_only_state.persistency().download_queue_repository(),
+        read_only_state.file_manager.clone(),
+        read_only_state.clone(),
+    )
+    .await?;
+    assert!(fuse.is_read_only());
+    assert!(!fuse.is_offline());
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    for operation in [ChangeOperation::Create, ChangeOperation::Update, ChangeOperation::Delete] {
+        assert!(fuse.create_processing_item(&item, operation).is_err());
+    }
+    assert!(fuse.queue_upload(5).is_err());
+    assert!(repo.get_all_processing_items().await?.is_empty());
+
+    // Pinning is a change too and queues no download
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+    assert!(fuse.set_pinned(&item, true).is_err());
+    assert!(!fuse.is_pinned(5));
+    assert!(download_queue_repo.get_all_items().await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_offline_mode_never_probes_connectivity() -> Result<()> {
+    println!("\n🧪 Running test: Offline mode reports offline without probing");
+    let (app_state, _repo, _drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let mut offline_state = (*app_state).clone();
+    offline_state.run_mode.offline = true;
+    assert_eq!(offline_state.connectivity_status().await, ConnectivityStatus::Offline);
+
+    let offline_state = Arc::new(offline_state);
+    let fuse = OneDriveFuse::new(
+        offline_state.persistency().pool().clone(),
+        offline_state.persistency().download_queue_repository(),
+        offline_state.file_manager.clone(),
+        offline_state.clone(),
+    )
+    .await?;
+    assert!(fuse.is_offline());
+    assert!(!fuse.is_read_only());
+
+    Ok(())
+}
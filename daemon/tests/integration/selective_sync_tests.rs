@@ -0,0 +1,118 @@
+use anyhow::Result;
+use onedrive_sync_daemon::sync::selective_sync::SyncRuleSet;
+use onedrive_sync_lib::config::SelectiveSync;
+use onedrive_sync_lib::dbus::types::SyncRules;
+use serial_test::serial;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+fn patterns(patterns: &[&str]) -> Vec<String> {
+    patterns.iter().map(|p| p.to_string()).collect()
+}
+
+#[test]
+fn test_sync_rules_match_gitignore_style_patterns() -> Result<()> {
+    println!("\n🧪 Running test: Selective sync rules match gitignore-style patterns");
+    let selective_sync = SelectiveSync {
+        include: patterns(&["Projects/**"]),
+        exclude: patterns(&["**/node_modules/**", "*.tmp", "~$*"]),
+        hide_excluded: false,
+    };
+    let rules = SyncRuleSet::new(&patterns(&["/Photos", "Documents"]), &selective_sync)?;
+
+    // Download folders match whole folder names only
+    assert!(rules.is_included("Photos/beach.jpg"));
+    assert!(rules.is_included("/Photos/2024/beach.jpg"));
+    assert!(!rules.is_included("Photos2/beach.jpg"));
+
+    assert!(rules.is_included("Projects/app/src/main.rs"));
+    assert!(!rules.is_included("Projects/app/node_modules/left-pad/index.js"));
+    assert!(rules.is_excluded("Projects/app/node_modules/left-pad/index.js"));
+
+    // Patterns without a slash match names at any depth, excludes win over includes
+    assert!(rules.is_included("Documents/report.docx"));
+    assert!(!rules.is_included("Documents/notes.tmp"));
+    assert!(!rules.is_included("Documents/~$report.docx"));
+    assert!(rules.is_excluded("Projects/cache.tmp"));
+    assert!(!rules.is_excluded("Documents/report.tmp.docx"));
+    assert!(!rules.is_included("Music/song.mp3"));
+
+    // OneDrive names are case-insensitive, so are the patterns
+    assert!(rules.is_included("projects/app/README.md"));
+    assert!(rules.is_excluded("Projects/app/Node_Modules/left-pad/index.js"));
+    assert!(rules.is_excluded("Documents/NOTES.TMP"));
+
+    // Only hidden from the mount when asked for
+    assert!(!rules.is_hidden("Documents/notes.tmp"));
+    let hiding = SyncRuleSet::new(&[], &SelectiveSync { hide_excluded: true, ..selective_sync })?;
+    assert!(hiding.is_hidden("Documents/notes.tmp"));
+    assert!(!hiding.is_hidden("Documents/report.docx"));
+
+    let invalid = SelectiveSync {
+        include: patterns(&["Projects/[ab"]),
+        ..Default::default()
+    };
+    assert!(SyncRuleSet::new(&[], &invalid).is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_sync_rules_preview_and_update_over_dbus() -> Result<()> {
+    println!("\n🧪 Running test: Selective sync rules are previewed and updated over D-Bus");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+
+    let rules = SyncRules {
+        include: patterns(&["*.pdf"]),
+        exclude: patterns(&["Q2_*"]),
+        hide_excluded: true,
+    };
+    let preview = dbus_service
+        .preview_sync_rules_for_test(rules.clone())
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus preview_sync_rules failed: {}", e))?;
+    let q1 = preview.iter().find(|m| m.path.ends_with("/Q1_Report.pdf")).expect("Q1 matches");
+    assert!(q1.included && !q1.excluded && !q1.hidden);
+    let q2 = preview.iter().find(|m| m.path.ends_with("/Q2_Report.pdf")).expect("Q2 matches");
+    assert!(!q2.included && q2.excluded && q2.hidden);
+    assert!(!preview.iter().any(|m| m.path.ends_with(".jpg")));
+    // A preview does not change the settings
+    assert_eq!(app_state.config().settings.read().await.selective_sync, SelectiveSync::default());
+
+    dbus_service
+        .set_sync_rules_for_test(rules)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus set_sync_rules failed: {}", e))?;
+    assert_eq!(app_state.config().settings.read().await.selective_sync.include, patterns(&["*.pdf"]));
+    assert!(app_state.sync_rules().await.is_hidden("Documents/Q2_Report.pdf"));
+
+    // Newly included files are queued for download, excluded ones are not
+    let queued: Vec<String> = app_state
+        .persistency()
+        .download_queue_repository()
+        .get_pending_downloads()
+        .await?
+        .into_iter()
+        .map(|(_, id, _)| id)
+        .collect();
+    let q1_item = drive_items_with_fuse_repo.get_drive_item_with_fuse_by_virtual_ino(5).await?.unwrap();
+    let q2_item = drive_items_with_fuse_repo.get_drive_item_with_fuse_by_virtual_ino(6).await?.unwrap();
+    assert!(queued.contains(&q1_item.id().to_string()));
+    assert!(!queued.contains(&q2_item.id().to_string()));
+
+    let invalid = SyncRules {
+        exclude: patterns(&["[oops"]),
+        ..Default::default()
+    };
+    assert!(dbus_service.set_sync_rules_for_test(invalid).await.is_err());
+    assert_eq!(app_state.config().settings.read().await.selective_sync.exclude, patterns(&["Q2_*"]));
+
+    dbus_service
+        .set_sync_rules_for_test(SyncRules::default())
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus set_sync_rules failed: {}", e))?;
+    app_state.persistency().download_queue_repository().clear_all_items().await?;
+    repo.clear_all_items().await?;
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use anyhow::Result;
+use onedrive_sync_daemon::app_state::AppState;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::fuse::streaming::{StreamingManager, STREAM_CHUNK_SIZE};
+use onedrive_sync_daemon::onedrive_service::onedrive_models::DownloadResult;
+use onedrive_sync_daemon::persistency::types::DriveItemWithFuse;
+use serial_test::serial;
+use std::os::unix::fs::FileExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::mock_onedrive_client::MockOneDriveClient;
+use crate::integration::processing_item_tests::setup_test_env;
+
+/// Three chunks, the last one partial
+const SIZE: u64 = 2 * STREAM_CHUNK_SIZE + 1000;
+
+fn content(seed: u8) -> Vec<u8> {
+    (0..SIZE).map(|i| (i % 251) as u8 ^ seed).collect()
+}
+
+/// Q1 report (ino 5) as a remote file of `SIZE` bytes in version `etag`
+async fn remote_report(
+    app_state: &AppState,
+    mock_client: &MockOneDriveClient,
+    etag: &str,
+    data: Vec<u8>,
+) -> Result<DriveItemWithFuse> {
+    let mut item = app_state
+        .persistency()
+        .drive_item_with_fuse_repository()
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    item.drive_item_mut().size = Some(SIZE);
+    item.drive_item_mut().etag = Some(etag.to_string());
+    mock_client.set_expected_download_result(
+        item.id().to_string(),
+        DownloadResult {
+            file_data: data,
+            file_name: "Q1_Report.pdf".to_string(),
+            onedrive_id: item.id().to_string(),
+            etag: Some(etag.to_string()),
+            mime_type: Some("application/pdf".to_string()),
+            size: Some(SIZE),
+            last_modified: Some("2023-01-01T00:00:00Z".to_string()),
+        },
+    );
+    std::fs::create_dir_all(app_state.config().download_dir())?;
+    std::fs::create_dir_all(app_state.file_manager().get_local_dir())?;
+    Ok(item)
+}
+
+/// Wait until the streamed file has been moved into the local folder
+async fn wait_for_local_file(app_state: &AppState, ino: u64, expected: &[u8]) -> Result<()> {
+    let local_path = app_state.file_manager().get_local_dir().join(ino.to_string());
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while std::fs::read(&local_path).ok().as_deref() != Some(expected) {
+        assert!(tokio::time::Instant::now() < deadline, "streaming download did not finish");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_read_fetches_its_range_first() -> Result<()> {
+    println!("\n🧪 Running test: A read streams its own chunk ahead of the background download");
+    let (app_state, _repo, _drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let data = content(0);
+    let item = remote_report(&app_state, &mock_client, "v1", data.clone()).await?;
+    mock_client.delay_downloads(Duration::from_millis(200));
+    let manager = StreamingManager::new(app_state.clone());
+
+    let stream = manager.get_or_start(&item)?;
+    let offset = 2 * STREAM_CHUNK_SIZE + 10;
+    stream.ensure_range(&app_state, offset, 100).await?;
+
+    // The read did not wait for the background download to get to its chunk
+    let ranges = mock_client.download_ranges();
+    assert!(ranges.contains(&(2 * STREAM_CHUNK_SIZE, SIZE - 1)));
+    assert!(!ranges.contains(&(STREAM_CHUNK_SIZE, 2 * STREAM_CHUNK_SIZE - 1)));
+    assert!(!stream.is_complete());
+    let mut buf = vec![0; 100];
+    std::fs::File::open(stream.cache_path())?.read_exact_at(&mut buf, offset)?;
+    assert_eq!(buf, &data[offset as usize..offset as usize + 100]);
+
+    wait_for_local_file(&app_state, 5, &data).await?;
+    assert_eq!(mock_client.download_ranges().len(), 3);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_background_download_hydrates_file() -> Result<()> {
+    println!("\n🧪 Running test: The background download moves the finished stream into the local folder");
+    let (app_state, _repo, _drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+    let data = content(0);
+    let item = remote_report(&app_state, &mock_client, "v1", data.clone()).await?;
+    let local_path = app_state.file_manager().get_download_dir().join(item.id());
+    download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+    let manager = StreamingManager::new(app_state.clone());
+
+    let stream = manager.get_or_start(&item)?;
+    wait_for_local_file(&app_state, 5, &data).await?;
+
+    assert!(stream.is_complete());
+    assert!(!stream.cache_path().exists());
+    // The queued full download is no longer needed
+    assert!(download_queue_repo.get_all_items().await?.is_empty());
+    // Every chunk was downloaded exactly once
+    let mut ranges = mock_client.download_ranges();
+    ranges.sort();
+    assert_eq!(
+        ranges,
+        vec![
+            (0, STREAM_CHUNK_SIZE - 1),
+            (STREAM_CHUNK_SIZE, 2 * STREAM_CHUNK_SIZE - 1),
+            (2 * STREAM_CHUNK_SIZE, SIZE - 1),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_changed_etag_restarts_stream() -> Result<()> {
+    println!("\n🧪 Running test: A stream restarts when the remote file changed");
+    let (app_state, _repo, _drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let old_data = content(0);
+    let item = remote_report(&app_state, &mock_client, "v1", old_data).await?;
+    mock_client.delay_downloads(Duration::from_millis(100));
+    let manager = StreamingManager::new(app_state.clone());
+
+    let old_stream = manager.get_or_start(&item)?;
+    assert!(Arc::ptr_eq(&old_stream, &manager.get_or_start(&item)?));
+
+    let new_data = content(0x5a);
+    let changed = remote_report(&app_state, &mock_client, "v2", new_data.clone()).await?;
+    let new_stream = manager.get_or_start(&changed)?;
+    assert!(!Arc::ptr_eq(&old_stream, &new_stream));
+
+    // Only the new version ends up in the local folder
+    wait_for_local_file(&app_state, 5, &new_data).await?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        std::fs::read(app_state.file_manager().get_local_dir().join("5"))?,
+        new_data
+    );
+    assert!(!old_stream.is_complete());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_hydrate_remembers_merge_base() -> Result<()> {
+    println!("\n🧪 Running test: A file hydrated through the mount becomes its merge base");
+    let (app_state, _repo, _drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let text = b"first line\nsecond line\n".to_vec();
+    let mut item = remote_report(&app_state, &mock_client, "v1", text.clone()).await?;
+    item.drive_item_mut().size = Some(text.len() as u64);
+    item.set_ctag("c1".to_string());
+
+    StreamingManager::new(app_state.clone()).hydrate(&item).await?;
+
+    assert_eq!(std::fs::read(app_state.file_manager().get_local_dir().join("5"))?, text);
+    let base = app_state
+        .persistency()
+        .merge_base_repository()
+        .get_base(item.id())
+        .await?
+        .unwrap();
+    assert_eq!(base.ctag, "c1");
+    assert_eq!(base.content.as_bytes(), text.as_slice());
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use fuser::FileType;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::fuse::attributes::AttributeManager;
+use onedrive_sync_daemon::fuse::symlink::{is_symlink, link_name, stored_name};
+use onedrive_sync_daemon::sync::ChangeOperation;
+use serial_test::serial;
+use std::path::Path;
+
+use crate::common::fixtures::create_test_drive_item_with_fuse_file;
+use crate::integration::fuse_test::{mount_point, FuseMount};
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_symlink_marker_file_is_shown_as_symlink() -> Result<()> {
+    println!("\n🧪 Running test: Symlink marker file is shown as symlink");
+    let (_app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    assert_eq!(stored_name("node_modules"), "node_modules.onedrive-symlink");
+    assert_eq!(link_name("node_modules.onedrive-symlink"), "node_modules");
+
+    let parent = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(1)
+        .await?
+        .unwrap();
+    let link = create_test_drive_item_with_fuse_file(
+        "symlink_id",
+        &stored_name("current"),
+        Some(parent.drive_item().id.clone()),
+        None,
+        Some(1),
+    );
+    let ino = drive_items_with_fuse_repo
+        .store_drive_item_with_fuse(&link)
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(1, "current.onedrive-symlink")
+        .await?
+        .unwrap();
+    assert_eq!(item.virtual_ino(), Some(ino));
+    assert!(is_symlink(&item));
+
+    let attr = AttributeManager::item_to_file_attr(&item);
+    assert_eq!(attr.kind, FileType::Symlink);
+    assert_eq!(attr.perm, 0o777);
+
+    // A regular file is not a symlink, neither is a file named only like the marker
+    let regular = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    assert!(!is_symlink(&regular));
+    let bare = create_test_drive_item_with_fuse_file("bare_id", ".onedrive-symlink", None, None, None);
+    assert!(!is_symlink(&bare));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+#[ignore = "Requires FUSE support - not available in CI"]
+async fn test_symlink_created_on_mount_is_stored_as_marker_file() -> Result<()> {
+    println!("\n🧪 Running test: ln -s on the mount stores a marker file and renames keep the marker");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let mut fuse_mount = FuseMount::new(&app_state).await?;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let link = Path::new(&mount_point()).join("current");
+    std::os::unix::fs::symlink("Documents/Work", &link)?;
+    assert!(link.symlink_metadata()?.file_type().is_symlink());
+    assert_eq!(std::fs::read_link(&link)?, Path::new("Documents/Work"));
+
+    let renamed = Path::new(&mount_point()).join("latest");
+    std::fs::rename(&link, &renamed)?;
+    assert!(!link.exists());
+    assert_eq!(std::fs::read_link(&renamed)?, Path::new("Documents/Work"));
+    // Creating a link over an existing name fails like on any filesystem
+    let err = std::os::unix::fs::symlink("Documents", &renamed).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EEXIST));
+
+    fuse_mount.stop()?;
+
+    let stored = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_parent_ino_and_name_case_insensitive(1, "latest.onedrive-symlink")
+        .await?
+        .unwrap();
+    assert!(is_symlink(&stored));
+    let local_path = app_state
+        .file_manager()
+        .get_local_dir()
+        .join(stored.virtual_ino().unwrap().to_string());
+    assert_eq!(std::fs::read(local_path)?, b"Documents/Work");
+
+    let operations: Vec<ChangeOperation> = repo
+        .get_all_processing_items()
+        .await?
+        .into_iter()
+        .map(|item| item.change_operation)
+        .collect();
+    assert_eq!(operations, vec![ChangeOperation::Create, ChangeOperation::Rename]);
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+#[ignore = "Requires FUSE support - not available in CI"]
+async fn test_readlink_on_mount_resolves_synced_symlink() -> Result<()> {
+    println!("\n🧪 Running test: readlink on the mount returns the target of a synced marker file");
+    let (app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let parent = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(1)
+        .await?
+        .unwrap();
+    let link = create_test_drive_item_with_fuse_file(
+        "synced_symlink_id",
+        &stored_name("reports"),
+        Some(parent.drive_item().id.clone()),
+        None,
+        Some(1),
+    );
+    let ino = drive_items_with_fuse_repo
+        .store_drive_item_with_fuse(&link)
+        .await?;
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join(ino.to_string()), b"Documents/Work/Reports")?;
+
+    let mut fuse_mount = FuseMount::new(&app_state).await?;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let path = Path::new(&mount_point()).join("reports");
+    assert!(path.symlink_metadata()?.file_type().is_symlink());
+    assert_eq!(std::fs::read_link(&path)?, Path::new("Documents/Work/Reports"));
+    // Following the link ends up in the real folder
+    assert!(path.join("Q1_Report.pdf.onedrivedownload").exists());
+    // A regular file is not a link
+    let regular = Path::new(&mount_point()).join("Documents");
+    let err = std::fs::read_link(&regular).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+    fuse_mount.stop()?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use onedrive_sync_daemon::persistency::processing_item_repository::{
+    ChangeOperation, ChangeType, ProcessingItem,
+};
+use onedrive_sync_daemon::tasks::delta_update::SyncCycle;
+use serial_test::serial;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_sync_path_refreshes_subtree() -> Result<()> {
+    println!("\n🧪 Running test: Sync path refreshes a folder and queues its files again");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Reports (ino 4) and its Q1..Q4 reports (ino 5..8); Q2 changed on OneDrive
+    let mut changed_id = String::new();
+    for ino in 4..=8 {
+        let item = drive_items_with_fuse_repo
+            .get_drive_item_with_fuse_by_virtual_ino(ino)
+            .await?
+            .unwrap();
+        let mut remote = item.drive_item().clone();
+        if ino == 6 {
+            remote.etag = Some("changed_etag".to_string());
+            changed_id = remote.id.clone();
+        }
+        mock_client.set_expected_drive_item(remote.id.clone(), remote);
+    }
+
+    // Q3 has local changes waiting for upload and must not be downloaded over
+    let q3 = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    repo.store_processing_item(&ProcessingItem::new_local(q3.drive_item().clone(), ChangeOperation::Update))
+        .await?;
+
+    let result = dbus_service
+        .sync_path_for_test("/Documents/Work/Reports".to_string(), true)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus sync_path failed: {}", e))?;
+    assert_eq!(result.refreshed, 5);
+    assert_eq!(result.changed, 1);
+    assert_eq!(result.queued_downloads, 3);
+
+    let change = repo
+        .get_pending_processing_item_by_drive_item_id_and_change_type(&changed_id, &ChangeType::Remote)
+        .await?
+        .expect("the changed report is queued for processing");
+    assert_eq!(change.change_operation, ChangeOperation::Update);
+    assert_eq!(download_queue_repo.get_pending_downloads().await?.len(), 3);
+
+    // Without redownload only the metadata is refreshed
+    download_queue_repo.clear_all_items().await?;
+    let result = dbus_service
+        .sync_path_for_test("Documents/Work/Reports/Q1_Report.pdf".to_string(), false)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus sync_path failed: {}", e))?;
+    assert_eq!(result.refreshed, 1);
+    assert_eq!(result.changed, 0);
+    assert_eq!(result.queued_downloads, 0);
+    assert!(download_queue_repo.get_pending_downloads().await?.is_empty());
+
+    let missing = dbus_service
+        .sync_path_for_test("/Documents/Nowhere".to_string(), false)
+        .await;
+    assert!(matches!(missing, Err(zbus::fdo::Error::InvalidArgs(_))));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_sync_now_coalesces_with_running_cycle() -> Result<()> {
+    println!("\n🧪 Running test: Sync now coalesces with a running sync cycle");
+    let (app_state, _repo, _drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+
+    // A cycle is running
+    let guard = app_state.sync_running.clone().lock_owned().await;
+    let started = dbus_service
+        .sync_now_for_test()
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus sync_now failed: {}", e))?;
+    assert!(!started);
+    // The sync task is asked for a cycle after the running one
+    assert!(app_state.sync_trigger().wait(std::time::Duration::from_millis(100)).await);
+    assert!(!SyncCycle::start_now(app_state.clone()));
+    drop(guard);
+
+    assert!(dbus_service
+        .sync_now_for_test()
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus sync_now failed: {}", e))?);
+    // The requested cycle holds the lock until it is done
+    let _guard = tokio::time::timeout(std::time::Duration::from_secs(10), app_state.sync_running.lock()).await?;
+    Ok(())
+}
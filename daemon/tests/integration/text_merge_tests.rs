@@ -0,0 +1,226 @@
+use anyhow::Result;
+use onedrive_sync_daemon::app_state::AppState;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::onedrive_service::onedrive_models::DownloadResult;
+use onedrive_sync_daemon::persistency::drive_item_with_fuse_repository::DriveItemWithFuseRepository;
+use onedrive_sync_daemon::persistency::processing_item_repository::{
+    ChangeOperation, ProcessingItemRepository, ProcessingStatus,
+};
+use onedrive_sync_daemon::sync::text_merge::MAX_MERGE_SIZE;
+use onedrive_sync_daemon::sync::SyncProcessor;
+use serial_test::serial;
+use std::sync::Arc;
+
+use crate::common::fixtures::{
+    create_test_local_processing_item, create_test_remote_processing_item,
+};
+use crate::common::mock_onedrive_client::MockOneDriveClient;
+use crate::integration::processing_item_tests::setup_test_env;
+
+/// Set up a text file changed on both sides since the synced `base` version.
+/// Returns the ids of the local and remote processing items.
+async fn setup_text_conflict(
+    app_state: &Arc<AppState>,
+    repo: &ProcessingItemRepository,
+    drive_items_with_fuse_repo: &DriveItemWithFuseRepository,
+    mock_client: &MockOneDriveClient,
+    base: &str,
+    local: &str,
+    remote: &str,
+) -> Result<(i64, i64)> {
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    let id = original.id().to_string();
+    drive_items_with_fuse_repo.update_ctag(&id, "base-ctag").await?;
+    app_state
+        .persistency()
+        .merge_base_repository()
+        .store_base(&id, "base-ctag", base)
+        .await?;
+
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("7"), local)?;
+
+    let mut remote_item = original.drive_item().clone();
+    remote_item.etag = Some("remote-etag".to_string());
+    remote_item.ctag = Some("remote-ctag".to_string());
+    remote_item.download_url = Some("https://download/remote".to_string());
+    mock_client.set_expected_drive_item(id.clone(), remote_item.clone());
+    mock_client.set_expected_download_result(
+        id.clone(),
+        DownloadResult {
+            file_data: remote.as_bytes().to_vec(),
+            file_name: "Q3_Report.pdf".to_string(),
+            onedrive_id: id.clone(),
+            etag: Some("remote-etag".to_string()),
+            mime_type: Some("text/plain".to_string()),
+            size: Some(remote.len() as u64),
+            last_modified: None,
+        },
+    );
+
+    let mut local_item = original.drive_item().clone();
+    local_item.etag = Some("local-etag".to_string());
+    let local_id = repo
+        .store_processing_item(&create_test_local_processing_item(local_item, ChangeOperation::Update))
+        .await?;
+    let remote_id = repo
+        .store_processing_item(&create_test_remote_processing_item(remote_item, ChangeOperation::Update))
+        .await?;
+    Ok((local_id, remote_id))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_content_conflict_is_merged() -> Result<()> {
+    println!("\n🧪 Running test: Content conflict of a text file is merged");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let (local_id, remote_id) = setup_text_conflict(
+        &app_state,
+        &repo,
+        &drive_items_with_fuse_repo,
+        &mock_client,
+        "one\ntwo\nthree\n",
+        "ONE\ntwo\nthree\n",
+        "one\ntwo\nTHREE\n",
+    )
+    .await?;
+
+    let remote_item = repo.get_processing_item_by_id(remote_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&remote_item)
+        .await?;
+
+    // The remote change is merged in, the local change uploads the result
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::Done);
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::New);
+    let local_path = app_state.file_manager().get_local_dir().join("7");
+    assert_eq!(std::fs::read_to_string(&local_path)?, "ONE\ntwo\nTHREE\n");
+
+    // The remote version is the new base
+    let merged = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(7)
+        .await?
+        .unwrap();
+    assert_eq!(merged.ctag(), Some("remote-ctag"));
+    assert_eq!(merged.etag(), Some("remote-etag"));
+    let base = app_state
+        .persistency()
+        .merge_base_repository()
+        .get_base(merged.id())
+        .await?
+        .unwrap();
+    assert_eq!(base.ctag, "remote-ctag");
+    assert_eq!(base.content, "one\ntwo\nTHREE\n");
+
+    app_state.persistency().merge_base_repository().clear_all_items().await?;
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_overlapping_changes_stay_conflicted_with_diff() -> Result<()> {
+    println!("\n🧪 Running test: Overlapping changes stay conflicted and can be diffed");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let (local_id, remote_id) = setup_text_conflict(
+        &app_state,
+        &repo,
+        &drive_items_with_fuse_repo,
+        &mock_client,
+        "one\ntwo\nthree\n",
+        "one\nlocal two\nthree\n",
+        "one\nremote two\nthree\n",
+    )
+    .await?;
+
+    let remote_item = repo.get_processing_item_by_id(remote_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&remote_item)
+        .await?;
+
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::Conflicted);
+    assert_eq!(repo.get_processing_item_by_id(local_id).await?.unwrap().status, ProcessingStatus::New);
+    let local_path = app_state.file_manager().get_local_dir().join("7");
+    assert_eq!(std::fs::read_to_string(&local_path)?, "one\nlocal two\nthree\n");
+
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+    let diff = dbus_service
+        .get_conflict_diff_for_test(remote_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus get_conflict_diff failed: {}", e))?;
+    assert!(diff.contains("--- Q3_Report.pdf (local)"), "{}", diff);
+    assert!(diff.contains("+++ Q3_Report.pdf (remote)"), "{}", diff);
+    assert!(diff.contains("-local two"), "{}", diff);
+    assert!(diff.contains("+remote two"), "{}", diff);
+
+    app_state.persistency().merge_base_repository().clear_all_items().await?;
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_large_remote_version_is_not_downloaded_for_merge() -> Result<()> {
+    println!("\n🧪 Running test: A remote version too large to merge is never downloaded");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let (_local_id, remote_id) = setup_text_conflict(
+        &app_state,
+        &repo,
+        &drive_items_with_fuse_repo,
+        &mock_client,
+        "one\ntwo\nthree\n",
+        "ONE\ntwo\nthree\n",
+        "one\ntwo\nTHREE\n",
+    )
+    .await?;
+    let mut remote_item = repo.get_processing_item_by_id(remote_id).await?.unwrap();
+    remote_item.drive_item.size = Some(MAX_MERGE_SIZE + 1);
+    repo.update_processing_item(&remote_item).await?;
+    mock_client.reset_call_counters();
+
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&remote_item)
+        .await?;
+
+    assert_eq!(repo.get_processing_item_by_id(remote_id).await?.unwrap().status, ProcessingStatus::Conflicted);
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+    let diff = dbus_service.get_conflict_diff_for_test(remote_id).await;
+    assert!(diff.is_err());
+    assert_eq!(mock_client.get_call_count("download_file"), 0);
+
+    app_state.persistency().merge_base_repository().clear_all_items().await?;
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_deleted_file_drops_merge_base() -> Result<()> {
+    println!("\n🧪 Running test: Deleting a file drops its merge base");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let merge_base_repo = app_state.persistency().merge_base_repository();
+
+    // Q3 report (ino 7) deleted on OneDrive, Q4 report (ino 8) deleted locally
+    for (ino, change) in [(7, create_test_remote_processing_item as fn(_, _) -> _), (8, create_test_local_processing_item)] {
+        let item = drive_items_with_fuse_repo
+            .get_drive_item_with_fuse_by_virtual_ino(ino)
+            .await?
+            .unwrap();
+        merge_base_repo.store_base(item.id(), "base-ctag", "base").await?;
+        let item_id = repo
+            .store_processing_item(&change(item.drive_item().clone(), ChangeOperation::Delete))
+            .await?;
+        let item_to_process = repo.get_processing_item_by_id(item_id).await?.unwrap();
+        SyncProcessor::new(app_state.clone())
+            .process_single_item(&item_to_process)
+            .await?;
+        assert!(merge_base_repo.get_base(item.id()).await?.is_none(), "ino {}", ino);
+    }
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
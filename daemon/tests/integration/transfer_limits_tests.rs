@@ -0,0 +1,134 @@
+use anyhow::Result;
+use chrono::NaiveTime;
+use onedrive_sync_daemon::onedrive_service::bandwidth::RateLimiter;
+use onedrive_sync_daemon::tasks::transfers::{held_back_size, is_in_transfer_window, TransferDirection};
+use onedrive_sync_lib::config::{TransferWindow, TransferWindowDirection};
+use onedrive_sync_lib::dbus::types::{TransferLimits, TransferWindowRule};
+use serial_test::serial;
+use std::time::Duration;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+fn at(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+#[test]
+fn test_rate_limiter_spaces_transfers() {
+    println!("\n🧪 Running test: Rate limiter spaces transfers to the limit");
+    let limiter = RateLimiter::default();
+    limiter.set_rate(1000);
+
+    // The first transfer starts right away, the next ones wait for the earlier bytes
+    assert_eq!(limiter.reserve(500), Duration::ZERO);
+    let wait = limiter.reserve(500);
+    assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    let wait = limiter.reserve(1000);
+    assert!(wait > Duration::from_millis(900) && wait <= Duration::from_millis(1000));
+
+    // A new limit drops the backlog, 0 is unlimited
+    limiter.set_rate(0);
+    assert_eq!(limiter.reserve(1_000_000), Duration::ZERO);
+    limiter.set_rate(2000);
+    assert_eq!(limiter.reserve(100), Duration::ZERO);
+}
+
+#[test]
+fn test_transfer_windows() {
+    println!("\n🧪 Running test: Transfer windows hold back matching transfers");
+    // Large uploads only in the evening and at night
+    let windows = vec![TransferWindow {
+        direction: TransferWindowDirection::Upload,
+        start: "19:00".to_string(),
+        end: "07:00".to_string(),
+        min_file_size: 1000,
+    }];
+    assert!(!is_in_transfer_window(&windows, TransferDirection::Upload, 5000, at("12:00")));
+    assert!(is_in_transfer_window(&windows, TransferDirection::Upload, 5000, at("19:00")));
+    assert!(is_in_transfer_window(&windows, TransferDirection::Upload, 5000, at("02:30")));
+    assert!(!is_in_transfer_window(&windows, TransferDirection::Upload, 5000, at("07:00")));
+    // Small files and downloads are not affected
+    assert!(is_in_transfer_window(&windows, TransferDirection::Upload, 999, at("12:00")));
+    assert!(is_in_transfer_window(&windows, TransferDirection::Download, 5000, at("12:00")));
+    assert_eq!(held_back_size(&windows, TransferDirection::Upload, at("12:00")), Some(1000));
+    assert_eq!(held_back_size(&windows, TransferDirection::Upload, at("19:00")), None);
+    assert_eq!(held_back_size(&windows, TransferDirection::Download, at("12:00")), None);
+
+    let windows = vec![TransferWindow {
+        direction: TransferWindowDirection::Both,
+        start: "09:00".to_string(),
+        end: "17:00".to_string(),
+        min_file_size: 0,
+    }];
+    assert!(is_in_transfer_window(&windows, TransferDirection::Download, 1, at("09:00")));
+    assert!(!is_in_transfer_window(&windows, TransferDirection::Download, 1, at("17:00")));
+    assert!(!is_in_transfer_window(&windows, TransferDirection::Upload, 1, at("08:59")));
+    assert!(is_in_transfer_window(&[], TransferDirection::Upload, 1, at("03:00")));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_transfer_limits_over_dbus() -> Result<()> {
+    println!("\n🧪 Running test: Transfer limits are changed over D-Bus");
+    let (app_state, _repo, _drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let dbus_service = onedrive_sync_daemon::dbus_server::server::ServiceImpl::new(app_state.clone());
+
+    let limits = TransferLimits {
+        max_upload_bytes_per_sec: 250_000,
+        max_download_bytes_per_sec: 1_000_000,
+        windows: vec![TransferWindowRule {
+            direction: "Upload".to_string(),
+            start: "19:00".to_string(),
+            end: "07:00".to_string(),
+            min_file_size: 50_000_000,
+        }],
+    };
+    dbus_service
+        .set_transfer_limits_for_test(limits)
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus set_transfer_limits failed: {}", e))?;
+
+    // The running limiter and the settings follow right away
+    assert_eq!(app_state.bandwidth().upload.rate(), 250_000);
+    assert_eq!(app_state.bandwidth().download.rate(), 1_000_000);
+    let settings = app_state.config().settings.read().await.clone();
+    assert_eq!(settings.transfer_config.max_upload_bytes_per_sec, 250_000);
+    assert_eq!(settings.sync_config.transfer_windows.len(), 1);
+    assert_eq!(settings.sync_config.transfer_windows[0].direction, TransferWindowDirection::Upload);
+
+    let current = dbus_service
+        .get_transfer_limits_for_test()
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus get_transfer_limits failed: {}", e))?;
+    assert_eq!(current.max_download_bytes_per_sec, 1_000_000);
+    assert_eq!(current.windows[0].direction, "Upload");
+    assert_eq!(current.windows[0].start, "19:00");
+    assert_eq!(current.windows[0].min_file_size, 50_000_000);
+
+    // Invalid input changes nothing
+    for (direction, start) in [("Sideways", "19:00"), ("Both", "25:00"), ("Both", "7pm")] {
+        let invalid = TransferLimits {
+            max_upload_bytes_per_sec: 0,
+            max_download_bytes_per_sec: 0,
+            windows: vec![TransferWindowRule {
+                direction: direction.to_string(),
+                start: start.to_string(),
+                end: "07:00".to_string(),
+                min_file_size: 0,
+            }],
+        };
+        assert!(dbus_service.set_transfer_limits_for_test(invalid).await.is_err());
+    }
+    assert_eq!(app_state.bandwidth().upload.rate(), 250_000);
+
+    dbus_service
+        .set_transfer_limits_for_test(TransferLimits {
+            max_upload_bytes_per_sec: 0,
+            max_download_bytes_per_sec: 0,
+            windows: vec![],
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("DBus set_transfer_limits failed: {}", e))?;
+    assert_eq!(app_state.bandwidth().upload.rate(), 0);
+    Ok(())
+}
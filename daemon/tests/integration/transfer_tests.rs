@@ -0,0 +1,176 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::tasks::transfers::{TransferDirection, TransferScheduler, TransferTracker};
+use onedrive_sync_lib::config::{TransferWindow, TransferWindowDirection};
+use serial_test::serial;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[test]
+fn test_transfer_tracker_lists_running_transfers() {
+    println!("\n🧪 Running test: Transfer tracker lists running transfers");
+    let tracker = Arc::new(TransferTracker::new());
+
+    let download = tracker.start(TransferDirection::Download, "item_a", "a.jpg", 2048);
+    let upload = tracker.start(TransferDirection::Upload, "item_b", "b.txt", 10);
+    download.set_progress(1024);
+
+    let transfers = tracker.list();
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(transfers[0].name, "a.jpg");
+    assert_eq!(transfers[0].bytes_done, 1024);
+    assert_eq!(transfers[0].bytes_total, 2048);
+    assert_eq!(transfers[1].direction, TransferDirection::Upload);
+    assert!(tracker.is_active(TransferDirection::Download, "item_a"));
+    assert!(!tracker.is_active(TransferDirection::Upload, "item_a"));
+    assert_eq!(tracker.count(TransferDirection::Download), 1);
+
+    // Finished transfers disappear
+    drop(download);
+    assert!(!tracker.is_active(TransferDirection::Download, "item_a"));
+    drop(upload);
+    assert!(tracker.list().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_downloads_run_in_parallel_by_priority() -> Result<()> {
+    println!("\n🧪 Running test: Downloads run in parallel by priority");
+    let (app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+    app_state.config().settings.write().await.transfer_config.max_parallel_downloads = 2;
+
+    // Q1, Q2 and Q3 reports (ino 5 to 7), Q3 with a higher priority
+    let mut ids = Vec::new();
+    for ino in 5..=7 {
+        let item = drive_items_with_fuse_repo
+            .get_drive_item_with_fuse_by_virtual_ino(ino)
+            .await?
+            .unwrap();
+        let local_path = app_state.file_manager().get_download_dir().join(item.id());
+        download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+        ids.push(item.id().to_string());
+    }
+    sqlx::query("UPDATE download_queue SET priority = 10 WHERE drive_item_id = ?")
+        .bind(&ids[2])
+        .execute(app_state.persistency().pool())
+        .await?;
+
+    let scheduler = TransferScheduler::new(app_state.clone());
+    let started = scheduler.start_downloads().await?;
+    assert_eq!(started.len(), 2);
+    assert_eq!(started[0], ids[2]);
+
+    // The remaining item starts once a slot is free
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while !download_queue_repo.get_pending_downloads().await?.is_empty()
+        || !app_state.transfers().list().is_empty()
+    {
+        assert!(tokio::time::Instant::now() < deadline, "downloads did not finish");
+        scheduler.start_downloads().await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let local_dir = app_state.file_manager().get_local_dir();
+    for ino in 5..=7 {
+        assert_eq!(std::fs::read(local_dir.join(ino.to_string()))?, b"mock file content");
+    }
+    assert!(download_queue_repo
+        .get_all_items()
+        .await?
+        .iter()
+        .all(|item| item.status == "completed"));
+
+    app_state.config().settings.write().await.transfer_config = Default::default();
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_cancel_downloads_keeps_them_queued() -> Result<()> {
+    println!("\n🧪 Running test: Cancelled downloads stop and stay queued");
+    let (app_state, _repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+    mock_client.delay_downloads(Duration::from_secs(5));
+
+    // Q1 report (ino 5)
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    let local_path = app_state.file_manager().get_download_dir().join(item.id());
+    download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+    let started = TransferScheduler::new(app_state.clone()).start_downloads().await?;
+    assert_eq!(started.len(), 1);
+    assert_eq!(app_state.transfers().count(TransferDirection::Download), 1);
+
+    tokio::time::timeout(Duration::from_secs(1), app_state.transfers().cancel_downloads()).await?;
+    assert!(app_state.transfers().list().is_empty());
+    assert_eq!(download_queue_repo.get_pending_downloads().await?.len(), 1);
+    assert!(!app_state.file_manager().get_local_dir().join("5").exists());
+
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_closed_transfer_window_skips_large_downloads() -> Result<()> {
+    println!("\n🧪 Running test: A closed transfer window leaves large downloads queued");
+    let (app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    download_queue_repo.clear_all_items().await?;
+
+    // Q1 report (ino 5) is large and comes first, Q2 report (ino 6) is small
+    let mut ids = Vec::new();
+    for (ino, size) in [(5, 10_000_000), (6, 100)] {
+        let item = drive_items_with_fuse_repo
+            .get_drive_item_with_fuse_by_virtual_ino(ino)
+            .await?
+            .unwrap();
+        sqlx::query("UPDATE drive_items_with_fuse SET size = ? WHERE virtual_ino = ?")
+            .bind(size)
+            .bind(ino as i64)
+            .execute(app_state.persistency().pool())
+            .await?;
+        let local_path = app_state.file_manager().get_download_dir().join(item.id());
+        download_queue_repo.add_to_download_queue(item.id(), &local_path).await?;
+        ids.push(item.id().to_string());
+    }
+
+    // Only the first of the queue is read when one download can start
+    let next = download_queue_repo.get_next_pending_downloads(1, None).await?;
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].drive_item_id, ids[0]);
+    assert_eq!(next[0].size, 10_000_000);
+
+    // A download window that opens in an hour holds back files of 1MB and more
+    let now = chrono::Local::now().time();
+    app_state.config().settings.write().await.sync_config.transfer_windows = vec![TransferWindow {
+        direction: TransferWindowDirection::Download,
+        start: (now + chrono::Duration::hours(1)).format("%H:%M").to_string(),
+        end: (now + chrono::Duration::hours(2)).format("%H:%M").to_string(),
+        min_file_size: 1_000_000,
+    }];
+
+    let started = TransferScheduler::new(app_state.clone()).start_downloads().await?;
+    assert_eq!(started, vec![ids[1].clone()]);
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while !app_state.transfers().list().is_empty() {
+        assert!(tokio::time::Instant::now() < deadline, "download did not finish");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let pending = download_queue_repo.get_pending_downloads().await?;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].1, ids[0]);
+
+    app_state.config().settings.write().await.sync_config.transfer_windows.clear();
+    download_queue_repo.clear_all_items().await?;
+    Ok(())
+}
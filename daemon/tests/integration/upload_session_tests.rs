@@ -0,0 +1,104 @@
+use anyhow::Result;
+use onedrive_sync_daemon::file_manager::FileManager;
+use onedrive_sync_daemon::persistency::processing_item_repository::{ChangeOperation, ProcessingStatus};
+use onedrive_sync_daemon::persistency::upload_session_repository::UploadSession;
+use onedrive_sync_daemon::sync::SyncProcessor;
+use serial_test::serial;
+
+use crate::common::fixtures::create_test_local_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_large_upload_resumes_stored_session() -> Result<()> {
+    println!("\n🧪 Running test: Large upload resumes its stored upload session");
+    let (app_state, repo, drive_items_with_fuse_repo, mock_client) = setup_test_env().await?;
+    let sessions = app_state.persistency().upload_session_repository();
+    // The processing items are gone, so this drops sessions left by earlier tests
+    sessions.remove_stale_sessions(chrono::Utc::now()).await?;
+
+    // Q4 report (ino 8) changed locally to 5MB
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    mock_client.set_expected_drive_item(original.id().to_string(), original.drive_item().clone());
+    let local_dir = app_state.file_manager().get_local_dir();
+    std::fs::create_dir_all(&local_dir)?;
+    std::fs::write(local_dir.join("8"), vec![7u8; 5 * 1024 * 1024])?;
+    let item_id = repo
+        .store_processing_item(&create_test_local_processing_item(
+            original.drive_item().clone(),
+            ChangeOperation::Update,
+        ))
+        .await?;
+    let item = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    let sync_processor = SyncProcessor::new(app_state.clone());
+
+    // The connection drops during the upload, the session stays for the next attempt
+    mock_client.make_operation_fail("upload_to_session");
+    assert!(sync_processor.process_single_item(&item).await.is_err());
+    let session = sessions.get_session(item_id).await?.expect("the upload session is stored");
+    assert_eq!(session.upload_url, format!("mock_upload_session/{}/1", original.id()));
+    assert_eq!(session.file_size, 5 * 1024 * 1024);
+
+    // After a restart the upload continues in the same session
+    mock_client.clear_operation_failures();
+    sync_processor.process_single_item(&item).await?;
+    assert_eq!(mock_client.resumed_upload_urls(), vec![session.upload_url.clone()]);
+    assert_eq!(mock_client.get_call_count("create_upload_session"), 1);
+    assert!(sessions.get_session(item_id).await?.is_none());
+    assert_eq!(repo.get_processing_item_by_id(item_id).await?.unwrap().status, ProcessingStatus::Done);
+
+    repo.clear_all_items().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_stale_upload_sessions_are_removed() -> Result<()> {
+    println!("\n🧪 Running test: Expired and orphaned upload sessions are removed");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+    let sessions = app_state.persistency().upload_session_repository();
+    // The processing items are gone, so this drops sessions left by earlier tests
+    sessions.remove_stale_sessions(chrono::Utc::now()).await?;
+
+    let original = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(8)
+        .await?
+        .unwrap();
+    let session_for = |processing_item_id: i64, expiration: &str| UploadSession {
+        processing_item_id,
+        drive_item_id: original.id().to_string(),
+        upload_url: format!("https://upload/{}", processing_item_id),
+        expiration: expiration.to_string(),
+        file_size: 1_000_000,
+        file_modified: 0,
+        committed_ranges: vec![],
+    };
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        ids.push(
+            repo.store_processing_item(&create_test_local_processing_item(
+                original.drive_item().clone(),
+                ChangeOperation::Update,
+            ))
+            .await?,
+        );
+    }
+    let mut current = session_for(ids[0], "2099-01-01T00:00:00.000Z");
+    current.committed_ranges = vec![(0, 327_679)];
+    sessions.store_session(&current).await?;
+    sessions.store_session(&session_for(ids[1], "2020-01-01T00:00:00Z")).await?;
+    sessions.store_session(&session_for(ids[1] + 1000, "2099-01-01T00:00:00Z")).await?;
+
+    assert_eq!(sessions.remove_stale_sessions(chrono::Utc::now()).await?, 2);
+    let kept = sessions.get_session(ids[0]).await?.unwrap();
+    assert_eq!(kept.committed_ranges, vec![(0, 327_679)]);
+    assert_eq!(kept.committed_bytes(), 327_680);
+    assert!(sessions.get_session(ids[1]).await?.is_none());
+
+    repo.clear_all_items().await?;
+    sessions.remove_stale_sessions(chrono::Utc::now()).await?;
+    Ok(())
+}
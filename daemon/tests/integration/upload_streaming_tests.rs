@@ -0,0 +1,134 @@
+use anyhow::Result;
+use onedrive_sync_daemon::auth::onedrive_auth::OneDriveAuth;
+use onedrive_sync_daemon::onedrive_service::bandwidth::BandwidthLimiter;
+use onedrive_sync_daemon::onedrive_service::onedrive_client::{OneDriveClient, OneDriveClientTrait};
+use onedrive_sync_daemon::onedrive_service::onedrive_models::{
+    UploadProgress, UploadProgressFn, UploadSessionConfig,
+};
+use futures_util::future::{self, FutureExt};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Chunks received by the fake upload session: Content-Range and body
+type ReceivedChunks = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+/// Serve one connection of an upload session that already has the first `received` bytes
+async fn serve_upload_session(stream: TcpStream, received: u64, total: u64, chunks: ReceivedChunks) -> Result<()> {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if stream.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let mut content_length = 0;
+        let mut content_range = String::new();
+        loop {
+            let mut header = String::new();
+            stream.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap_or((header, ""));
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse()?,
+                "content-range" => content_range = value.trim().to_string(),
+                _ => {}
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        stream.read_exact(&mut body).await?;
+
+        let (status, response) = if request_line.starts_with("GET") {
+            (
+                "200 OK",
+                format!(
+                    r#"{{"expirationDateTime":"2030-01-01T00:00:00Z","nextExpectedRanges":["{}-"]}}"#,
+                    received
+                ),
+            )
+        } else {
+            let last = content_range.ends_with(&format!("-{}/{}", total - 1, total));
+            chunks.lock().unwrap().push((content_range, body));
+            if last {
+                ("201 Created", r#"{"id":"uploaded_id","eTag":"etag_1","size":1000000}"#.to_string())
+            } else {
+                ("202 Accepted", r#"{"nextExpectedRanges":[]}"#.to_string())
+            }
+        };
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            status,
+            response.len()
+        );
+        stream.get_mut().write_all(head.as_bytes()).await?;
+        stream.get_mut().write_all(response.as_bytes()).await?;
+    }
+}
+
+#[tokio::test]
+async fn test_resume_upload_streams_missing_chunks_from_disk() -> Result<()> {
+    println!("\n🧪 Running test: Resumed upload streams the missing chunks from the local file");
+    const TOTAL: u64 = 1_000_000;
+    const RECEIVED: u64 = 327_680;
+    let data: Vec<u8> = (0..TOTAL).map(|i| (i % 251) as u8).collect();
+    let file = tempfile::NamedTempFile::new()?;
+    std::fs::write(file.path(), &data)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let upload_url = format!("http://{}/upload-session", listener.local_addr()?);
+    let chunks: ReceivedChunks = Arc::new(Mutex::new(Vec::new()));
+    let server_chunks = chunks.clone();
+    let server = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_upload_session(stream, RECEIVED, TOTAL, server_chunks.clone()));
+        }
+    });
+
+    let reports: Arc<Mutex<Vec<UploadProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let progress_reports = reports.clone();
+    let progress: UploadProgressFn = Arc::new(move |progress: &UploadProgress| {
+        progress_reports.lock().unwrap().push(progress.clone());
+        future::ready(()).boxed()
+    });
+
+    let client = OneDriveClient::new(
+        Arc::new(OneDriveAuth::new().await?),
+        Arc::new(BandwidthLimiter::default()),
+    )?;
+    let config = UploadSessionConfig {
+        chunk_size: 327_680,
+    };
+    let item = OneDriveClientTrait::resume_large_file_upload(
+        &client,
+        &upload_url,
+        file.path(),
+        Some(config),
+        Some(progress),
+    )
+    .await?;
+    server.abort();
+    assert_eq!(item.id, "uploaded_id");
+
+    // Only the missing part is sent, in chunks of the configured size
+    let chunks = chunks.lock().unwrap().clone();
+    let ranges: Vec<&str> = chunks.iter().map(|(range, _)| range.as_str()).collect();
+    assert_eq!(
+        ranges,
+        vec![
+            "bytes 327680-655359/1000000",
+            "bytes 655360-983039/1000000",
+            "bytes 983040-999999/1000000",
+        ]
+    );
+    let uploaded: Vec<u8> = chunks.into_iter().flat_map(|(_, body)| body).collect();
+    assert_eq!(uploaded, data[RECEIVED as usize..]);
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].bytes_uploaded, 655_360);
+    assert_eq!(reports[2].bytes_uploaded, TOTAL);
+    assert_eq!(reports[2].chunks_completed, reports[2].total_chunks);
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use onedrive_sync_daemon::fuse::xattr::{
+    parse_pin_value, xattr_list, xattr_value, XATTR_ETAG, XATTR_HYDRATED, XATTR_ID, XATTR_PIN,
+    XATTR_WEB_URL,
+};
+use onedrive_sync_daemon::persistency::processing_item_repository::ChangeOperation;
+use onedrive_sync_daemon::sync::SyncProcessor;
+use serial_test::serial;
+
+use crate::common::fixtures::create_test_remote_processing_item;
+use crate::integration::processing_item_tests::setup_test_env;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_xattrs_expose_drive_item_metadata() -> Result<()> {
+    println!("\n🧪 Running test: xattrs expose drive item metadata");
+    let (_app_state, _repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let mut item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    item.drive_item_mut().web_url = Some("https://onedrive.live.com/view/5".to_string());
+    drive_items_with_fuse_repo
+        .store_drive_item_with_fuse(&item)
+        .await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+
+    assert_eq!(
+        xattr_value(XATTR_ID, &item, false, false),
+        Some(item.drive_item().id.clone().into_bytes())
+    );
+    assert_eq!(
+        xattr_value(XATTR_ETAG, &item, false, false),
+        item.drive_item().etag.clone().map(String::into_bytes)
+    );
+    assert_eq!(
+        xattr_value(XATTR_WEB_URL, &item, false, false),
+        Some(b"https://onedrive.live.com/view/5".to_vec())
+    );
+    assert_eq!(xattr_value(XATTR_HYDRATED, &item, true, false), Some(b"1".to_vec()));
+    assert_eq!(xattr_value(XATTR_PIN, &item, false, false), None);
+    assert_eq!(xattr_value(XATTR_PIN, &item, false, true), Some(b"1".to_vec()));
+
+    let list = xattr_list(&item, false, false);
+    let names: Vec<&[u8]> = list.split(|b| *b == 0).filter(|n| !n.is_empty()).collect();
+    assert!(names.contains(&XATTR_WEB_URL.as_bytes()));
+    assert!(!names.contains(&XATTR_PIN.as_bytes()));
+
+    assert_eq!(parse_pin_value(b"1\n"), Some(true));
+    assert_eq!(parse_pin_value(b"0"), Some(false));
+    assert_eq!(parse_pin_value(b"maybe"), None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[serial]
+async fn test_pinned_file_is_queued_on_remote_update() -> Result<()> {
+    println!("\n🧪 Running test: Pinned file is queued for download on remote update");
+    let (app_state, repo, drive_items_with_fuse_repo, _mock_client) = setup_test_env().await?;
+
+    let pinned_repo = app_state.persistency().pinned_item_repository();
+    let download_queue_repo = app_state.persistency().download_queue_repository();
+    pinned_repo.clear_all_items().await?;
+    download_queue_repo.clear_all_items().await?;
+
+    let item = drive_items_with_fuse_repo
+        .get_drive_item_with_fuse_by_virtual_ino(5)
+        .await?
+        .unwrap();
+    pinned_repo.pin(5).await?;
+    assert!(
+        pinned_repo
+            .is_pinned_by_onedrive_id(&item.drive_item().id)
+            .await?
+    );
+
+    let mut di = item.drive_item().clone();
+    di.etag = Some("pinned-etag-2".to_string());
+    let processing_item = create_test_remote_processing_item(di, ChangeOperation::Update);
+    let item_id = repo.store_processing_item(&processing_item).await?;
+    let item_to_process = repo.get_processing_item_by_id(item_id).await?.unwrap();
+    SyncProcessor::new(app_state.clone())
+        .process_single_item(&item_to_process)
+        .await?;
+
+    let queued = download_queue_repo.get_all_items().await?;
+    assert!(queued
+        .iter()
+        .any(|q| q.onedrive_id == item.drive_item().id));
+
+    pinned_repo.unpin(5).await?;
+    assert!(!pinned_repo.is_pinned(5).await?);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result, anyhow};
 use log::warn;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tokio::sync::RwLock;
@@ -70,11 +71,138 @@ impl ProjectConfig {
 pub struct Settings {
     /// List of OneDrive folders to sync
     pub download_folders: Vec<String>,
+    /// Include/exclude patterns refining `download_folders`
+    #[serde(default)]
+    pub selective_sync: SelectiveSync,
+    /// Files created through the mount that are never uploaded
+    #[serde(default)]
+    pub local_ignore: LocalIgnore,
     pub sync_config: SyncConfig,
+    /// Parallel downloads and uploads and their rate limits
+    #[serde(default)]
+    pub transfer_config: TransferConfig,
+    /// What happens to bulk transfers on a metered connection or on battery
+    #[serde(default)]
+    pub transfer_policy: TransferPolicy,
     /// Conflict resolution strategy
     pub conflict_resolution_strategy: ConflictResolutionStrategy,
+    /// Strategies for single folders (and their subfolders), keyed like `download_folders`
+    #[serde(default)]
+    pub conflict_resolution_overrides: HashMap<String, ConflictResolutionStrategy>,
     /// Whether sync is currently paused
     pub sync_paused: bool,
+    /// Options of the FUSE mount
+    #[serde(default)]
+    pub mount_config: MountConfig,
+    /// Never use the network: serve downloaded files and stored metadata only
+    #[serde(default)]
+    pub offline: bool,
+}
+
+/// Gitignore-style patterns on paths relative to the drive root, e.g. `Projects/**`,
+/// `**/node_modules/**`, `*.tmp` or `~$*`. A pattern without a `/` matches the name at any
+/// depth, a matching folder matches everything below it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SelectiveSync {
+    /// Files matching one of these patterns are downloaded automatically
+    pub include: Vec<String>,
+    /// Items matching one of these patterns are never downloaded automatically, even if included
+    pub exclude: Vec<String>,
+    /// Leave excluded items out of the mount entirely
+    pub hide_excluded: bool,
+}
+
+/// Editor swap, lock and partial download files that are ignored by default
+pub const DEFAULT_LOCAL_IGNORE_PATTERNS: &[&str] = &[
+    "*.swp",
+    "*.swo",
+    "*.swx",
+    "4913",
+    ".~lock.*#",
+    "~lock.*#",
+    "~$*",
+    ".#*",
+    "*~",
+    "*.part",
+    "*.crdownload",
+    ".goutputstream-*",
+];
+
+/// Patterns (like `SelectiveSync`) for files created through the mount that stay in the
+/// local folder only. Items already on OneDrive keep syncing even if they match.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LocalIgnore {
+    /// Also ignore `DEFAULT_LOCAL_IGNORE_PATTERNS`
+    pub use_defaults: bool,
+    /// Additional patterns
+    pub patterns: Vec<String>,
+}
+
+impl Default for LocalIgnore {
+    fn default() -> Self {
+        Self {
+            use_defaults: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl LocalIgnore {
+    /// All patterns in effect, the defaults first
+    pub fn effective_patterns(&self) -> Vec<String> {
+        let defaults = if self.use_defaults { DEFAULT_LOCAL_IGNORE_PATTERNS } else { &[] };
+        defaults
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(self.patterns.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MountConfig {
+    /// Reject every change made through the mount (EROFS)
+    pub read_only: bool,
+    /// Block fsync() until the upload of the file to OneDrive is confirmed
+    pub fsync_waits_for_upload: bool,
+    /// How files that are not downloaded yet are shown in the mount
+    pub placeholder_mode: PlaceholderMode,
+    /// How long the kernel may cache name lookups
+    pub entry_ttl_seconds: u64,
+    /// How long the kernel may cache file attributes
+    pub attr_ttl_seconds: u64,
+    /// Keep the kernel page cache of downloaded files between opens
+    pub keep_cache: bool,
+    /// Let the kernel buffer writes (writeback caching)
+    pub writeback_cache: bool,
+}
+
+impl Default for MountConfig {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            fsync_waits_for_upload: false,
+            placeholder_mode: PlaceholderMode::default(),
+            entry_ttl_seconds: 1,
+            attr_ttl_seconds: 1,
+            keep_cache: true,
+            writeback_cache: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum PlaceholderMode {
+    /// Listed as `<name>.onedrivedownload`, read on demand
+    #[default]
+    Suffix,
+    /// Listed with their real name, downloaded completely when opened
+    HydrateOnOpen,
+    /// Listed with their real name, read on demand; `user.onedrive.hydrated` tells them apart
+    Xattr,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -82,6 +210,12 @@ pub struct SyncConfig {
     pub sync_interval_seconds: u64,
     pub max_retry_count: u32,
     pub enable_notifications: bool,
+    /// Times of day some transfers are limited to, e.g. large uploads after 19:00
+    #[serde(default)]
+    pub transfer_windows: Vec<TransferWindow>,
+    /// Sync as soon as Graph reports a change instead of waiting for the next poll (opt-in)
+    #[serde(default)]
+    pub push_notifications: bool,
 }
 
 impl Default for SyncConfig {
@@ -90,13 +224,92 @@ impl Default for SyncConfig {
             sync_interval_seconds: 30,
             max_retry_count: 3,
             enable_notifications: true,
+            transfer_windows: Vec::new(),
+            push_notifications: false,
         }
     }
 }
 
+/// Transfers a window applies to run only while it is open. Transfers no window applies to
+/// run any time.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransferWindow {
+    pub direction: TransferWindowDirection,
+    /// Local time the window opens, "HH:MM"
+    pub start: String,
+    /// Local time the window closes, "HH:MM"; earlier than `start` runs past midnight
+    pub end: String,
+    /// Smaller files are not held back by the window (0 = all files)
+    #[serde(default)]
+    pub min_file_size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TransferWindowDirection {
+    Upload,
+    Download,
+    Both,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TransferConfig {
+    /// Files downloaded at the same time
+    pub max_parallel_downloads: usize,
+    /// Files uploaded at the same time
+    pub max_parallel_uploads: usize,
+    /// Upload rate limit in bytes per second (0 = unlimited)
+    pub max_upload_bytes_per_sec: u64,
+    /// Download rate limit in bytes per second (0 = unlimited)
+    pub max_download_bytes_per_sec: u64,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            max_parallel_downloads: 4,
+            max_parallel_uploads: 2,
+            max_upload_bytes_per_sec: 0,
+            max_download_bytes_per_sec: 0,
+        }
+    }
+}
+
+/// Background downloads and uploads only; delta sync and files opened through the mount
+/// are never held back
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TransferPolicy {
+    pub on_metered: ConstrainedAction,
+    pub on_battery: ConstrainedAction,
+}
+
+impl Default for TransferPolicy {
+    fn default() -> Self {
+        Self {
+            on_metered: ConstrainedAction::Pause,
+            on_battery: ConstrainedAction::Throttle,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ConstrainedAction {
+    /// Transfer as usual
+    Ignore,
+    /// One download and one upload at a time
+    Throttle,
+    /// No background transfers until the condition ends
+    Pause,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum ConflictResolutionStrategy {
     Manual, // Wait for user decision
+    KeepBoth, // Keep the remote version, rename the local one to `name (conflict <host> <date>).ext`
+    NewestWins, // Keep the version with the newer lastModifiedDateTime
+    RemoteWins, // Always keep the OneDrive version
+    LocalWins, // Always keep the local version
 }
 
 impl Default for ConflictResolutionStrategy {
@@ -106,6 +319,26 @@ impl Default for ConflictResolutionStrategy {
 }
 
 impl Settings {
+    /// Conflict resolution strategy for an item in a folder (path relative to the drive root).
+    /// The override of the deepest matching folder wins over the global strategy. Folder
+    /// names are compared case-insensitively, like OneDrive does.
+    pub fn conflict_strategy_for(&self, folder_path: &str) -> ConflictResolutionStrategy {
+        let folder_path = folder_path.trim_matches('/').to_lowercase();
+        self.conflict_resolution_overrides
+            .iter()
+            .filter(|(folder, _)| {
+                let folder = folder.trim_matches('/').to_lowercase();
+                folder_path == folder
+                    || folder.is_empty()
+                    || folder_path
+                        .strip_prefix(folder.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(folder, _)| folder.trim_matches('/').len())
+            .map(|(_, strategy)| *strategy)
+            .unwrap_or(self.conflict_resolution_strategy)
+    }
+
     pub async fn new(config_file_path: &PathBuf) -> Result<Self> {
         match Self::load_settings_from_file(&config_file_path) {
             Ok(settings) => Ok(settings),
@@ -15,6 +15,11 @@ pub struct DaemonStatus {
     pub sync_status: SyncStatus,
     pub has_conflicts: bool,
     pub is_mounted: bool,
+    pub quota_total: u64,
+    pub quota_used: u64,
+    pub quota_remaining: u64,
+    /// Why background transfers are paused or throttled, e.g. "Paused: metered connection"; empty if they run normally
+    pub transfer_pause_reason: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Type)]
@@ -36,6 +41,8 @@ pub struct UserProfile {
 pub enum UserChoice {
     KeepLocal,
     UseRemote,
+    /// Keep the remote version under the original name and the local one as a conflict copy
+    KeepBoth,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Type)]
@@ -64,3 +71,68 @@ pub struct MediaItem {
     pub created_date: String,
     pub last_modified: String,
 } 
+
+/// Selective sync patterns, see `config::SelectiveSync`
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type, PartialEq)]
+pub struct SyncRules {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub hide_excluded: bool,
+}
+
+/// An item matched by selective sync rules
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct SyncRuleMatch {
+    pub path: String,
+    pub is_folder: bool,
+    /// Downloaded automatically (files only)
+    pub included: bool,
+    /// Matches an exclude pattern
+    pub excluded: bool,
+    /// Left out of the mount
+    pub hidden: bool,
+}
+
+/// A download or upload in flight
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct TransferItem {
+    pub transfer_id: u64,
+    pub onedrive_id: String,
+    pub name: String,
+    pub direction: String, // "Download" or "Upload"
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Rate limits and time-of-day windows for transfers
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct TransferLimits {
+    /// Bytes per second, 0 for unlimited
+    pub max_upload_bytes_per_sec: u64,
+    /// Bytes per second, 0 for unlimited
+    pub max_download_bytes_per_sec: u64,
+    pub windows: Vec<TransferWindowRule>,
+}
+
+/// Limits matching transfers to a time of day
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct TransferWindowRule {
+    pub direction: String, // "Upload", "Download" or "Both"
+    /// Local time, "HH:MM"
+    pub start: String,
+    /// Local time, "HH:MM"; earlier than `start` runs past midnight
+    pub end: String,
+    /// Smaller files are not held back (0 = all files)
+    pub min_file_size: u64,
+}
+
+/// Outcome of refreshing a path with `SyncPath`
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+pub struct SyncPathResult {
+    /// Items whose metadata was fetched again
+    pub refreshed: u32,
+    /// Items that changed on OneDrive
+    pub changed: u32,
+    /// Files queued for download again
+    pub queued_downloads: u32,
+}
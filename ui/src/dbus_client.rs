@@ -111,6 +111,20 @@ impl DbusClient {
         info!("Successfully resolved conflict for item {}", db_id);
         Ok(())
     }
+
+    /// Get a unified diff from the local to the remote version of a conflicted file
+    pub async fn get_conflict_diff(&self, db_id: i64) -> Result<String> {
+        info!("Fetching conflict diff for item {}", db_id);
+        let proxy = self.get_proxy().await?;
+
+        let diff = proxy
+            .call_method("GetConflictDiff", &(db_id,))
+            .await?
+            .body()
+            .deserialize::<String>()?;
+
+        Ok(diff)
+    }
     
     /// Get the user profile from the daemon
     pub async fn get_user_profile(&self) -> Result<UserProfile> {
@@ -315,6 +329,19 @@ impl DbusClient {
         info!("Sync pause toggled: {}", if result { "paused" } else { "resumed" });
         Ok(result)
     }
+
+    /// Start a sync cycle now; false if one is already running
+    pub async fn sync_now(&self) -> Result<bool> {
+        info!("Requesting a sync cycle");
+        let proxy = self.get_proxy().await?;
+
+        let started = proxy
+            .call_method("SyncNow", &())
+            .await?
+            .body()
+            .deserialize::<bool>()?;
+        Ok(started)
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,8 @@ use onedrive_sync_lib::dbus::types::{ConflictItem, UserChoice};
 pub enum Message {
     Resolve { db_id: i64, choice: UserChoice },
     Resolved(Result<(), String>),
+    ShowDiff(i64),
+    DiffLoaded(i64, Result<String, String>),
     Loaded(Result<Vec<ConflictItem>, String>),
     Reload,
     AutoRefresh,
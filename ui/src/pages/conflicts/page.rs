@@ -7,17 +7,21 @@ use cosmic::{
 use cosmic::widget::text;
 use onedrive_sync_lib::dbus::types::{ConflictItem, UserChoice};
 use crate::dbus_client::{self, DbusClient};
+use std::collections::HashMap;
 use std::time::Duration;
 use super::message::Message;
 
 pub struct ConflictsPage {
     conflicts: Result<Vec<ConflictItem>, String>,
+    /// Diffs loaded for conflicts, by db_id
+    diffs: HashMap<i64, Result<String, String>>,
 }
 
 impl ConflictsPage {
     pub fn new() -> Self {
         Self {
             conflicts: Err("Loading...".into()),
+            diffs: HashMap::new(),
         }
     }
 
@@ -39,6 +43,10 @@ impl ConflictsPage {
                 })
             }
             Message::Loaded(conflicts) => {
+                // Forget diffs of conflicts that are gone
+                if let Ok(conflicts) = &conflicts {
+                    self.diffs.retain(|db_id, _| conflicts.iter().any(|c| c.db_id == *db_id));
+                }
                 self.conflicts = conflicts;
                 cosmic::Task::none()
             }
@@ -69,6 +77,21 @@ impl ConflictsPage {
                 self.conflicts = Err(e);
                 cosmic::Task::none()
             }
+            Message::ShowDiff(db_id) => {
+                let load_diff = async move {
+                    dbus_client::with_dbus_client(move |client: DbusClient| async move {
+                        client.get_conflict_diff(db_id).await
+                    })
+                    .await
+                };
+                cosmic::task::future(load_diff).map(move |result| {
+                    cosmic::Action::App(crate::app::Message::ConflictsPage(Message::DiffLoaded(db_id, result)))
+                })
+            }
+            Message::DiffLoaded(db_id, diff) => {
+                self.diffs.insert(db_id, diff);
+                cosmic::Task::none()
+            }
         }
     }
 
@@ -145,7 +168,34 @@ impl ConflictsPage {
                                     choice: UserChoice::UseRemote 
                                 })
                             )
+                            .push(
+                                button("Keep Both").on_press(Message::Resolve {
+                                    db_id: first_item.db_id,
+                                    choice: UserChoice::KeepBoth
+                                })
+                            )
+                            .push(
+                                button("Show Changes").on_press(Message::ShowDiff(first_item.db_id))
+                            )
                     );
+
+                    // Unified diff of local vs remote version, once loaded
+                    match self.diffs.get(&first_item.db_id) {
+                        Some(Ok(diff)) if diff.is_empty() => {
+                            conflict_group = conflict_group.push(text::body("Both versions have the same content.").size(14));
+                        }
+                        Some(Ok(diff)) => {
+                            conflict_group = conflict_group.push(
+                                container(text::monotext(diff.clone()).size(13))
+                                    .padding(10)
+                                    .width(Length::Fill)
+                            );
+                        }
+                        Some(Err(e)) => {
+                            conflict_group = conflict_group.push(text::body(format!("No changes available: {}", e)).size(14));
+                        }
+                        None => {}
+                    }
                     
                     col.push(container(conflict_group).padding(15).class(style::Container::Card))
                 });
@@ -13,5 +13,6 @@ pub enum Message {
     RequestFullResetDialog,
     ConfirmReset,
     ToggleSyncPause,
+    SyncNow,
 }
 
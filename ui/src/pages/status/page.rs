@@ -256,6 +256,37 @@ impl Page {
                     )))
                 })
             }
+
+            Message::SyncNow => {
+                info!("StatusPage: Sync now requested");
+                self.loading = true;
+                self.error = None;
+
+                let sync_now = async move {
+                    match DbusClient::new().await {
+                        Ok(client) => match client.sync_now().await {
+                            Ok(started) => {
+                                if !started {
+                                    info!("StatusPage: A sync cycle is already running");
+                                }
+                                // The status shows the running cycle
+                                match client.get_daemon_status().await {
+                                    Ok(status) => Ok(status),
+                                    Err(e) => Err(format!("Failed to get daemon status after sync request: {}", e)),
+                                }
+                            },
+                            Err(e) => Err(format!("Failed to start sync: {}", e)),
+                        },
+                        Err(e) => Err(format!("Failed to connect to daemon: {}", e)),
+                    }
+                };
+
+                cosmic::task::future(sync_now).map(|result| {
+                    cosmic::Action::App(crate::app::Message::StatusPage(Message::StatusLoaded(
+                        result,
+                    )))
+                })
+            }
         }
     }
 }
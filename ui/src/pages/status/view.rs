@@ -34,6 +34,7 @@ impl Page {
         // Action buttons row with better styling
         let action_buttons = row()
             .spacing(spacing.space_s)
+            .push(button::suggested("Sync now").on_press(Message::SyncNow))
             .push(button::standard("Pause/Resume Sync").on_press(Message::ToggleSyncPause))
             .push(button::destructive("Full Reset").on_press(Message::RequestFullResetDialog));
 
@@ -116,6 +117,17 @@ impl Page {
                 .push(self.create_status_row("Conflicts", !status.has_conflicts))
                 .push(widget::divider::horizontal::default())
                 .push(self.create_status_row("Mounted", status.is_mounted))
+                .push(widget::divider::horizontal::default())
+                .push(self.create_storage_row(status.quota_used, status.quota_total))
+                .push(widget::divider::horizontal::default())
+                .push(self.create_profile_row(
+                    "Transfers",
+                    if status.transfer_pause_reason.is_empty() {
+                        "Running"
+                    } else {
+                        &status.transfer_pause_reason
+                    },
+                ))
         } else {
             column()
                 .spacing(spacing.space_s)
@@ -181,6 +193,27 @@ impl Page {
             .into()
     }
 
+    fn create_storage_row<'a>(&self, used: u64, total: u64) -> cosmic::Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let value = if total > 0 {
+            format!("{} of {} used", format_bytes(used), format_bytes(total))
+        } else {
+            "Unknown".to_string()
+        };
+
+        row()
+            .spacing(spacing.space_s)
+            .align_y(Alignment::Center)
+            .padding([spacing.space_xs, spacing.space_none])
+            .push(
+                text::body("Storage")
+                    .width(Length::Fixed(120.0))
+            )
+            .push(text::body(value))
+            .into()
+    }
+
     fn create_profile_row<'a>(&self, label: &'a str, value: &'a str) -> cosmic::Element<'a, Message> {
         let spacing = cosmic::theme::active().cosmic().spacing;
         
@@ -197,3 +230,17 @@ impl Page {
     }
 }
 
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}